@@ -1,9 +1,179 @@
 use anchor_lang::prelude::*;
 
 /// defining the constants for the anchor program
+///
+/// `NAME`, `URI` and `SYMBOL` are the defaults clients pass as the `name`,
+/// `uri` and `symbol` of `ConfigParams`.
 #[constant]
 pub const NAME: &str = "Token Lottery Ticket #";
 #[constant]
 pub const URI: &str = "Token Lottery";
 #[constant]
 pub const SYMBOL: &str = "TICKET";
+/// Prefix of winner badge names, see `TokenLottery::badge_name`.
+#[constant]
+pub const BADGE_NAME: &str = "Winner #";
+
+/// Number of tickets per round verified into the sized Metaplex collection.
+///
+/// Metaplex tracks the collection size on the collection metadata and every
+/// verification rewrites it, so the program stops tracking tickets past this
+/// point instead of growing the collection without bound. Later tickets are
+/// still sold and stay valid for claims: a ticket is authenticated by its mint
+/// being the program-derived ticket mint for its index, which the collection
+/// check only duplicates.
+#[constant]
+pub const MAX_COLLECTION_SIZE: u64 = 100_000;
+
+/// Upper bound on tickets per lottery. `ticket_num` is used as the ticket
+/// mint seed, so a corrupted counter must never reach the seed derivation.
+#[constant]
+pub const MAX_TICKET_NUM: u64 = 1_000_000;
+
+/// Highest round a recurring lottery can reach. Ticket names of later rounds
+/// carry the round number, see `TokenLottery::ticket_name`, and must still
+/// fit the Metaplex name limit for every ticket number.
+#[constant]
+pub const MAX_ROUND_NUMBER: u64 = 999;
+
+/// Number of slots a committed randomness account has to be revealed in.
+/// Switchboard reveals against the seed slot's hash, which is only kept in
+/// the `SlotHashes` sysvar for this many slots.
+#[constant]
+pub const RANDOMNESS_VALIDITY_SLOTS: u64 = 512;
+
+/// Maximum number of tickets bought in one `buy_multiple_tickets` call.
+///
+/// Every ticket creates a mint, token account, metadata, master edition and
+/// `TicketRecord`, around 150k compute units, so eight tickets fit the
+/// 1.4M unit transaction limit. Its five accounts per ticket also mean more
+/// than three tickets need an address lookup table to fit the transaction.
+#[constant]
+pub const MAX_BATCH_TICKETS: u8 = 8;
+
+/// Accounts passed per ticket to `buy_multiple_tickets`: the ticket mint,
+/// the buyer's token account, the `TicketRecord`, the metadata and the
+/// master edition.
+pub const BATCH_TICKET_ACCOUNTS: usize = 5;
+
+/// Basis points representing 100%.
+#[constant]
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Maximum number of treasuries the ticket fee can be split between.
+#[constant]
+pub const MAX_TREASURIES: u8 = 4;
+
+/// Maximum number of winners drawn per round, the length of the winner arrays
+/// on `TokenLottery`.
+pub const MAX_WINNERS: usize = 4;
+
+/// Maximum number of SPL tokens accepted for tickets alongside SOL.
+#[constant]
+pub const MAX_PAYMENT_TOKENS: u8 = 4;
+
+/// `payment_index` paying for a ticket in SOL. Index `i + 1` selects the
+/// `i`th configured payment token.
+#[constant]
+pub const PAYMENT_INDEX_SOL: u8 = 0;
+
+/// Decimals of SOL amounts, which are in lamports.
+#[constant]
+pub const SOL_DECIMALS: u8 = 9;
+
+/// Fee mode paying the ticket fee to the treasuries on every purchase.
+#[constant]
+pub const FEE_MODE_IMMEDIATE: u8 = 0;
+
+/// Fee mode holding the ticket fee on the lottery until `withdraw_fees`.
+#[constant]
+pub const FEE_MODE_DEFERRED: u8 = 1;
+
+/// Max supply passed to every master edition the program creates.
+/// Tickets and the collection must stay non-printable 1/1 NFTs: a nonzero
+/// supply would let prints of a ticket be minted and used to claim the pot.
+pub const TICKET_MAX_SUPPLY: Option<u64> = Some(0);
+
+/// Size of a Metaplex metadata account, as allocated by `create_metadata_accounts_v3`.
+pub const MAX_METADATA_LEN: usize = 679;
+
+/// Size of a Metaplex master edition account, as allocated by `create_master_edition_v3`.
+pub const MAX_MASTER_EDITION_LEN: usize = 282;
+
+/// Size of a Metaplex collection authority record, as allocated by `approve_collection_authority`.
+pub const COLLECTION_AUTHORITY_RECORD_LEN: usize = 35;
+
+/// Metaplex limit on the length of an NFT name.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Metaplex limit on the length of an NFT symbol.
+pub const MAX_SYMBOL_LENGTH: usize = 10;
+
+/// Metaplex limit on the length of an NFT URI.
+pub const MAX_URI_LENGTH: usize = 200;
+
+/// Returns the number of decimal digits of `value`.
+const fn digits(mut value: u64) -> usize {
+    let mut digits = 1;
+    while value >= 10 {
+        value /= 10;
+        digits += 1;
+    }
+    digits
+}
+
+/// Returns `true` if a ticket name built from `prefix` fits Metaplex's name
+/// length limit for every ticket number below `MAX_TICKET_NUM`, in every
+/// round up to `MAX_ROUND_NUMBER`.
+pub const fn fits_name(prefix: &str) -> bool {
+    // `<prefix><round>-<ticket>`, see `TokenLottery::ticket_name`
+    prefix.len() + digits(MAX_ROUND_NUMBER) + 1 + digits(MAX_TICKET_NUM - 1) <= MAX_NAME_LENGTH
+}
+
+/// Returns `true` if the winner badge names of lottery `lottery_id` fit
+/// Metaplex's name length limit for every ticket number below
+/// `MAX_TICKET_NUM`, in every round up to `MAX_ROUND_NUMBER`.
+pub const fn fits_badge_name(lottery_id: u64) -> bool {
+    // `<BADGE_NAME><lottery_id>-<round>-<ticket>`, see `TokenLottery::badge_name`
+    BADGE_NAME.len()
+        + digits(lottery_id)
+        + 1
+        + digits(MAX_ROUND_NUMBER)
+        + 1
+        + digits(MAX_TICKET_NUM - 1)
+        <= MAX_NAME_LENGTH
+}
+
+/// Returns `true` if `symbol` fits Metaplex's symbol length limit.
+///
+/// Checked at compile time, so an over-long symbol fails the build:
+///
+/// ```compile_fail
+/// const _: () = assert!(anchor::constants::fits_symbol("OVERLONG_SYMBOL"));
+/// ```
+pub const fn fits_symbol(symbol: &str) -> bool {
+    symbol.len() <= MAX_SYMBOL_LENGTH
+}
+
+/// Returns `true` if `uri` fits Metaplex's URI length limit.
+pub const fn fits_uri(uri: &str) -> bool {
+    uri.len() <= MAX_URI_LENGTH
+}
+
+/// Returns `true` if a ticket URI built from `base_uri` fits Metaplex's URI
+/// length limit for every ticket number below `MAX_TICKET_NUM`.
+pub const fn fits_base_uri(base_uri: &str) -> bool {
+    // `<base_uri>/<ticket>.json`, see `TokenLottery::ticket_uri`
+    base_uri.len() + 1 + digits(MAX_TICKET_NUM - 1) + ".json".len() <= MAX_URI_LENGTH
+}
+
+const _: () = assert!(fits_name(NAME), "NAME exceeds the Metaplex name limit");
+const _: () = assert!(
+    fits_badge_name(0),
+    "BADGE_NAME exceeds the Metaplex name limit"
+);
+const _: () = assert!(
+    fits_symbol(SYMBOL),
+    "SYMBOL exceeds the Metaplex symbol limit"
+);
+const _: () = assert!(fits_uri(URI), "URI exceeds the Metaplex URI limit");
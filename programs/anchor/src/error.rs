@@ -23,4 +23,144 @@ pub enum ErrorCode {
     NotVerifiedTicket,
     #[msg("Incorrect ticket")]
     IncorrectTicket,
+    #[msg("Payment is below the ticket price")]
+    InsufficientPayment,
+    #[msg("Ticket number is out of bounds")]
+    TicketNumOutOfBounds,
+    #[msg("Prize cannot be claimed during the claim cooldown")]
+    ClaimCooldownActive,
+    #[msg("Randomness already committed")]
+    RandomnessAlreadyCommitted,
+    #[msg("Committed randomness can still be revealed")]
+    RandomnessStillValid,
+    #[msg("Invalid fee configuration")]
+    InvalidFeeConfig,
+    #[msg("Incorrect treasury account")]
+    IncorrectTreasury,
+    #[msg("Prize has not been claimed")]
+    PrizeNotClaimed,
+    #[msg("Buyer balance is below the minimum")]
+    BuyerBalanceTooLow,
+    #[msg("Authority cannot participate in this lottery")]
+    AuthorityCannotParticipate,
+    #[msg("Collection creator could not be verified")]
+    CollectionVerificationFailed,
+    #[msg("Invalid instant win configuration")]
+    InvalidInstantWinConfig,
+    #[msg("Price is not a multiple of the configured rounding")]
+    PriceNotRounded,
+    #[msg("The winning ticket cannot be burned")]
+    CannotBurnWinningTicket,
+    #[msg("Randomness was seeded before the lottery ended")]
+    RandomnessPredatesDrawWindow,
+    #[msg("Sponsor amount must be greater than zero")]
+    InvalidSponsorAmount,
+    #[msg("Nothing has vested since the last claim")]
+    NothingVested,
+    #[msg("A vesting record is required to claim a vesting prize")]
+    VestingRecordRequired,
+    #[msg("Lottery start is not in the future")]
+    StartNotInFuture,
+    #[msg("Sponsor amount is below the minimum")]
+    SponsorAmountTooLow,
+    #[msg("Fees are not deferred")]
+    FeesNotDeferred,
+    #[msg("Ticket mint authority is not the collection mint")]
+    IncorrectMintAuthority,
+    #[msg("Ticket sales have already ended")]
+    SalesAlreadyEnded,
+    #[msg("Revealed randomness is not 32 bytes")]
+    InvalidRandomnessValue,
+    #[msg("No tickets were sold")]
+    NoTicketsSold,
+    #[msg("Invalid payment token")]
+    InvalidPaymentToken,
+    #[msg("Payment token accounts are required to pay with a token")]
+    PaymentAccountsRequired,
+    #[msg("URI exceeds the Metaplex URI length limit")]
+    UriTooLong,
+    #[msg("Lottery has been cancelled")]
+    LotteryCancelled,
+    #[msg("Lottery has not been cancelled")]
+    LotteryNotCancelled,
+    #[msg("Deferred fees have not been withdrawn")]
+    FeesNotWithdrawn,
+    #[msg("Winner badge accounts are required")]
+    BadgeAccountsRequired,
+    #[msg("Invalid number of winners or prize split")]
+    InvalidPrizeSplit,
+    #[msg("Fewer tickets sold than winners to draw")]
+    NotEnoughTickets,
+    #[msg("Invalid winner index")]
+    InvalidWinnerIndex,
+    #[msg("Prize already claimed")]
+    PrizeAlreadyClaimed,
+    #[msg("Pot can only be burned below min_tickets with burn_on_failure set")]
+    PotBurnNotAllowed,
+    #[msg("All tickets have been sold")]
+    SoldOut,
+    #[msg("Wallet ticket limit exceeded")]
+    WalletLimitExceeded,
+    #[msg("Wallet spend cap exceeded")]
+    WalletSpendCapExceeded,
+    #[msg("No pot to migrate to the vault")]
+    NothingToMigrate,
+    #[msg("Config update not allowed")]
+    InvalidConfigUpdate,
+    #[msg("Lottery is paused")]
+    LotteryPaused,
+    #[msg("Invalid new authority")]
+    InvalidNewAuthority,
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+    #[msg("A bond needs a draw deadline")]
+    InvalidBondConfig,
+    #[msg("Draw is not overdue")]
+    DrawNotOverdue,
+    #[msg("Bond is locked until the pot is paid out")]
+    BondLocked,
+    #[msg("Claim deadline has passed")]
+    ClaimExpired,
+    #[msg("Claim deadline has not passed")]
+    ClaimNotExpired,
+    #[msg("Ticket is locked until sales end")]
+    ResaleLocked,
+    #[msg("Not a Switchboard randomness account")]
+    InvalidRandomnessAccount,
+    #[msg("Vault is already rent-exempt")]
+    VaultRentExempt,
+    #[msg("Randomness was re-committed since the lottery committed to it")]
+    StaleRandomness,
+    #[msg("Ticket count must be between 1 and MAX_BATCH_TICKETS")]
+    InvalidTicketCount,
+    #[msg("Missing or incorrect ticket accounts")]
+    IncorrectTicketAccounts,
+    #[msg("The lottery reached its last round")]
+    RoundLimitReached,
+    #[msg("The ticket was not minted to the buyer")]
+    TicketNotMinted,
+    #[msg("The randomness has not been confirmed for min_confirmations slots yet")]
+    RandomnessNotConfirmed,
+    #[msg("Name leaves no room for the round and ticket numbers within the Metaplex name limit")]
+    NameTooLong,
+    #[msg("Symbol exceeds the Metaplex symbol limit")]
+    SymbolTooLong,
+    #[msg("Fewer than min_tickets were sold; cancel the lottery to refund them")]
+    MinimumNotReached,
+    #[msg("Creator share is invalid or leaves no room for the ticket fee")]
+    InvalidCreatorShare,
+    #[msg("Creator account is missing from the remaining accounts")]
+    IncorrectCreator,
+    #[msg("The refund deadline of the cancelled lottery has passed")]
+    RefundExpired,
+    #[msg("Refunds can be claimed until the refund deadline")]
+    RefundNotExpired,
+    #[msg("Merged lotteries must be other lotteries passed with their vaults")]
+    IncorrectMergeAccounts,
+    #[msg("The draw is overdue, the bond must be slashed first")]
+    DrawOverdue,
+    #[msg("Slot-hash randomness can't be re-requested")]
+    SlotHashNotRerequestable,
+    #[msg("This winning ticket has already been claimed")]
+    TicketAlreadyClaimed,
 }
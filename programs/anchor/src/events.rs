@@ -0,0 +1,147 @@
+//! Events emitted by the lottery, when `emit_events` is set.
+//!
+//! `emit!` logs every event as a `Program data: ` line holding the base64 of
+//! an 8 byte discriminator followed by the Borsh-encoded fields in declaration
+//! order: integers little endian, `Pubkey` as 32 bytes, `Vec` as a `u32`
+//! length then its items. The discriminator is the first 8 bytes of
+//! `sha256("event:<EventName>")`, so it only changes if the event is renamed.
+//!
+//! `WinnerChosen` and `PrizeClaimed` are meant for notification bots decoding
+//! them without the IDL. Their fields are never reordered or removed, new
+//! fields are only appended.
+//!
+//! To bound log size, an instruction emits at most two events: `buy_ticket`
+//! emits `TicketPurchased` and, for an instant prize, `InstantWin`; every
+//! other instruction emits at most one. Instructions acting on several items
+//! emit a single summary event instead of one per item, like
+//! `TicketsPurchased` for `buy_multiple_tickets`.
+
+use anchor_lang::prelude::*;
+
+/// An inconsistency found by `validate_lottery`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Anomaly {
+    /// The stored `TokenLottery` bump does not derive its address.
+    LotteryBumpMismatch,
+    /// The collection mint is not at its expected PDA.
+    CollectionMintAddressMismatch,
+    /// The collection mint account has not been initialized.
+    CollectionMintNotInitialized,
+    /// The collection token account is not at its expected PDA.
+    CollectionTokenAccountMismatch,
+    /// The collection metadata is missing or not at its expected PDA.
+    CollectionMetadataMissing,
+    /// The collection mint is not a verified creator on the collection metadata.
+    CollectionNotVerified,
+    /// The lamports held for the pot are less than `lottery_pot_amount`.
+    PotExceedsBalance,
+    /// The lottery vault is not at its expected PDA.
+    VaultAddressMismatch,
+}
+
+/// Emitted by `validate_lottery` with every anomaly found.
+/// An empty `anomalies` list means the lottery is healthy.
+#[event]
+pub struct IntegrityReport {
+    pub token_lottery: Pubkey,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Emitted by `reset_lottery` when a new round starts.
+#[event]
+pub struct LotteryReset {
+    pub token_lottery: Pubkey,
+    pub round_number: u64,
+    pub lottery_start: u64,
+    pub lottery_end: u64,
+}
+
+/// Emitted by `buy_ticket` for every ticket sold. `price` is in lamports, or
+/// in base units of the payment token selected by `payment_index`, with
+/// `decimals` decimals.
+#[event]
+pub struct TicketPurchased {
+    pub token_lottery: Pubkey,
+    pub buyer: Pubkey,
+    pub ticket_num: u64,
+    pub price: u64,
+    pub payment_index: u8,
+    pub decimals: u8,
+}
+
+/// Emitted by `buy_multiple_tickets` once per batch, for tickets
+/// `first_ticket` to `first_ticket + count - 1`. `total_price` is in
+/// lamports and `instant_winnings` sums the instant prizes of the batch.
+#[event]
+pub struct TicketsPurchased {
+    pub token_lottery: Pubkey,
+    pub buyer: Pubkey,
+    pub first_ticket: u64,
+    pub count: u64,
+    pub total_price: u64,
+    pub instant_winnings: u64,
+}
+
+/// Emitted by `choose_a_winner` once the winner is drawn. `total_tickets` is
+/// the number of tickets the winner was drawn from, for displaying the odds.
+/// `winner_wallet` is `Pubkey::default()` if the winner's `TicketRecord` was
+/// not passed. `winners` lists every winning ticket, starting with `winner`.
+///
+/// Layout after the discriminator: `token_lottery` (32 bytes),
+/// `round_number` (u64), `winner` (u64), `winner_wallet` (32 bytes),
+/// `winners` (u32 length, then u64 each), `total_tickets` (u64).
+#[event]
+pub struct WinnerChosen {
+    pub token_lottery: Pubkey,
+    pub round_number: u64,
+    pub winner: u64,
+    pub winner_wallet: Pubkey,
+    pub winners: Vec<u64>,
+    pub total_tickets: u64,
+}
+
+/// Emitted by `claim_prize` when the winner claims the pot. For a vesting
+/// prize `amount` is the total moved into the `VestingRecord`.
+///
+/// Layout after the discriminator: `token_lottery` (32 bytes),
+/// `round_number` (u64), `winner` (32 bytes), `amount` (u64).
+#[event]
+pub struct PrizeClaimed {
+    pub token_lottery: Pubkey,
+    pub round_number: u64,
+    pub winner: Pubkey,
+    pub amount: u64,
+}
+
+/// Emitted by `buy_ticket` when a ticket wins an instant prize.
+#[event]
+pub struct InstantWin {
+    pub buyer: Pubkey,
+    pub ticket_num: u64,
+    pub amount: u64,
+}
+
+/// Emitted by `verify_draw` with the winner recomputed from the given
+/// randomness and whether it matches the stored winner.
+#[event]
+pub struct DrawVerified {
+    pub token_lottery: Pubkey,
+    pub winner: u64,
+    pub derived_winner: u64,
+    pub matches: bool,
+}
+
+/// Emitted by `preview_close` with the lamports `close_lottery` would return.
+#[event]
+pub struct ClosePreview {
+    pub token_lottery: Pubkey,
+    pub reclaimable: u64,
+}
+
+/// Emitted by `withdraw_fees` when accrued fees are paid to the treasuries.
+#[event]
+pub struct FeesWithdrawn {
+    pub token_lottery: Pubkey,
+    pub fee_mode: u8,
+    pub amount: u64,
+}
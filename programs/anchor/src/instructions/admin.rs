@@ -1,20 +1,28 @@
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
 use anchor_spl::metadata::{
-    create_master_edition_v3, create_metadata_accounts_v3,
+    approve_collection_authority, create_master_edition_v3, create_metadata_accounts_v3,
     mpl_token_metadata::types::{CollectionDetails, Creator, DataV2},
-    set_and_verify_sized_collection_item, sign_metadata, CreateMasterEditionV3,
-    CreateMetadataAccountsV3, Metadata, MetadataAccount, SetAndVerifySizedCollectionItem,
-    SignMetadata,
+    set_and_verify_sized_collection_item, sign_metadata, ApproveCollectionAuthority,
+    CreateMasterEditionV3, CreateMetadataAccountsV3, Metadata, MetadataAccount,
+    SetAndVerifySizedCollectionItem, SignMetadata,
 };
 use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
 };
 
+use crate::constants::{
+    fits_badge_name, fits_base_uri, fits_name, fits_symbol, fits_uri, FEE_MODE_DEFERRED,
+    FEE_MODE_IMMEDIATE, MAX_PAYMENT_TOKENS, MAX_WINNERS,
+};
+use crate::state::{LotteryVault, PaymentToken, RandomnessSource, TimingMode};
+use crate::time::schedule_time;
+
 /// Accounts required to initialize the Token Lottery configuration.
 /// This sets up the main lottery account on-chain with initial parameters.
 #[derive(Accounts)]
+#[instruction(lottery_id: u64)]
 pub struct InitializeConfig<'info> {
     /// The account paying for account creation and fees.
     #[account(mut)]
@@ -25,15 +33,218 @@ pub struct InitializeConfig<'info> {
         init,
         payer = payer,
         space = 8 + TokenLottery::INIT_SPACE,
-        seeds = [b"token_lottery".as_ref()],
+        seeds = [b"token_lottery".as_ref(), lottery_id.to_le_bytes().as_ref()],
         bump
     )]
     pub token_lottery: Box<Account<'info, TokenLottery>>,
 
+    /// Escrow receiving ticket revenue and paying out prizes.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + LotteryVault::INIT_SPACE,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump
+    )]
+    pub lottery_vault: Box<Account<'info, LotteryVault>>,
+
     /// System program to create accounts.
     pub system_program: Program<'info, System>,
 }
 
+/// Optional lottery settings supplied alongside the core schedule and price
+/// when initializing the config.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigParams {
+    /// Slots after the draw during which the prize cannot be claimed,
+    /// leaving a window for audits and disputes.
+    pub claim_delay_slots: u64,
+
+    /// Fee taken from every ticket price, in basis points.
+    pub fee_basis_points: u16,
+
+    /// Treasuries the fee is split between. Shares must sum to `BASIS_POINTS`.
+    pub treasuries: Vec<TreasuryShare>,
+
+    /// Lamports paid from the pot to a loser who burns their ticket.
+    pub consolation_amount: u64,
+
+    /// Keeper allowed to draw the winner alongside the authority. The keeper is
+    /// trusted not to withhold a draw it dislikes, and skips the
+    /// `min_confirmations` wait: it could draw from a seed slot that a reorg
+    /// later replaces. The randomness must still be the committed, freshly
+    /// revealed one. `Pubkey::default()` disables the keeper.
+    pub trusted_keeper: Pubkey,
+
+    /// Lamports a buyer must hold on top of the ticket price, filtering out
+    /// dust and spam accounts.
+    pub min_buyer_balance: u64,
+
+    /// Bars the authority from buying tickets in its own lottery, ruling out a
+    /// conflict of interest. `false` lets the authority buy like anyone else.
+    pub authority_cannot_buy: bool,
+
+    /// Chance, in basis points, that a ticket wins an instant prize on purchase.
+    pub instant_win_bps: u16,
+
+    /// Lamports paid from the pot for an instant win.
+    pub instant_win_prize: u64,
+
+    /// Ticket prices must be a multiple of this many lamports, e.g. 0.01 SOL
+    /// for clean prices. Unrounded prices are rejected rather than silently
+    /// rounded. `0` allows any price.
+    pub round_price_to: u64,
+
+    /// Slots over which a claimed prize vests, paid out in installments by
+    /// `claim_vested`. `0` pays the prize out in full on claim.
+    pub vesting_slots: u64,
+
+    /// Reject a `start` slot that is not in the future, so sales don't open
+    /// immediately by mistake.
+    pub require_future_start: bool,
+
+    /// Smallest contribution `sponsor_pot` accepts, keeping dust
+    /// sponsorships out.
+    pub min_sponsor_amount: u64,
+
+    /// A ticket bought within this many slots of `lottery_end` pushes
+    /// `lottery_end` back by the same amount, preventing last-slot sniping.
+    /// `0` disables the extension.
+    pub anti_snipe_slots: u64,
+
+    /// Cap on the total slots `lottery_end` can be pushed back in a round.
+    pub max_anti_snipe_extension: u64,
+
+    /// How the ticket fee reaches the treasuries: `FEE_MODE_IMMEDIATE` pays
+    /// it on every purchase, `FEE_MODE_DEFERRED` holds it until
+    /// `withdraw_fees`.
+    pub fee_mode: u8,
+
+    /// SPL tokens accepted for tickets alongside SOL, each with its own
+    /// price. Selected by `payment_index` in `buy_ticket`.
+    pub payment_tokens: Vec<PaymentToken>,
+
+    /// Slots after `lottery_end` from which anyone may draw the winner, so
+    /// the pot isn't stuck if the authority disappears. `0` disables it.
+    pub public_draw_timeout_slots: u64,
+
+    /// Collection a badge NFT is minted into for every winner on
+    /// `claim_prize`. Its update authority must approve this lottery's
+    /// `collection_authority` PDA as a collection authority. Badge names
+    /// carry the `lottery_id`, which must leave room for the round and ticket
+    /// numbers, see `fits_badge_name`. `Pubkey::default()` mints no badge.
+    pub winner_badge_collection: Pubkey,
+
+    /// Number of distinct winners drawn each round, from 1 to `MAX_WINNERS`.
+    /// Several winners can't be combined with `vesting_slots`.
+    pub num_winners: u8,
+
+    /// Share of the pot paid to each winner, in basis points, by winner index.
+    /// The first `num_winners` shares must sum to `BASIS_POINTS`, the rest
+    /// must be `0`.
+    pub prize_split: [u16; MAX_WINNERS],
+
+    /// Tickets that must be sold for the round to succeed. `0` disables the
+    /// minimum.
+    pub min_tickets: u64,
+
+    /// Burn the pot with `burn_pot` if the round ends below `min_tickets`,
+    /// instead of refunding the tickets.
+    pub burn_on_failure: bool,
+
+    /// Tickets sold per round at most, for fixed-supply raffles. `0` leaves
+    /// the supply unlimited.
+    pub max_tickets: u64,
+
+    /// Tickets a single wallet may buy per round, so no one buyer takes
+    /// over the pot. `0` disables the limit.
+    pub max_tickets_per_wallet: u64,
+
+    /// Lamports a single wallet may spend on SOL tickets per round. Token
+    /// payments don't count against it. `0` disables the cap.
+    pub max_spend_per_wallet: u64,
+
+    /// Whether ticket sales, draws, claims, resets and fee withdrawals emit
+    /// events. Turning them off saves compute for high-volume lotteries
+    /// whose indexers read account state instead. The reports of
+    /// `validate_lottery` and `verify_draw` are always emitted.
+    pub emit_events: bool,
+
+    /// Lamports the authority deposits into the vault as a bond. It gets the
+    /// bond back with `withdraw_bond` once the winners are drawn and paid,
+    /// and anyone can move it into the pot with `slash_bond` if the draw
+    /// misses `draw_deadline_slots`.
+    pub authority_bond: u64,
+
+    /// Slots after `lottery_end` the authority has to draw the winners
+    /// before its bond can be slashed. Required with `authority_bond`.
+    pub draw_deadline_slots: u64,
+
+    /// Slots after the draw during which the winners can claim. Prizes left
+    /// unclaimed after that can be rolled into the next round with
+    /// `rollover_unclaimed`. `0` lets winners claim indefinitely.
+    pub claim_deadline_slots: u64,
+
+    /// Freezes tickets at purchase so they can't change hands before the
+    /// draw. `unlock_ticket` releases them once sales have ended.
+    pub resale_lock: bool,
+
+    /// Whether `start`, `end` and the schedule durations are in slots or
+    /// UNIX timestamps. See `TimingMode`.
+    pub timing_mode: TimingMode,
+
+    /// Slots that must pass after the committed randomness was seeded
+    /// before the winner can be drawn, so a reorg around the seed slot can't
+    /// be used to influence it. `0` draws as soon as it is revealed. The
+    /// trusted keeper is exempt.
+    pub min_confirmations: u64,
+
+    /// Name of the collection and prefix of the ticket names, e.g. `NAME`.
+    /// Must leave room for the round and ticket numbers, see `fits_name`.
+    pub name: String,
+
+    /// Metadata symbol of the collection and tickets, e.g. `SYMBOL`.
+    pub symbol: String,
+
+    /// Metadata URI of the collection and of tickets, until changed with
+    /// `update_config`, e.g. `URI`.
+    pub uri: String,
+
+    /// Where the randomness of the draw comes from. `SlotHash` needs no
+    /// oracle but can be influenced by the slot leader, see
+    /// `SlotHashRandomness`.
+    pub randomness_source: RandomnessSource,
+
+    /// Directory of per-ticket metadata: ticket `n` gets
+    /// `<base_uri>/<n>.json`. Empty to give every ticket `uri`.
+    pub base_uri: String,
+
+    /// Rounds the lottery runs for at most, counting the first, after which
+    /// `reset_lottery` fails and the lottery can only be closed. `0` leaves
+    /// it unbounded, up to `MAX_ROUND_NUMBER`.
+    pub max_rounds: u64,
+
+    /// Receives `creator_share_bps` of each SOL prize when it is claimed.
+    /// Unlike the ticket fee, it is taken from the final pot.
+    pub creator: Pubkey,
+
+    /// Share of each SOL prize paid to `creator`, in basis points. Together
+    /// with `fee_basis_points` it may not exceed `BASIS_POINTS`.
+    pub creator_share_bps: u16,
+
+    /// Slots after `cancel_lottery` during which tickets can be refunded.
+    /// What is left after that can be reclaimed by the authority with
+    /// `sweep_unclaimed_refunds`. `0` keeps refunds open indefinitely.
+    pub refund_window_slots: u64,
+
+    /// Share of the tickets sold that win, in basis points, e.g. `100` for
+    /// the top 1%. At draw time it overrides `num_winners` with
+    /// `ticket_num * winner_fraction_bps / BASIS_POINTS` winners, at least 1
+    /// and at most `MAX_WINNERS`, splitting the pot evenly. Can't be combined
+    /// with `vesting_slots`. `0` draws `num_winners` winners.
+    pub winner_fraction_bps: u16,
+}
+
 /// Accounts required to initialize a new lottery collection.
 /// This includes the mint, token account, metadata, and master edition accounts.
 #[derive(Accounts)]
@@ -42,13 +253,20 @@ pub struct InitializeLottery<'info> {
     #[account(mut)]
     pub payer: Signer<'info>,
 
+    /// The lottery config, which must be initialized first.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
     #[account(
         init,
         payer = payer,
         mint::decimals = 0,
         mint::authority = collection_mint,
         mint::freeze_authority = collection_mint,
-        seeds = [b"collection_mint".as_ref()],
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub collection_mint: Box<InterfaceAccount<'info, Mint>>,
@@ -60,10 +278,30 @@ pub struct InitializeLottery<'info> {
     #[account(mut)]
     pub master_edition: UncheckedAccount<'info>,
 
+    /// PDA delegated as the collection authority used to verify tickets.
+    /// CHECK: Only used as a PDA signer, it holds no data.
+    #[account(
+        seeds = [b"collection_authority".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// Metaplex record approving `collection_authority` on the collection.
+    /// CHECK: Created and validated by the Metaplex program.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            collection_mint.key().as_ref(), b"collection_authority",
+            collection_authority.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_authority_record: UncheckedAccount<'info>,
+
     #[account(
         init_if_needed,
         payer = payer,
-        seeds = [b"collection_token_account".as_ref()],
+        seeds = [b"collection_token_account".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump,
         token::mint = collection_mint,
         token::authority = collection_token_account
@@ -87,21 +325,109 @@ pub struct InitializeLottery<'info> {
 }
 
 /// Initializes the main Token Lottery account with start/end times, ticket price,
-/// and sets the authority.
+/// and sets the authority. The `authority_bond`, if any, is deposited into the
+/// vault.
 ///
 /// # Arguments
 /// * `ctx` - Context holding the InitializeConfig accounts
-/// * `start` - UNIX timestamp for lottery start
-/// * `end` - UNIX timestamp for lottery end
+/// * `lottery_id` - Identifies the lottery, so many can run side by side
+/// * `start` - Lottery start, in slots or UNIX timestamps per `timing_mode`
+/// * `end` - Lottery end, in slots or UNIX timestamps per `timing_mode`
 /// * `price` - Ticket price in lamports
+/// * `params` - Optional lottery settings
 pub fn process_initialize_config(
     ctx: Context<InitializeConifg>,
+    lottery_id: u64,
     start: u64,
     end: u64,
     price: u64,
+    params: ConfigParams,
 ) -> Result<()> {
+    if params.require_future_start {
+        require!(
+            start > schedule_time(params.timing_mode, ctx.remaining_accounts)?,
+            ErrorCode::StartNotInFuture
+        );
+    }
+    require!(fits_name(&params.name), ErrorCode::NameTooLong);
+    require!(fits_symbol(&params.symbol), ErrorCode::SymbolTooLong);
+    require!(fits_uri(&params.uri), ErrorCode::UriTooLong);
+    require!(fits_base_uri(&params.base_uri), ErrorCode::UriTooLong);
+    require!(
+        params.winner_badge_collection == Pubkey::default() || fits_badge_name(lottery_id),
+        ErrorCode::NameTooLong
+    );
+    require!(
+        params.fee_basis_points <= BASIS_POINTS,
+        ErrorCode::InvalidFeeConfig
+    );
+    require!(
+        params.fee_basis_points as u32 + params.creator_share_bps as u32 <= BASIS_POINTS as u32
+            && (params.creator_share_bps == 0 || params.creator != Pubkey::default()),
+        ErrorCode::InvalidCreatorShare
+    );
+    require!(
+        params.instant_win_bps <= BASIS_POINTS,
+        ErrorCode::InvalidInstantWinConfig
+    );
+    require!(
+        params.treasuries.len() <= MAX_TREASURIES as usize,
+        ErrorCode::InvalidFeeConfig
+    );
+    require!(
+        params.fee_mode == FEE_MODE_IMMEDIATE || params.fee_mode == FEE_MODE_DEFERRED,
+        ErrorCode::InvalidFeeConfig
+    );
+    require!(
+        params.payment_tokens.len() <= MAX_PAYMENT_TOKENS as usize
+            && params.payment_tokens.iter().all(|token| token.price > 0),
+        ErrorCode::InvalidPaymentToken
+    );
+    let winners = params.num_winners as usize;
+    let split_bps: u32 = params.prize_split.iter().map(|bps| *bps as u32).sum();
+    require!(
+        (1..=MAX_WINNERS).contains(&winners)
+            && split_bps == BASIS_POINTS as u32
+            && params.prize_split[winners..].iter().all(|bps| *bps == 0)
+            && (winners == 1 || params.vesting_slots == 0)
+            && params.winner_fraction_bps <= BASIS_POINTS
+            && (params.winner_fraction_bps == 0 || params.vesting_slots == 0),
+        ErrorCode::InvalidPrizeSplit
+    );
+    if params.fee_basis_points > 0 || !params.treasuries.is_empty() {
+        let total_shares: u32 = params
+            .treasuries
+            .iter()
+            .map(|treasury| treasury.share_bps as u32)
+            .sum();
+        require!(
+            total_shares == BASIS_POINTS as u32,
+            ErrorCode::InvalidFeeConfig
+        );
+    }
+
+    require!(
+        params.authority_bond == 0 || params.draw_deadline_slots > 0,
+        ErrorCode::InvalidBondConfig
+    );
+
+    ctx.accounts.lottery_vault.bump = ctx.bumps.lottery_vault;
+    if params.authority_bond > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.lottery_vault.to_account_info(),
+                },
+            ),
+            params.authority_bond,
+        )?;
+    }
+
     let token_lottery = &mut ctx.accounts.token_lottery;
     token_lottery.bump = ctx.bumps.token_lottery;
+    token_lottery.lottery_id = lottery_id;
     token_lottery.lottery_start = start;
     token_lottery.lottery_end = end;
     token_lottery.price = price;
@@ -109,20 +435,82 @@ pub fn process_initialize_config(
     token_lottery.randomness_account = Pubkey::default();
     token_lottery.ticket_num = 0;
     token_lottery.winner_chosen = false;
+    token_lottery.winner_address = Pubkey::default();
+    token_lottery.claim_delay_slots = params.claim_delay_slots;
+    token_lottery.fee_basis_points = params.fee_basis_points;
+    token_lottery.treasuries = params.treasuries;
+    token_lottery.consolation_amount = params.consolation_amount;
+    token_lottery.trusted_keeper = params.trusted_keeper;
+    token_lottery.min_buyer_balance = params.min_buyer_balance;
+    token_lottery.authority_cannot_buy = params.authority_cannot_buy;
+    token_lottery.instant_win_bps = params.instant_win_bps;
+    token_lottery.instant_win_prize = params.instant_win_prize;
+    token_lottery.round_price_to = params.round_price_to;
+    token_lottery.vesting_slots = params.vesting_slots;
+    token_lottery.min_sponsor_amount = params.min_sponsor_amount;
+    token_lottery.anti_snipe_slots = params.anti_snipe_slots;
+    token_lottery.max_anti_snipe_extension = params.max_anti_snipe_extension;
+    token_lottery.fee_mode = params.fee_mode;
+    token_lottery.payment_token_pots = vec![0; params.payment_tokens.len()];
+    token_lottery.payment_token_rollovers = vec![0; params.payment_tokens.len()];
+    token_lottery.payment_token_decimals = vec![0; params.payment_tokens.len()];
+    token_lottery.payment_tokens = params.payment_tokens;
+    token_lottery.uri = params.uri;
+    token_lottery.public_draw_timeout_slots = params.public_draw_timeout_slots;
+    token_lottery.winner_badge_collection = params.winner_badge_collection;
+    token_lottery.num_winners = params.num_winners;
+    token_lottery.prize_split = params.prize_split;
+    token_lottery.min_tickets = params.min_tickets;
+    token_lottery.burn_on_failure = params.burn_on_failure;
+    token_lottery.max_tickets = params.max_tickets;
+    token_lottery.max_tickets_per_wallet = params.max_tickets_per_wallet;
+    token_lottery.max_spend_per_wallet = params.max_spend_per_wallet;
+    token_lottery.emit_events = params.emit_events;
+    token_lottery.authority_bond = params.authority_bond;
+    token_lottery.draw_deadline_slots = params.draw_deadline_slots;
+    token_lottery.claim_deadline_slots = params.claim_deadline_slots;
+    token_lottery.resale_lock = params.resale_lock;
+    token_lottery.timing_mode = params.timing_mode;
+    token_lottery.min_confirmations = params.min_confirmations;
+    token_lottery.name = params.name;
+    token_lottery.symbol = params.symbol;
+    token_lottery.randomness_source = params.randomness_source;
+    token_lottery.base_uri = params.base_uri;
+    token_lottery.max_rounds = params.max_rounds;
+    token_lottery.creator = params.creator;
+    token_lottery.creator_share_bps = params.creator_share_bps;
+    token_lottery.refund_window_slots = params.refund_window_slots;
+    token_lottery.refund_deadline_slot = 0;
+    token_lottery.winner_fraction_bps = params.winner_fraction_bps;
+    require!(
+        token_lottery.is_rounded_price(price),
+        ErrorCode::PriceNotRounded
+    );
     Ok(())
 }
 
-/// Initializes a new lottery collection by creating:
+/// Initializes a new lottery collection for an initialized config. Only the
+/// config's authority may create the collection. Creates:
 /// - the mint account
 /// - the collection token account
 /// - the metadata account
 /// - the master edition account
-/// - signs the metadata for the collection
+/// - signs the metadata for the collection and checks the signature took effect
+/// - delegates ticket verification to the `collection_authority` PDA
 ///
 /// # Arguments
 /// * `ctx` - Context holding the InitializeLottery accounts
 pub fn process_initialize_lottery(ctx: Context<InitializeLottery>) -> Result<()> {
-    let signer_seeds: &[&[&[u8]]] = &[&[b"collection_mint".as_ref(), &[ctx.bumps.collection_mint]]];
+    if ctx.accounts.payer.key() != ctx.accounts.token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+
+    let lottery_id = ctx.accounts.token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
 
     msg!("Creating mint accounts");
     mint_to(
@@ -154,9 +542,9 @@ pub fn process_initialize_lottery(ctx: Context<InitializeLottery>) -> Result<()>
             &signer_seeds,
         ),
         DataV2 {
-            name: NAME.to_string(),
-            symbol: SYMBOL.to_string(),
-            uri: URI.to_string(),
+            name: ctx.accounts.token_lottery.name.clone(),
+            symbol: ctx.accounts.token_lottery.symbol.clone(),
+            uri: ctx.accounts.token_lottery.uri.clone(),
             seller_fee_basis_points: 0,
             creators: Some(vec![Creator {
                 address: ctx.accounts.collection_mint.key(),
@@ -188,7 +576,7 @@ pub fn process_initialize_lottery(ctx: Context<InitializeLottery>) -> Result<()>
             },
             &signer_seeds,
         ),
-        Some(0),
+        TICKET_MAX_SUPPLY,
     )?;
 
     msg!("verifying collection");
@@ -201,5 +589,28 @@ pub fn process_initialize_lottery(ctx: Context<InitializeLottery>) -> Result<()>
         &signer_seeds,
     ))?;
 
+    msg!("Delegating collection authority");
+    approve_collection_authority(CpiContext::new_with_signer(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ApproveCollectionAuthority {
+            collection_authority_record: ctx.accounts.collection_authority_record.to_account_info(),
+            new_collection_authority: ctx.accounts.collection_authority.to_account_info(),
+            update_authority: ctx.accounts.collection_mint.to_account_info(),
+            payer: ctx.accounts.payer.to_account_info(),
+            metadata: ctx.accounts.metadata.to_account_info(),
+            mint: ctx.accounts.collection_mint.to_account_info(),
+        },
+        &signer_seeds,
+    ))?;
+
+    // Reload the metadata and confirm the collection mint is now a verified creator
+    let metadata = MetadataAccount::try_deserialize(&mut &ctx.accounts.metadata.data.borrow()[..])?;
+    let creator_verified = metadata.creators.as_ref().map_or(false, |creators| {
+        creators.iter().any(|creator| {
+            creator.address == ctx.accounts.collection_mint.key() && creator.verified
+        })
+    });
+    require!(creator_verified, ErrorCode::CollectionVerificationFailed);
+
     Ok(())
 }
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::schedule_time;
+
+/// Accounts required to slash the authority's bond.
+///
+/// Anyone may slash an overdue bond, so no authority check is made.
+#[derive(Accounts)]
+pub struct SlashBond<'info> {
+    /// The account submitting the slash.
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Accounts required to withdraw the authority's bond.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can withdraw the bond.
+/// 2. The draw is not overdue, or the bond has been slashed.
+#[derive(Accounts)]
+pub struct WithdrawBond<'info> {
+    /// The lottery authority, receiving the bond.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the bond.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+}
+
+/// Adds the authority's bond to the pot once the winners weren't drawn
+/// within `draw_deadline_slots` of `lottery_end`.
+///
+/// The bond is already held in the vault, so only the accounting moves; it
+/// then goes to the winners when the round is drawn. A cancelled round
+/// refunds its buyers instead and can't be slashed.
+///
+/// # Arguments
+/// * `ctx` - Context containing `SlashBond` accounts
+pub fn process_slash_bond(ctx: Context<SlashBond>) -> Result<()> {
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(
+        token_lottery.is_draw_overdue(now),
+        ErrorCode::DrawNotOverdue
+    );
+
+    let bond = token_lottery.authority_bond;
+    token_lottery.lottery_pot_amount += bond;
+    token_lottery.authority_bond = 0;
+    msg!("Slashed bond of {} lamports into the pot", bond);
+
+    Ok(())
+}
+
+/// Returns the authority's bond once the last round's winners are drawn
+/// and its pot is paid out, or once the round is cancelled and its buyers
+/// can refund their tickets. The bond stays locked across rounds so that
+/// every round is covered; a lottery without `max_rounds` returns it with
+/// `close_lottery`. `cancel_lottery` refuses an overdue draw, so a cancelled round
+/// was cancelled in time.
+///
+/// # Arguments
+/// * `ctx` - Context containing `WithdrawBond` accounts
+pub fn process_withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(!token_lottery.is_draw_overdue(now), ErrorCode::DrawOverdue);
+    require!(
+        (token_lottery.winner_chosen
+            && token_lottery.lottery_pot_amount == 0
+            && !token_lottery.has_round_capacity())
+            || token_lottery.cancelled,
+        ErrorCode::BondLocked
+    );
+
+    let bond = token_lottery.authority_bond;
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? -= bond;
+    **ctx.accounts.payer.try_borrow_mut_lamports()? += bond;
+    token_lottery.authority_bond = 0;
+    msg!("Withdrew bond of {} lamports", bond);
+
+    Ok(())
+}
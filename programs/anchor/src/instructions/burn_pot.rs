@@ -0,0 +1,97 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::incinerator;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::{current_slot, schedule_time};
+
+/// Accounts required to burn the pot of an undersubscribed lottery.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can burn the pot.
+/// 2. The pot goes to the incinerator.
+#[derive(Accounts)]
+pub struct BurnPot<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the pot.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The incinerator, whose lamports are burned at the end of the block.
+    /// CHECK: Only receives lamports, checked against its address.
+    #[account(mut, address = incinerator::ID)]
+    pub incinerator: UncheckedAccount<'info>,
+}
+
+/// Burns the SOL pot of a lottery that ended with fewer than `min_tickets`
+/// tickets sold, when `burn_on_failure` is set, instead of refunding it.
+///
+/// The lottery is then cancelled, so it can't be drawn. Token pots and
+/// deferred fees are left for `refund_ticket`, and, as with `cancel_lottery`,
+/// `refund_deadline_slot` is set so `sweep_unclaimed_refunds` can collect
+/// them once `refund_window_slots` have passed.
+///
+/// # Arguments
+/// * `ctx` - Context containing `BurnPot` accounts
+pub fn process_burn_pot(ctx: Context<BurnPot>) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    if now <= token_lottery.lottery_end {
+        return Err(ErrorCode::LotteryNotCompleted.into());
+    }
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(
+        token_lottery.burn_on_failure && token_lottery.ticket_num < token_lottery.min_tickets,
+        ErrorCode::PotBurnNotAllowed
+    );
+
+    let amount = token_lottery.lottery_pot_amount;
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.incinerator.try_borrow_mut_lamports()? += amount;
+
+    token_lottery.lottery_pot_amount = 0;
+    token_lottery.cancelled = true;
+    if token_lottery.refund_window_slots > 0 {
+        token_lottery.refund_deadline_slot = slot.saturating_add(token_lottery.refund_window_slots);
+        msg!(
+            "Refunds close after slot {}",
+            token_lottery.refund_deadline_slot
+        );
+    }
+    msg!(
+        "Burned pot of {} lamports, {} of {} tickets sold",
+        amount,
+        token_lottery.ticket_num,
+        token_lottery.min_tickets
+    );
+
+    Ok(())
+}
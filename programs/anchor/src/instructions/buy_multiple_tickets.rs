@@ -0,0 +1,587 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{
+    self, get_associated_token_address_with_program_id, AssociatedToken,
+};
+use anchor_spl::metadata::{
+    create_master_edition_v3, create_metadata_accounts_v3, freeze_delegated_account,
+    mpl_token_metadata::types::DataV2, set_and_verify_sized_collection_item, CreateMasterEditionV3,
+    CreateMetadataAccountsV3, FreezeDelegatedAccount, Metadata, SetAndVerifySizedCollectionItem,
+};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::{
+    approve, initialize_mint2, mint_to, Approve, InitializeMint2, Mint, MintTo, TokenInterface,
+};
+use solana_program::program_pack::Pack;
+
+use crate::constants::{
+    BATCH_TICKET_ACCOUNTS, FEE_MODE_DEFERRED, MAX_BATCH_TICKETS, MAX_TICKET_NUM, PAYMENT_INDEX_SOL,
+    TICKET_MAX_SUPPLY,
+};
+use crate::error::ErrorCode;
+use crate::events::TicketsPurchased;
+use crate::state::{BuyerRecord, LotteryVault, TicketRecord, TokenLottery};
+use crate::time::schedule_time;
+
+/// Accounts required to buy several lottery tickets at once.
+///
+/// The accounts of each ticket can't be derived before `ticket_num` is
+/// known, so they are passed as remaining accounts instead of being
+/// declared here. See `process_buy_multiple_tickets`.
+#[derive(Accounts)]
+pub struct BuyMultipleTickets<'info> {
+    /// The account paying for the tickets.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// TokenLottery state account tracking the current lottery.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Box<Account<'info, TokenLottery>>,
+
+    /// Escrow receiving the ticket prices and paying instant prizes.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Box<Account<'info, LotteryVault>>,
+
+    /// Counts the payer's tickets this round, for `max_tickets_per_wallet`.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer".as_ref(), token_lottery.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+
+    /// Metadata account of the collection the tickets belong to.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// Master edition account of the collection.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// Mint account of the collection.
+    #[account(
+        mut,
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Delegated collection authority verifying the tickets into the
+    /// collection, and the delegate freezing them under `resale_lock`.
+    /// CHECK: Only used as a PDA signer, it holds no data.
+    #[account(
+        seeds = [b"collection_authority".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// Metaplex record approving `collection_authority` on the collection.
+    /// CHECK: Validated by the Metaplex program during verification.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            collection_mint.key().as_ref(), b"collection_authority",
+            collection_authority.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_authority_record: UncheckedAccount<'info>,
+
+    /// Associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Token program interface
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// System program interface
+    pub system_program: Program<'info, System>,
+
+    /// Metaplex token metadata program
+    pub token_metadata_program: Program<'info, Metadata>,
+
+    /// Rent sysvar for account creation
+    pub rent: Sysvar<'info, Rent>,
+}
+
+/// Buys `count` sequential tickets in SOL for the caller, charging
+/// `price * count` at once.
+///
+/// Applies the same rules as `buy_ticket` to every ticket: the sales
+/// window, pause, supply and per-wallet limits, fees, instant wins, the
+/// anti-snipe extension and the resale lock. Instead of a `TicketPurchased`
+/// and an `InstantWin` per ticket, a single `TicketsPurchased` summarizes
+/// the batch. Tickets paid with a payment token are bought one at a time
+/// with `buy_ticket`.
+///
+/// Remaining accounts:
+/// - under `FEE_MODE_IMMEDIATE`, the treasury accounts in the order they
+///   were configured;
+/// - then, for each ticket from the current `ticket_num` on, the
+///   `BATCH_TICKET_ACCOUNTS` accounts: the ticket mint PDA, the payer's
+///   associated token account for it, the `TicketRecord` PDA, the metadata
+///   and the master edition.
+///
+/// # Arguments
+/// * `ctx` - Context containing `BuyMultipleTickets` accounts
+/// * `amount` - Lamports the buyer pays for all the tickets. Anything above
+///   `price * count` is returned to the payer.
+/// * `count` - Number of tickets, from 1 to `MAX_BATCH_TICKETS`
+pub fn process_buy_multiple_tickets<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyMultipleTickets<'info>>,
+    amount: u64,
+    count: u8,
+) -> Result<()> {
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    require!(
+        (1..=MAX_BATCH_TICKETS).contains(&count),
+        ErrorCode::InvalidTicketCount
+    );
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(!token_lottery.paused, ErrorCode::LotteryPaused);
+    if now < token_lottery.lottery_start || now > token_lottery.lottery_end {
+        return Err(ErrorCode::LotteryNotOpen.into());
+    }
+    let count = count as u64;
+    let first_ticket = token_lottery.ticket_num;
+    require!(
+        first_ticket + count <= MAX_TICKET_NUM,
+        ErrorCode::TicketNumOutOfBounds
+    );
+    require!(
+        first_ticket + count <= token_lottery.max_tickets || token_lottery.max_tickets == 0,
+        ErrorCode::SoldOut
+    );
+    require!(
+        !token_lottery.authority_cannot_buy || ctx.accounts.payer.key() != token_lottery.authority,
+        ErrorCode::AuthorityCannotParticipate
+    );
+
+    let price = token_lottery.price;
+    let total_price = price * count;
+    require!(amount >= total_price, ErrorCode::InsufficientPayment);
+    require!(
+        ctx.accounts.payer.lamports()
+            >= token_lottery.min_buyer_balance.saturating_add(total_price),
+        ErrorCode::BuyerBalanceTooLow
+    );
+
+    let round_number = token_lottery.round_number;
+    let max_tickets_per_wallet = token_lottery.max_tickets_per_wallet;
+    let max_spend_per_wallet = token_lottery.max_spend_per_wallet;
+    let buyer_record = &mut ctx.accounts.buyer_record;
+    if buyer_record.round_number != round_number {
+        buyer_record.round_number = round_number;
+        buyer_record.tickets = 0;
+        buyer_record.spent = 0;
+    }
+    buyer_record.bump = ctx.bumps.buyer_record;
+    buyer_record.tickets += count;
+    buyer_record.spent = buyer_record.spent.saturating_add(total_price);
+    require!(
+        max_tickets_per_wallet == 0 || buyer_record.tickets <= max_tickets_per_wallet,
+        ErrorCode::WalletLimitExceeded
+    );
+    require!(
+        max_spend_per_wallet == 0 || buyer_record.spent <= max_spend_per_wallet,
+        ErrorCode::WalletSpendCapExceeded
+    );
+
+    // Fees are taken per ticket, so rounding matches separate purchases
+    let fee = ctx.accounts.token_lottery.fee_for(price) * count;
+    let (deferred_fee, treasury_count) = if ctx.accounts.token_lottery.fee_mode == FEE_MODE_DEFERRED
+    {
+        (fee, 0)
+    } else {
+        let treasury_amounts = ctx.accounts.token_lottery.treasury_amounts(fee);
+        require!(
+            ctx.remaining_accounts.len() >= treasury_amounts.len(),
+            ErrorCode::IncorrectTreasury
+        );
+        for ((treasury, treasury_account), treasury_amount) in ctx
+            .accounts
+            .token_lottery
+            .treasuries
+            .iter()
+            .zip(ctx.remaining_accounts.iter())
+            .zip(treasury_amounts.iter())
+        {
+            require_keys_eq!(
+                treasury_account.key(),
+                treasury.recipient,
+                ErrorCode::IncorrectTreasury
+            );
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.payer.to_account_info(),
+                        to: treasury_account.clone(),
+                    },
+                ),
+                *treasury_amount,
+            )?;
+        }
+        (0, treasury_amounts.len())
+    };
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.lottery_vault.to_account_info(),
+            },
+        ),
+        amount - fee + deferred_fee,
+    )?;
+
+    let overpayment = amount - total_price;
+    if overpayment > 0 {
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= overpayment;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += overpayment;
+        msg!("Returned overpayment of {}", overpayment);
+    }
+
+    let token_lottery = &mut ctx.accounts.token_lottery;
+    token_lottery.lottery_pot_amount += total_price - fee;
+    token_lottery.accrued_fees += deferred_fee;
+    token_lottery.gross_sales += total_price;
+    token_lottery.total_fees += fee;
+
+    let ticket_accounts = ctx
+        .remaining_accounts
+        .get(treasury_count..treasury_count + BATCH_TICKET_ACCOUNTS * count as usize)
+        .ok_or(ErrorCode::IncorrectTicketAccounts)?;
+    let mut instant_winnings = 0;
+    for accounts in ticket_accounts.chunks(BATCH_TICKET_ACCOUNTS) {
+        let ticket_num = ctx.accounts.token_lottery.ticket_num;
+        mint_batch_ticket(&ctx, accounts, ticket_num)?;
+
+        // Instant prize, paid straight from the pot
+        if ctx.accounts.token_lottery.is_instant_win(now, ticket_num) {
+            let prize = ctx
+                .accounts
+                .token_lottery
+                .instant_win_prize
+                .min(ctx.accounts.token_lottery.lottery_pot_amount);
+            **ctx
+                .accounts
+                .lottery_vault
+                .to_account_info()
+                .try_borrow_mut_lamports()? -= prize;
+            **ctx.accounts.payer.try_borrow_mut_lamports()? += prize;
+            ctx.accounts.token_lottery.lottery_pot_amount -= prize;
+            ctx.accounts.token_lottery.instant_payouts += prize;
+            instant_winnings += prize;
+
+            msg!("Instant win on ticket {}: {}", ticket_num, prize);
+        }
+
+        let token_lottery = &mut ctx.accounts.token_lottery;
+        let extension = token_lottery.anti_snipe_extension_at(now);
+        if extension > 0 {
+            token_lottery.lottery_end += extension;
+            token_lottery.anti_snipe_extension += extension;
+            msg!("Extended lottery end to {}", token_lottery.lottery_end);
+        }
+        token_lottery.ticket_num += 1;
+    }
+
+    if ctx.accounts.token_lottery.emit_events {
+        emit!(TicketsPurchased {
+            token_lottery: ctx.accounts.token_lottery.key(),
+            buyer: ctx.accounts.payer.key(),
+            first_ticket,
+            count,
+            total_price,
+            instant_winnings,
+        });
+    }
+
+    Ok(())
+}
+
+/// Creates and mints ticket `ticket_num` from its `BATCH_TICKET_ACCOUNTS`
+/// remaining accounts, as the `init` constraints and CPIs of `buy_ticket`
+/// would, and records its buyer.
+fn mint_batch_ticket<'info>(
+    ctx: &Context<'_, '_, 'info, 'info, BuyMultipleTickets<'info>>,
+    accounts: &[AccountInfo<'info>],
+    ticket_num: u64,
+) -> Result<()> {
+    let [ticket_mint, destination, ticket_record, metadata, master_edition] = accounts else {
+        return Err(ErrorCode::IncorrectTicketAccounts.into());
+    };
+    let token_lottery = &ctx.accounts.token_lottery;
+    let lottery_id = token_lottery.lottery_id.to_le_bytes();
+    let round_number = token_lottery.round_number.to_le_bytes();
+    let ticket_index = ticket_num.to_le_bytes();
+    let rent = Rent::get()?;
+
+    // Ticket mint
+    let (mint_address, mint_bump) = Pubkey::find_program_address(
+        &[
+            lottery_id.as_ref(),
+            round_number.as_ref(),
+            ticket_index.as_ref(),
+        ],
+        ctx.program_id,
+    );
+    require_keys_eq!(
+        ticket_mint.key(),
+        mint_address,
+        ErrorCode::IncorrectTicketAccounts
+    );
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ticket_mint.clone(),
+            },
+            &[&[
+                lottery_id.as_ref(),
+                round_number.as_ref(),
+                ticket_index.as_ref(),
+                &[mint_bump],
+            ]],
+        ),
+        rent.minimum_balance(spl_token::state::Mint::LEN),
+        spl_token::state::Mint::LEN as u64,
+        &ctx.accounts.token_program.key(),
+    )?;
+    initialize_mint2(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            InitializeMint2 {
+                mint: ticket_mint.clone(),
+            },
+        ),
+        0,
+        &ctx.accounts.collection_mint.key(),
+        Some(&ctx.accounts.collection_mint.key()),
+    )?;
+
+    // The buyer's token account
+    require_keys_eq!(
+        destination.key(),
+        get_associated_token_address_with_program_id(
+            &ctx.accounts.payer.key(),
+            &mint_address,
+            &ctx.accounts.token_program.key(),
+        ),
+        ErrorCode::IncorrectTicketAccounts
+    );
+    associated_token::create(CpiContext::new(
+        ctx.accounts.associated_token_program.to_account_info(),
+        associated_token::Create {
+            payer: ctx.accounts.payer.to_account_info(),
+            associated_token: destination.clone(),
+            authority: ctx.accounts.payer.to_account_info(),
+            mint: ticket_mint.clone(),
+            system_program: ctx.accounts.system_program.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+    ))?;
+
+    // Ticket record
+    let (record_address, record_bump) = Pubkey::find_program_address(
+        &[
+            b"ticket_record".as_ref(),
+            lottery_id.as_ref(),
+            round_number.as_ref(),
+            ticket_index.as_ref(),
+        ],
+        ctx.program_id,
+    );
+    require_keys_eq!(
+        ticket_record.key(),
+        record_address,
+        ErrorCode::IncorrectTicketAccounts
+    );
+    let record_space = 8 + TicketRecord::INIT_SPACE;
+    system_program::create_account(
+        CpiContext::new_with_signer(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::CreateAccount {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ticket_record.clone(),
+            },
+            &[&[
+                b"ticket_record".as_ref(),
+                lottery_id.as_ref(),
+                round_number.as_ref(),
+                ticket_index.as_ref(),
+                &[record_bump],
+            ]],
+        ),
+        rent.minimum_balance(record_space),
+        record_space as u64,
+        ctx.program_id,
+    )?;
+    TicketRecord {
+        bump: record_bump,
+        buyer: ctx.accounts.payer.key(),
+        ticket_mint: mint_address,
+        round_number: token_lottery.round_number,
+        ticket_index: ticket_num,
+        payment_index: PAYMENT_INDEX_SOL,
+    }
+    .try_serialize(&mut &mut ticket_record.try_borrow_mut_data()?[..])?;
+
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
+
+    mint_to(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ticket_mint.clone(),
+                to: destination.clone(),
+                authority: ctx.accounts.collection_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            CreateMetadataAccountsV3 {
+                metadata: metadata.clone(),
+                mint: ticket_mint.clone(),
+                mint_authority: ctx.accounts.collection_mint.to_account_info(),
+                update_authority: ctx.accounts.collection_mint.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        DataV2 {
+            name: token_lottery.ticket_name(token_lottery.round_number, ticket_num),
+            symbol: token_lottery.symbol.clone(),
+            uri: token_lottery.ticket_uri(ticket_num),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None,
+    )?;
+
+    create_master_edition_v3(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            CreateMasterEditionV3 {
+                payer: ctx.accounts.payer.to_account_info(),
+                mint: ticket_mint.clone(),
+                edition: master_edition.clone(),
+                mint_authority: ctx.accounts.collection_mint.to_account_info(),
+                update_authority: ctx.accounts.collection_mint.to_account_info(),
+                metadata: metadata.clone(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        TICKET_MAX_SUPPLY,
+    )?;
+
+    let authority_seeds: &[&[u8]] = &[
+        b"collection_authority".as_ref(),
+        lottery_id.as_ref(),
+        &[ctx.bumps.collection_authority],
+    ];
+    if TokenLottery::is_tracked_in_collection(ticket_num) {
+        set_and_verify_sized_collection_item(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                SetAndVerifySizedCollectionItem {
+                    metadata: metadata.clone(),
+                    collection_authority: ctx.accounts.collection_authority.to_account_info(),
+                    payer: ctx.accounts.payer.to_account_info(),
+                    update_authority: ctx.accounts.collection_mint.to_account_info(),
+                    collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                    collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                    collection_master_edition: ctx
+                        .accounts
+                        .collection_master_edition
+                        .to_account_info(),
+                },
+                &[signer_seeds[0], authority_seeds],
+            )
+            .with_remaining_accounts(vec![ctx
+                .accounts
+                .collection_authority_record
+                .to_account_info()]),
+            Some(ctx.accounts.collection_authority_record.key()),
+        )?;
+    } else {
+        msg!("Collection size limit reached, ticket not verified into collection");
+    }
+
+    if token_lottery.resale_lock {
+        approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: destination.clone(),
+                    delegate: ctx.accounts.collection_authority.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        freeze_delegated_account(CpiContext::new_with_signer(
+            ctx.accounts.token_metadata_program.to_account_info(),
+            FreezeDelegatedAccount {
+                metadata: metadata.clone(),
+                delegate: ctx.accounts.collection_authority.to_account_info(),
+                token_account: destination.clone(),
+                edition: master_edition.clone(),
+                mint: ticket_mint.clone(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+            },
+            &[authority_seeds],
+        ))?;
+    }
+
+    Ok(())
+}
@@ -1,17 +1,28 @@
 use anchor_lang::prelude::*;
+#[cfg(feature = "profile")]
+use anchor_lang::solana_program::log::sol_log_compute_units;
+use anchor_lang::solana_program::program_option::COption;
 use anchor_lang::system_program;
 use anchor_spl::metadata::{
-    create_master_edition_v3, create_metadata_accounts_v3,
+    create_master_edition_v3, create_metadata_accounts_v3, freeze_delegated_account,
     mpl_token_metadata::types::{CollectionDetails, Creator, DataV2},
     set_and_verify_sized_collection_item, sign_metadata, CreateMasterEditionV3,
-    CreateMetadataAccountsV3, Metadata, MetadataAccount, SetAndVerifySizedCollectionItem,
-    SignMetadata,
+    CreateMetadataAccountsV3, FreezeDelegatedAccount, Metadata, MetadataAccount,
+    SetAndVerifySizedCollectionItem, SignMetadata,
 };
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
+    token_interface::{
+        approve, mint_to, transfer_checked, Approve, Mint, MintTo, TokenAccount, TokenInterface,
+        TransferChecked,
+    },
 };
 
+use crate::constants::{FEE_MODE_DEFERRED, PAYMENT_INDEX_SOL};
+use crate::events::{InstantWin, TicketPurchased};
+use crate::state::{BuyerRecord, LotteryVault, TicketRecord};
+use crate::time::schedule_time;
+
 /// Accounts required to buy a lottery ticket.
 /// Handles:
 /// - Payment transfer
@@ -26,18 +37,32 @@ pub struct BuyTicket<'info> {
     pub payer: Signer<'info>,
 
     /// TokenLottery state account tracking the current lottery.
+    /// `ticket_num` is bounds-checked here, before it is used as the ticket mint seed.
     #[account(
         mut,
-        seeds = [b"token_lottery".as_ref()],
-        bump = token_lottery.bump
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+        constraint = token_lottery.has_ticket_capacity() @ ErrorCode::TicketNumOutOfBounds,
     )]
     pub token_lottery: Account<'info, TokenLottery>,
 
+    /// Escrow receiving the ticket price and paying instant prizes.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Box<Account<'info, LotteryVault>>,
+
     /// Mint for the specific ticket being purchased.
     #[account(
         init,
         payer = payer,
-        seeds = [token_lottery.ticket_num.to_le_bytes().as_ref()],
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            token_lottery.ticket_num.to_le_bytes().as_ref(),
+        ],
         bump,
         mint::decimals = 0,
         mint::authority = collection_mint,
@@ -56,6 +81,31 @@ pub struct BuyTicket<'info> {
     )]
     pub destination: InterfaceAccount<'info, TokenAccount>,
 
+    /// Records who bought the ticket, so the winner can be resolved to a wallet.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + TicketRecord::INIT_SPACE,
+        seeds = [
+            b"ticket_record".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            token_lottery.ticket_num.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub ticket_record: Account<'info, TicketRecord>,
+
+    /// Counts the payer's tickets this round, for `max_tickets_per_wallet`.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer".as_ref(), token_lottery.key().as_ref(), payer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_record: Box<Account<'info, BuyerRecord>>,
+
     /// Metadata account for the minted ticket.
     #[account(
         mut,
@@ -98,11 +148,31 @@ pub struct BuyTicket<'info> {
     /// Mint account of the collection.
     #[account(
         mut,
-        seeds = [b"collection_mint".as_ref()],
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub collection_mint: InterfaceAccount<'info, Mint>,
 
+    /// Delegated collection authority verifying the ticket into the collection,
+    /// and the delegate freezing it under `resale_lock`.
+    /// CHECK: Only used as a PDA signer, it holds no data.
+    #[account(
+        seeds = [b"collection_authority".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// Metaplex record approving `collection_authority` on the collection.
+    /// CHECK: Validated by the Metaplex program during verification.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            collection_mint.key().as_ref(), b"collection_authority",
+            collection_authority.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub collection_authority_record: UncheckedAccount<'info>,
+
     /// Associated token program
     pub associated_token_program: Program<'info, AssociatedToken>,
 
@@ -117,45 +187,226 @@ pub struct BuyTicket<'info> {
 
     /// Rent sysvar for account creation
     pub rent: Sysvar<'info, Rent>,
+
+    /// Mint of the selected payment token. Only for token payments.
+    pub payment_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// The payer's token account paying for the ticket. Only for token payments.
+    #[account(mut)]
+    pub payer_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// A token account of the vault for the payment mint, receiving the
+    /// ticket price. Only for token payments.
+    #[account(mut)]
+    pub vault_payment_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 }
 
 /// Buys a lottery ticket for the caller.
 ///
 /// Steps performed:
-/// 1. Check if the lottery is currently open.
-/// 2. Transfer SOL from payer to the lottery pot.
-/// 3. Mint the NFT ticket.
-/// 4. Create metadata for the ticket.
-/// 5. Create the master edition.
-/// 6. Verify the NFT as part of the collection.
-/// 7. Increment the lottery ticket counter.
+/// 1. Check if the lottery is currently open, not paused or sold out, and the
+///    payer may participate, counting the ticket against
+///    `max_tickets_per_wallet`.
+/// 2. Check the payer sent at least the ticket price, holds the minimum balance
+///    and stays within `max_spend_per_wallet`.
+/// 3. Transfer the amount paid from payer to the lottery pot, less the fee
+///    which is split between the configured treasuries, and return whatever
+///    exceeds the ticket price. Under `FEE_MODE_DEFERRED` the fee is held on
+///    the lottery for `withdraw_fees`. Tickets paid with a payment token are
+///    charged that token's price in full, into the vault's token account for
+///    it.
+/// 4. Mint the NFT ticket and check the buyer received it.
+/// 5. Create metadata for the ticket.
+/// 6. Create the master edition.
+/// 7. Verify the NFT as part of the collection, up to `MAX_COLLECTION_SIZE` tickets.
+/// 8. Freeze the ticket until `unlock_ticket` if `resale_lock` is set.
+/// 9. Pay an instant prize from the pot if the ticket rolls one.
+/// 10. Record the buyer in the ticket's `TicketRecord`.
+/// 11. Extend `lottery_end` if the ticket was bought within the anti-snipe window.
+/// 12. Increment the lottery ticket counter.
+/// 13. Emit `TicketPurchased`.
 ///
 /// # Arguments
 /// * `ctx` - Context containing BuyTicket accounts
-pub fn process_buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
-    let clock = Clock::get()?;
-    let ticket_name = NAME.to_owned() + ctx.accounts.token_lottery.ticket_num.to_string().as_str();
+/// * `amount` - Lamports, or base units of the payment token, the buyer pays.
+///   The whole amount is taken, then anything above the current ticket price
+///   is returned to the payer.
+/// * `payment_index` - `PAYMENT_INDEX_SOL` to pay in SOL, or `i + 1` to pay
+///   with the `i`th of `payment_tokens`, passing the payment token accounts.
+///
+/// Under `FEE_MODE_IMMEDIATE` the treasury accounts must be passed as
+/// remaining accounts, in the order they were configured.
+///
+/// Built with the `profile` feature, logs the compute units left at the
+/// start and end of the handler, for sizing compute budgets.
+///
+/// Aggregators may call this through CPI on behalf of users. The payer can
+/// then be a PDA of the calling program, signed for with its seeds via
+/// `invoke_signed`. It must be a system-owned account without data. It must
+/// hold the ticket price, `min_buyer_balance`, and the rent for the ticket
+/// mint, token account, metadata, master edition, `TicketRecord` and, on its
+/// first ticket, `BuyerRecord`. The ticket is minted to the payer's
+/// associated token account. See `programs/cpi-buyer` for a minimal caller.
+pub fn process_buy_ticket<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyTicket<'info>>,
+    amount: u64,
+    payment_index: u8,
+) -> Result<()> {
+    #[cfg(feature = "profile")]
+    sol_log_compute_units();
 
-    if clock.slot < ctx.accounts.token_lottery.lottery_start
-        || clock.slot > ctx.accounts.token_lottery.lottery_end
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let ticket_name = ctx.accounts.token_lottery.ticket_name(
+        ctx.accounts.token_lottery.round_number,
+        ctx.accounts.token_lottery.ticket_num,
+    );
+    let ticket_uri = ctx
+        .accounts
+        .token_lottery
+        .ticket_uri(ctx.accounts.token_lottery.ticket_num);
+
+    require!(
+        !ctx.accounts.token_lottery.cancelled,
+        ErrorCode::LotteryCancelled
+    );
+    require!(!ctx.accounts.token_lottery.paused, ErrorCode::LotteryPaused);
+    if now < ctx.accounts.token_lottery.lottery_start
+        || now > ctx.accounts.token_lottery.lottery_end
     {
         return Err(ErrorCode::LotteryNotOpen.into());
     }
+    require!(
+        ctx.accounts.token_lottery.ticket_num < ctx.accounts.token_lottery.max_tickets
+            || ctx.accounts.token_lottery.max_tickets == 0,
+        ErrorCode::SoldOut
+    );
 
-    system_program::transfer(
-        CpiContext::new(
-            ctx.accounts.system_program.to_account_info(),
-            system_program::Transfer {
-                from: ctx.accounts.payer.to_account_info(),
-                to: ctx.accounts.token_lottery.to_account_info(),
-            },
-        ),
-        ctx.accounts.token_lottery.price,
-    )?;
+    let round_number = ctx.accounts.token_lottery.round_number;
+    let max_tickets_per_wallet = ctx.accounts.token_lottery.max_tickets_per_wallet;
+    let buyer_record = &mut ctx.accounts.buyer_record;
+    if buyer_record.round_number != round_number {
+        buyer_record.round_number = round_number;
+        buyer_record.tickets = 0;
+        buyer_record.spent = 0;
+    }
+    buyer_record.bump = ctx.bumps.buyer_record;
+    require!(
+        max_tickets_per_wallet == 0 || buyer_record.tickets < max_tickets_per_wallet,
+        ErrorCode::WalletLimitExceeded
+    );
+    buyer_record.tickets += 1;
+
+    require!(
+        !ctx.accounts.token_lottery.authority_cannot_buy
+            || ctx.accounts.payer.key() != ctx.accounts.token_lottery.authority,
+        ErrorCode::AuthorityCannotParticipate
+    );
+
+    // The init constraint sets the collection mint as the ticket mint
+    // authority; check it so a drifting constraint can't mint unverified tickets.
+    require!(
+        ctx.accounts.ticket_mint.mint_authority
+            == COption::Some(ctx.accounts.collection_mint.key()),
+        ErrorCode::IncorrectMintAuthority
+    );
+
+    let price = if payment_index == PAYMENT_INDEX_SOL {
+        let price = ctx.accounts.token_lottery.price;
+        require!(amount >= price, ErrorCode::InsufficientPayment);
+        let min_balance = ctx
+            .accounts
+            .token_lottery
+            .min_buyer_balance
+            .saturating_add(price);
+        require!(
+            ctx.accounts.payer.lamports() >= min_balance,
+            ErrorCode::BuyerBalanceTooLow
+        );
 
-    ctx.accounts.token_lottery.lottery_pot_amount += ctx.accounts.token_lottery.price;
+        let max_spend_per_wallet = ctx.accounts.token_lottery.max_spend_per_wallet;
+        let buyer_record = &mut ctx.accounts.buyer_record;
+        buyer_record.spent = buyer_record.spent.saturating_add(price);
+        require!(
+            max_spend_per_wallet == 0 || buyer_record.spent <= max_spend_per_wallet,
+            ErrorCode::WalletSpendCapExceeded
+        );
 
-    let signer_seeds: &[&[&[u8]]] = &[&[b"collection_mint".as_ref(), &[ctx.bumps.collection_mint]]];
+        let fee = ctx.accounts.token_lottery.fee_for(price);
+        let deferred_fee = if ctx.accounts.token_lottery.fee_mode == FEE_MODE_DEFERRED {
+            fee
+        } else {
+            let treasury_amounts = ctx.accounts.token_lottery.treasury_amounts(fee);
+            require!(
+                ctx.remaining_accounts.len() >= treasury_amounts.len(),
+                ErrorCode::IncorrectTreasury
+            );
+            for ((treasury, treasury_account), treasury_amount) in ctx
+                .accounts
+                .token_lottery
+                .treasuries
+                .iter()
+                .zip(ctx.remaining_accounts.iter())
+                .zip(treasury_amounts)
+            {
+                require_keys_eq!(
+                    treasury_account.key(),
+                    treasury.recipient,
+                    ErrorCode::IncorrectTreasury
+                );
+                system_program::transfer(
+                    CpiContext::new(
+                        ctx.accounts.system_program.to_account_info(),
+                        system_program::Transfer {
+                            from: ctx.accounts.payer.to_account_info(),
+                            to: treasury_account.clone(),
+                        },
+                    ),
+                    treasury_amount,
+                )?;
+            }
+            0
+        };
+
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.lottery_vault.to_account_info(),
+                },
+            ),
+            amount - fee + deferred_fee,
+        )?;
+
+        let overpayment = amount - price;
+        if overpayment > 0 {
+            **ctx
+                .accounts
+                .lottery_vault
+                .to_account_info()
+                .try_borrow_mut_lamports()? -= overpayment;
+            **ctx.accounts.payer.try_borrow_mut_lamports()? += overpayment;
+            msg!("Returned overpayment of {}", overpayment);
+        }
+
+        ctx.accounts.token_lottery.lottery_pot_amount += price - fee;
+        ctx.accounts.token_lottery.accrued_fees += deferred_fee;
+        ctx.accounts.token_lottery.gross_sales += price;
+        ctx.accounts.token_lottery.total_fees += fee;
+        price
+    } else {
+        pay_with_token(ctx.accounts, payment_index, amount)?
+    };
+
+    let lottery_id = ctx.accounts.token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
 
     // Mint Ticket
     mint_to(
@@ -171,6 +422,16 @@ pub fn process_buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
         1,
     )?;
 
+    // `destination` was deserialized before the mint, so reload it to check
+    // the ticket actually arrived. `token_lottery` is owned by this program,
+    // so no CPI can write to it, and reloading it would drop the payment
+    // accounting above, which is only saved when the handler returns.
+    ctx.accounts.destination.reload()?;
+    require!(
+        ctx.accounts.destination.amount == 1,
+        ErrorCode::TicketNotMinted
+    );
+
     create_metadata_accounts_v3(
         CpiContext::new_with_signer(
             ctx.accounts.token_metadata_program.to_account_info(),
@@ -187,8 +448,8 @@ pub fn process_buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
         ),
         DataV2 {
             name: ticket_name,
-            symbol: SYMBOL.to_string(),
-            uri: URI.to_string(),
+            symbol: ctx.accounts.token_lottery.symbol.clone(),
+            uri: ticket_uri,
             seller_fee_basis_points: 0,
             creators: None,
             collection: None,
@@ -215,28 +476,227 @@ pub fn process_buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
             },
             &signer_seeds,
         ),
-        Some(0),
+        TICKET_MAX_SUPPLY,
     )?;
 
-    // verify nft as part of collection
-    set_and_verify_sized_collection_item(
-        CpiContext::new_with_signer(
+    // verify nft as part of collection through the delegated collection authority,
+    // up to the tracked collection size
+    if TokenLottery::is_tracked_in_collection(ctx.accounts.token_lottery.ticket_num) {
+        let verify_signer_seeds: &[&[&[u8]]] = &[
+            &[
+                b"collection_mint".as_ref(),
+                lottery_id.as_ref(),
+                &[ctx.bumps.collection_mint],
+            ],
+            &[
+                b"collection_authority".as_ref(),
+                lottery_id.as_ref(),
+                &[ctx.bumps.collection_authority],
+            ],
+        ];
+
+        set_and_verify_sized_collection_item(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_metadata_program.to_account_info(),
+                SetAndVerifySizedCollectionItem {
+                    metadata: ctx.accounts.metadata.to_account_info(),
+                    collection_authority: ctx.accounts.collection_authority.to_account_info(),
+                    payer: ctx.accounts.payer.to_account_info(),
+                    update_authority: ctx.accounts.collection_mint.to_account_info(),
+                    collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                    collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                    collection_master_edition: ctx
+                        .accounts
+                        .collection_master_edition
+                        .to_account_info(),
+                },
+                verify_signer_seeds,
+            )
+            .with_remaining_accounts(vec![ctx
+                .accounts
+                .collection_authority_record
+                .to_account_info()]),
+            Some(ctx.accounts.collection_authority_record.key()),
+        )?;
+    } else {
+        msg!("Collection size limit reached, ticket not verified into collection");
+    }
+
+    // The master edition now holds the freeze authority, so the ticket is
+    // frozen through Metaplex with the collection authority as its delegate.
+    if ctx.accounts.token_lottery.resale_lock {
+        approve(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Approve {
+                    to: ctx.accounts.destination.to_account_info(),
+                    delegate: ctx.accounts.collection_authority.to_account_info(),
+                    authority: ctx.accounts.payer.to_account_info(),
+                },
+            ),
+            1,
+        )?;
+        freeze_delegated_account(CpiContext::new_with_signer(
             ctx.accounts.token_metadata_program.to_account_info(),
-            SetAndVerifySizedCollectionItem {
+            FreezeDelegatedAccount {
                 metadata: ctx.accounts.metadata.to_account_info(),
-                collection_authority: ctx.accounts.collection_mint.to_account_info(),
-                payer: ctx.accounts.payer.to_account_info(),
-                update_authority: ctx.accounts.collection_mint.to_account_info(),
-                collection_mint: ctx.accounts.collection_mint.to_account_info(),
-                collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
-                collection_master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+                delegate: ctx.accounts.collection_authority.to_account_info(),
+                token_account: ctx.accounts.destination.to_account_info(),
+                edition: ctx.accounts.master_edition.to_account_info(),
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
             },
-            &signer_seeds,
-        ),
-        None,
-    )?;
+            &[&[
+                b"collection_authority".as_ref(),
+                lottery_id.as_ref(),
+                &[ctx.bumps.collection_authority],
+            ]],
+        ))?;
+    }
+
+    // Instant prize, paid straight from the pot
+    let ticket_num = ctx.accounts.token_lottery.ticket_num;
+    if ctx.accounts.token_lottery.is_instant_win(now, ticket_num) {
+        let prize = ctx
+            .accounts
+            .token_lottery
+            .instant_win_prize
+            .min(ctx.accounts.token_lottery.lottery_pot_amount);
+
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= prize;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += prize;
+
+        ctx.accounts.token_lottery.lottery_pot_amount -= prize;
+        ctx.accounts.token_lottery.instant_payouts += prize;
+
+        msg!("Instant win on ticket {}: {}", ticket_num, prize);
+        if ctx.accounts.token_lottery.emit_events {
+            emit!(InstantWin {
+                buyer: ctx.accounts.payer.key(),
+                ticket_num,
+                amount: prize,
+            });
+        }
+    }
+
+    let ticket_record = &mut ctx.accounts.ticket_record;
+    ticket_record.bump = ctx.bumps.ticket_record;
+    ticket_record.buyer = ctx.accounts.payer.key();
+    ticket_record.ticket_mint = ctx.accounts.ticket_mint.key();
+    ticket_record.round_number = ctx.accounts.token_lottery.round_number;
+    ticket_record.ticket_index = ticket_num;
+    ticket_record.payment_index = payment_index;
+
+    let extension = ctx.accounts.token_lottery.anti_snipe_extension_at(now);
+    if extension > 0 {
+        let token_lottery = &mut ctx.accounts.token_lottery;
+        token_lottery.lottery_end += extension;
+        token_lottery.anti_snipe_extension += extension;
+        msg!("Extended lottery end to {}", token_lottery.lottery_end);
+    }
 
     ctx.accounts.token_lottery.ticket_num += 1;
 
+    if ctx.accounts.token_lottery.emit_events {
+        emit!(TicketPurchased {
+            token_lottery: ctx.accounts.token_lottery.key(),
+            buyer: ctx.accounts.payer.key(),
+            ticket_num,
+            price,
+            payment_index,
+            decimals: ctx.accounts.token_lottery.payment_decimals(payment_index),
+        });
+    }
+
+    #[cfg(feature = "profile")]
+    sol_log_compute_units();
+
     Ok(())
 }
+
+/// Takes `amount` of the payment token selected by `payment_index`, returns
+/// what exceeds its ticket price and adds the price to that token's pot. No
+/// fee is taken from token payments. Returns the price charged.
+fn pay_with_token(accounts: &mut BuyTicket<'_>, payment_index: u8, amount: u64) -> Result<u64> {
+    let token_index = payment_index as usize - 1;
+    let payment_token = accounts
+        .token_lottery
+        .payment_tokens
+        .get(token_index)
+        .cloned()
+        .ok_or(ErrorCode::InvalidPaymentToken)?;
+    require!(
+        amount >= payment_token.price,
+        ErrorCode::InsufficientPayment
+    );
+    require!(
+        accounts.payer.lamports() >= accounts.token_lottery.min_buyer_balance,
+        ErrorCode::BuyerBalanceTooLow
+    );
+
+    let (Some(payment_mint), Some(payer_payment_account), Some(vault_payment_account)) = (
+        accounts.payment_mint.as_ref(),
+        accounts.payer_payment_account.as_ref(),
+        accounts.vault_payment_account.as_ref(),
+    ) else {
+        return Err(ErrorCode::PaymentAccountsRequired.into());
+    };
+    require_keys_eq!(
+        payment_mint.key(),
+        payment_token.mint,
+        ErrorCode::InvalidPaymentToken
+    );
+    require_keys_eq!(
+        vault_payment_account.owner,
+        accounts.lottery_vault.key(),
+        ErrorCode::InvalidPaymentToken
+    );
+
+    transfer_checked(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: payer_payment_account.to_account_info(),
+                mint: payment_mint.to_account_info(),
+                to: vault_payment_account.to_account_info(),
+                authority: accounts.payer.to_account_info(),
+            },
+        ),
+        amount,
+        payment_mint.decimals,
+    )?;
+
+    let overpayment = amount - payment_token.price;
+    if overpayment > 0 {
+        let token_lottery_key = accounts.token_lottery.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            b"vault".as_ref(),
+            token_lottery_key.as_ref(),
+            &[accounts.lottery_vault.bump],
+        ]];
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: vault_payment_account.to_account_info(),
+                    mint: payment_mint.to_account_info(),
+                    to: payer_payment_account.to_account_info(),
+                    authority: accounts.lottery_vault.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            overpayment,
+            payment_mint.decimals,
+        )?;
+        msg!("Returned overpayment of {}", overpayment);
+    }
+
+    accounts.token_lottery.payment_token_pots[token_index] += payment_token.price;
+    accounts.token_lottery.payment_token_decimals[token_index] = payment_mint.decimals;
+
+    Ok(payment_token.price)
+}
@@ -0,0 +1,101 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::constants::FEE_MODE_DEFERRED;
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::{current_slot, schedule_time};
+
+/// Accounts required to cancel a lottery.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can cancel it.
+/// 2. The winner has not been drawn yet.
+/// 3. The draw is not overdue, so cancelling can't dodge `slash_bond`.
+/// 4. Under `FEE_MODE_IMMEDIATE`, the authority pays the fees already sent to
+///    the treasuries back into the vault, so refunds return the full price.
+#[derive(Accounts)]
+pub struct CancelLottery<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow receiving the fees covered by the authority.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// System program for covering the fees.
+    pub system_program: Program<'info, System>,
+}
+
+/// Cancels the lottery. Ticket sales and the draw are blocked for good and
+/// ticket holders reclaim their tickets' price with `refund_ticket`, within
+/// `refund_window_slots` if it is set.
+///
+/// Fees paid out on purchase are held in `accrued_fees` from then on, like
+/// deferred fees, and `sweep_unclaimed_refunds` returns what holders leave
+/// unclaimed to the authority.
+///
+/// # Arguments
+/// * `ctx` - Context containing `CancelLottery` accounts
+pub fn process_cancel_lottery(ctx: Context<CancelLottery>) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    // Once the draw is overdue the bond belongs to the pot
+    require!(!token_lottery.is_draw_overdue(now), ErrorCode::DrawOverdue);
+
+    let paid_fees = if token_lottery.fee_mode == FEE_MODE_DEFERRED {
+        0
+    } else {
+        token_lottery.total_fees
+    };
+    if paid_fees > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: ctx.accounts.lottery_vault.to_account_info(),
+                },
+            ),
+            paid_fees,
+        )?;
+        msg!("Covered {} lamports of fees for refunds", paid_fees);
+    }
+
+    let token_lottery = &mut ctx.accounts.token_lottery;
+    token_lottery.accrued_fees += paid_fees;
+    token_lottery.cancelled = true;
+    if token_lottery.refund_window_slots > 0 {
+        token_lottery.refund_deadline_slot = slot.saturating_add(token_lottery.refund_window_slots);
+        msg!(
+            "Refunds close after slot {}",
+            token_lottery.refund_deadline_slot
+        );
+    }
+    msg!("Cancelled round {}", token_lottery.round_number);
+
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+#[cfg(feature = "profile")]
+use anchor_lang::solana_program::log::sol_log_compute_units;
 use anchor_lang::system_program;
 use anchor_spl::metadata::{
     create_master_edition_v3, create_metadata_accounts_v3,
@@ -11,15 +13,28 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
 };
-use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::constants::MAX_WINNERS;
+use crate::events::WinnerChosen;
+use crate::randomness::{derive_winners, load_randomness};
+use crate::state::TicketRecord;
+use crate::time::{current_slot, schedule_time};
 
 /// Accounts required to choose a lottery winner.
 ///
 /// This ensures that:
-/// 1. Only the authorized lottery authority can pick a winner.
+/// 1. Only the authorized lottery authority or the trusted keeper can pick a
+///    winner, until `public_draw_timeout_slots` after `lottery_end` when
+///    anyone can.
 /// 2. The randomness account provided matches the lottery.
 /// 3. The lottery period has ended.
 /// 4. A winner hasn't already been chosen.
+/// 5. At least one ticket was sold, and no fewer than `num_winners`. With
+///    `winner_fraction_bps`, `num_winners` is first scaled to the sales.
+/// 6. The randomness was seeded at least `min_confirmations` slots ago,
+///    unless the trusted keeper draws.
+/// 7. At least `min_tickets` were sold. Otherwise the authority cancels the
+///    lottery so the tickets are refunded.
 #[derive(Accounts)]
 pub struct ChooseWinner<'info> {
     /// Account paying for any transaction fees.
@@ -29,7 +44,7 @@ pub struct ChooseWinner<'info> {
     /// The main lottery state account.
     #[account(
         mut,
-        seeds = [b"token_lottery".as_ref()],
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump = token_lottery.bump,
     )]
     pub token_lottery: Account<'info, TokenLottery>,
@@ -42,41 +57,146 @@ pub struct ChooseWinner<'info> {
     pub system_program: Program<'info, System>,
 }
 
+/// Draws the `num_winners` distinct winning tickets from the revealed
+/// randomness. The first of them is also stored as `winner`.
+///
+/// Alongside the winner index, stores the winning ticket mint and, if the
+/// winner's `TicketRecord` is among the remaining accounts, the wallet that
+/// bought it, so a single read of `TokenLottery` describes the winner.
+/// Clients that can't predict the winner pass every `TicketRecord` of the
+/// round.
+///
+/// Built with the `profile` feature, logs the compute units left at the
+/// start and end of the handler.
 pub fn process_choose_a_winner(ctx: Context<ChooseWinner>) -> Result<()> {
+    #[cfg(feature = "profile")]
+    sol_log_compute_units();
+
     let clock = Clock::get()?;
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
     let token_lottery = &mut ctx.accounts.token_lottery;
 
     if ctx.accounts.randomness_account_data.key() != token_lottery.randomness_account {
         return Err(ErrorCode::IncorrectRandomnessAccount.into());
     }
-    if ctx.accounts.payer.key() != token_lottery.authority {
+    if ctx.accounts.payer.key() != token_lottery.authority
+        && !token_lottery.is_trusted_keeper(&ctx.accounts.payer.key())
+        && !token_lottery.is_public_draw_open(now)
+    {
         return Err(ErrorCode::NotAuthorized.into());
     }
-    if clock.slot < token_lottery.lottery_end {
-        msg!("Current slot: {}", clock.slot);
-        msg!("End slot: {}", token_lottery.lottery_end);
+    if now < token_lottery.lottery_end {
+        msg!("Current time: {}", now);
+        msg!("End time: {}", token_lottery.lottery_end);
         return Err(ErrorCode::LotteryNotCompleted.into());
     }
     require!(
         token_lottery.winner_chosen == false,
         ErrorCode::WinnerChosen
     );
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(token_lottery.ticket_num > 0, ErrorCode::NoTicketsSold);
+    require!(
+        token_lottery.ticket_num >= token_lottery.min_tickets,
+        ErrorCode::MinimumNotReached
+    );
+    if token_lottery.winner_fraction_bps > 0 {
+        token_lottery.num_winners = token_lottery.fractional_num_winners();
+        token_lottery.prize_split = TokenLottery::even_prize_split(token_lottery.num_winners);
+    }
+    require!(
+        token_lottery.num_winners as u64 <= token_lottery.ticket_num,
+        ErrorCode::NotEnoughTickets
+    );
 
-    let randomness_data =
-        RandomnessAccountData::parse(ctx.accounts.randomness_account_data.data.borrow()).unwrap();
-    let revealed_random_value = randomness_data
-        .get_value(&clock)
-        .map_err(|_| ErrorCode::RandomnessNotResolved)?;
+    let randomness = load_randomness(
+        token_lottery.randomness_source,
+        &ctx.accounts.randomness_account_data,
+        token_lottery.randomness_seed_slot,
+    )?;
+    // The account's owner can commit it again for a seed of its choosing
+    require!(
+        randomness.seed_slot() == token_lottery.randomness_seed_slot,
+        ErrorCode::StaleRandomness
+    );
+    // The keeper is trusted not to draw across a reorg of the seed slot
+    require!(
+        token_lottery.is_trusted_keeper(&ctx.accounts.payer.key())
+            || token_lottery.is_randomness_confirmed(randomness.seed_slot(), clock.slot),
+        ErrorCode::RandomnessNotConfirmed
+    );
+    let revealed_random_value = randomness.reveal(&clock)?;
 
     msg!("Randomness result: {}", revealed_random_value[0]);
     msg!("Ticket num: {}", token_lottery.ticket_num);
 
-    let randomness_result = revealed_random_value[0] as u64 % token_lottery.ticket_num;
+    let winners = derive_winners(
+        &revealed_random_value,
+        token_lottery.ticket_num,
+        token_lottery.num_winners as usize,
+    );
+    let randomness_result = winners[0];
 
     msg!("Winner: {}", randomness_result);
+    msg!("Winners: {:?}", winners);
 
     token_lottery.winner = randomness_result;
+    token_lottery.winners[..winners.len()].copy_from_slice(&winners);
+    token_lottery.winners_claimed = [false; MAX_WINNERS];
+    token_lottery.revealed_randomness = revealed_random_value;
+
+    let lottery_seed = token_lottery.lottery_id.to_le_bytes();
+    let round_seed = token_lottery.round_number.to_le_bytes();
+    let winner_seed = randomness_result.to_le_bytes();
+    let (winner_mint, _) = Pubkey::find_program_address(
+        &[
+            lottery_seed.as_ref(),
+            round_seed.as_ref(),
+            winner_seed.as_ref(),
+        ],
+        ctx.program_id,
+    );
+    let (winner_record, _) = Pubkey::find_program_address(
+        &[
+            b"ticket_record".as_ref(),
+            lottery_seed.as_ref(),
+            round_seed.as_ref(),
+            winner_seed.as_ref(),
+        ],
+        ctx.program_id,
+    );
+    token_lottery.winner_mint = winner_mint;
+    token_lottery.winner_wallet = Pubkey::default();
+    if let Some(record_info) = ctx
+        .remaining_accounts
+        .iter()
+        .find(|account| account.key() == winner_record)
+    {
+        let record = TicketRecord::try_deserialize(&mut &record_info.data.borrow()[..])?;
+        token_lottery.winner_wallet = record.buyer;
+    }
+    msg!("Winner mint: {}", token_lottery.winner_mint);
+    msg!("Winner wallet: {}", token_lottery.winner_wallet);
     token_lottery.winner_chosen = true;
+    token_lottery.winner_chosen_slot = slot;
+
+    if token_lottery.emit_events {
+        emit!(WinnerChosen {
+            token_lottery: token_lottery.key(),
+            round_number: token_lottery.round_number,
+            winner: token_lottery.winner,
+            winner_wallet: token_lottery.winner_wallet,
+            winners,
+            total_tickets: token_lottery.ticket_num,
+        });
+    }
+
+    #[cfg(feature = "profile")]
+    sol_log_compute_units();
 
     Ok(())
 }
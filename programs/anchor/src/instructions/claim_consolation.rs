@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{Metadata, MetadataAccount};
+use anchor_spl::token_interface::{burn, Burn, Mint, TokenAccount, TokenInterface};
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+
+/// Accounts required to burn a losing ticket for a consolation payout.
+///
+/// Ensures:
+/// 1. The ticket is part of the lottery collection and held by the payer.
+/// 2. The ticket is not the winning ticket.
+#[derive(Accounts)]
+#[instruction(ticket_index: u64)]
+pub struct ClaimConsolation<'info> {
+    /// The ticket holder receiving the consolation.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the lottery's lamports.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The collection mint used for lottery tickets.
+    #[account(
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_mint: InterfaceAccount<'info, Mint>,
+
+    /// The NFT mint of the losing ticket.
+    #[account(
+        mut,
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            ticket_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    /// Metadata account for the losing ticket NFT.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: Account<'info, MetadataAccount>,
+
+    /// The payer's token account holding the ticket, burned from.
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for burning the ticket.
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Metadata program owning the ticket metadata.
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+/// Burns a verified, non-winning ticket and pays the configured consolation
+/// amount from the pot, bounded by what is left in it.
+///
+/// # Arguments
+/// * `ctx` - Context containing `ClaimConsolation` accounts
+/// * `ticket_index` - Index of the losing ticket being burned
+pub fn process_claim_consolation(ctx: Context<ClaimConsolation>, ticket_index: u64) -> Result<()> {
+    require!(
+        ctx.accounts.token_lottery.winner_chosen,
+        ErrorCode::WinnerNotChosen
+    );
+    // The winning tickets are needed to claim the prize, never burn them
+    require!(
+        !ctx.accounts.token_lottery.is_winning_ticket(ticket_index),
+        ErrorCode::CannotBurnWinningTicket
+    );
+
+    // Check if token is a part of the collection. Tickets past the tracked
+    // collection size are authenticated by the ticket mint seeds alone.
+    if TokenLottery::is_tracked_in_collection(ticket_index) {
+        let collection = ctx
+            .accounts
+            .metadata
+            .collection
+            .as_ref()
+            .ok_or(ErrorCode::NotVerifiedTicket)?;
+        require!(collection.verified, ErrorCode::NotVerifiedTicket);
+        require!(
+            collection.key == ctx.accounts.collection_mint.key(),
+            ErrorCode::IncorrectTicket
+        );
+    }
+    require!(
+        ctx.accounts.destination.amount > 0,
+        ErrorCode::IncorrectTicket
+    );
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                from: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let payout = ctx
+        .accounts
+        .token_lottery
+        .consolation_amount
+        .min(ctx.accounts.token_lottery.lottery_pot_amount);
+
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? -= payout;
+    **ctx.accounts.payer.try_borrow_mut_lamports()? += payout;
+
+    ctx.accounts.token_lottery.lottery_pot_amount -= payout;
+
+    msg!("Consolation paid for ticket {}: {}", ticket_index, payout);
+
+    Ok(())
+}
@@ -12,6 +12,10 @@ use anchor_spl::{
     token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
 };
 
+use crate::events::PrizeClaimed;
+use crate::state::LotteryVault;
+use crate::time::current_slot;
+
 /// Accounts required for claiming the lottery prize.
 ///
 /// Ensures:
@@ -20,6 +24,7 @@ use anchor_spl::{
 /// 3. The lottery winner has been selected.
 /// 4. Lamports are correctly transferred to the winner.
 #[derive(Accounts, Accounts)]
+#[instruction(winner_index: u8)]
 pub struct ClaimPrize<'info> {
     /// The account paying transaction fees.
     #[account(mut)]
@@ -28,23 +33,38 @@ pub struct ClaimPrize<'info> {
     /// The main lottery state account.
     #[account(
         mut,
-        seeds = [b"token_lottery".as_ref()],
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump = token_lottery.bump,
+        constraint = winner_index < token_lottery.num_winners @ ErrorCode::InvalidWinnerIndex,
     )]
     pub token_lottery: Account<'info, TokenLottery>,
 
+    /// Escrow holding the lottery's lamports.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
     /// The collection mint used for lottery tickets.
     #[account(
         mut,
-        seeds = [b"collection_mint".as_ref()],
+        seeds = [b"collection_mint".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump,
     )]
     pub collection_mint: InterfaceAccount<'info, Mint>,
 
-    /// The NFT mint representing the winner's ticket.
+    /// The NFT mint of the ticket that won at `winner_index`. Must be owned by
+    /// `token_program`, the program the `destination` is derived with.
     #[account(
-        seeds = [token_lottery.winner.to_le_bytes().as_ref()],
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            token_lottery.winners[winner_index as usize].to_le_bytes().as_ref(),
+        ],
         bump,
+        mint::token_program = token_program,
     )]
     pub ticket_mint: InterfaceAccount<'info, Mint>,
 
@@ -73,6 +93,120 @@ pub struct ClaimPrize<'info> {
     )]
     pub collection_metadata: Account<'info, MetadataAccount>,
 
+    /// Records the prize vesting schedule. Required when `vesting_slots` is set.
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VestingRecord::INIT_SPACE,
+        seeds = [
+            b"vesting".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub vesting_record: Option<Account<'info, VestingRecord>>,
+
+    /// Mint of the winner's badge NFT. Required when `winner_badge_collection`
+    /// is set.
+    #[account(
+        init,
+        payer = payer,
+        seeds = [
+            b"winner_badge".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            winner_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = collection_mint,
+        mint::freeze_authority = collection_mint,
+        mint::token_program = token_program
+    )]
+    pub badge_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// The winner's token account receiving the badge.
+    #[account(
+        init,
+        payer = payer,
+        associated_token::mint = badge_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub badge_destination: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Metadata account for the badge.
+    /// CHECK: Created by the Metaplex program.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), badge_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub badge_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Master edition account for the badge.
+    /// CHECK: Created by the Metaplex program.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            badge_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub badge_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// Mint of the collection the badge is verified into.
+    #[account(address = token_lottery.winner_badge_collection)]
+    pub badge_collection_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+
+    /// Metadata account of the badge collection.
+    /// CHECK: Validated by the Metaplex program during verification.
+    #[account(
+        mut,
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            badge_collection_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub badge_collection_metadata: Option<UncheckedAccount<'info>>,
+
+    /// Master edition account of the badge collection.
+    /// CHECK: Validated by the Metaplex program during verification.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            badge_collection_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub badge_collection_master_edition: Option<UncheckedAccount<'info>>,
+
+    /// Metaplex record approving `collection_authority` on the badge collection.
+    /// CHECK: Validated by the Metaplex program during verification.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            badge_collection_mint.key().as_ref(), b"collection_authority",
+            collection_authority.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub badge_collection_authority_record: Option<UncheckedAccount<'info>>,
+
+    /// Delegated collection authority verifying the badge into its collection.
+    /// CHECK: Only used as a PDA signer, it holds no data.
+    #[account(
+        seeds = [b"collection_authority".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// Associated token program for the badge token account.
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// Rent sysvar for the badge accounts.
+    pub rent: Sysvar<'info, Rent>,
+
     /// Token program for transferring tokens.
     pub token_program: Interface<'info, TokenInterface>,
 
@@ -86,14 +220,26 @@ pub struct ClaimPrize<'info> {
 /// Processes the prize claim for the winner.
 ///
 /// Steps:
-/// 1. Verify that a winner has been chosen.
+/// 1. Verify that a winner has been chosen, the claim cooldown has passed and
+///    the claim deadline hasn't.
 /// 2. Validate that the ticket NFT belongs to the correct collection and matches the winning ticket.
 /// 3. Ensure the caller owns the winning ticket.
-/// 4. Transfer the lottery pot amount to the winner and reset the pot to zero.
+/// 4. Mint a badge NFT to the winner if `winner_badge_collection` is set,
+///    verified into that collection.
+/// 5. Pay `creator_share_bps` of the winner's share of the pot, per
+///    `prize_split`, to the `creator`, passed as a remaining account.
+/// 6. Transfer the rest to the winner and mark the share claimed. If
+///    `vesting_slots` is set, it is moved into a `VestingRecord` instead and
+///    paid out by `claim_vested`.
+/// 7. Record the claimant of the first winner's prize as `winner_address`.
+/// 8. Emit `PrizeClaimed`.
 ///
 /// # Arguments
 /// * `ctx` - Context containing `ClaimPrize` accounts
-pub fn process_claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
+/// * `winner_index` - Which of the `num_winners` winners is claiming
+pub fn process_claim_prize(ctx: Context<ClaimPrize>, winner_index: u8) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+
     // Check if winner has been chosen
     msg!(
         "Winner chosen: {}",
@@ -104,18 +250,47 @@ pub fn process_claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
         ErrorCode::WinnerNotChosen
     );
 
-    // Check if token is a part of the collection
+    // Check if the claim cooldown after the draw has passed
     require!(
-        ctx.accounts.metadata.collection.as_ref().unwrap().verified,
-        ErrorCode::NotVerifiedTicket
+        slot >= ctx.accounts.token_lottery.claimable_from_slot(),
+        ErrorCode::ClaimCooldownActive
     );
     require!(
-        ctx.accounts.metadata.collection.as_ref().unwrap().key
-            == ctx.accounts.collection_mint.key(),
-        ErrorCode::IncorrectTicket
+        !ctx.accounts.token_lottery.is_claim_expired(slot),
+        ErrorCode::ClaimExpired
+    );
+
+    let winner_index = winner_index as usize;
+    require!(
+        !ctx.accounts.token_lottery.winners_claimed[winner_index],
+        ErrorCode::TicketAlreadyClaimed
+    );
+    let winning_ticket = ctx.accounts.token_lottery.winners[winner_index];
+    // The draw reduces modulo `ticket_num`, so this only fails if the state
+    // is corrupted.
+    require!(
+        ctx.accounts.token_lottery.is_sold_ticket(winning_ticket),
+        ErrorCode::InvalidWinnerIndex
     );
 
-    let ticket_name = NAME.to_owned() + &ctx.accounts.token_lottery.winner.to_string();
+    // Check if token is a part of the collection. Tickets past the tracked
+    // collection size are authenticated by the ticket mint seeds alone.
+    if TokenLottery::is_tracked_in_collection(winning_ticket) {
+        require!(
+            ctx.accounts.metadata.collection.as_ref().unwrap().verified,
+            ErrorCode::NotVerifiedTicket
+        );
+        require!(
+            ctx.accounts.metadata.collection.as_ref().unwrap().key
+                == ctx.accounts.collection_mint.key(),
+            ErrorCode::IncorrectTicket
+        );
+    }
+
+    let ticket_name = ctx
+        .accounts
+        .token_lottery
+        .ticket_name(ctx.accounts.token_lottery.round_number, winning_ticket);
     let metadata_name = ctx.accounts.metadata.name.replace("\u{0}", "");
 
     msg!("Ticket name: {}", ticket_name);
@@ -128,15 +303,201 @@ pub fn process_claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
         ErrorCode::IncorrectTicket
     );
 
-    **ctx
-        .accounts
-        .token_lottery
-        .to_account_info()
-        .try_borrow_mut_lamports()? -= ctx.accounts.token_lottery.lottery_pot_amount;
-    **ctx.accounts.payer.try_borrow_mut_lamports()? +=
-        ctx.accounts.token_lottery.lottery_pot_amount;
+    if ctx.accounts.token_lottery.winner_badge_collection != Pubkey::default() {
+        mint_winner_badge(&ctx, winning_ticket)?;
+    }
+
+    let share = ctx.accounts.token_lottery.prize_share(winner_index);
+    let creator_cut = ctx.accounts.token_lottery.creator_cut(share);
+    if creator_cut > 0 {
+        let creator = ctx
+            .remaining_accounts
+            .iter()
+            .find(|account| account.key() == ctx.accounts.token_lottery.creator)
+            .ok_or(ErrorCode::IncorrectCreator)?;
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= creator_cut;
+        **creator.try_borrow_mut_lamports()? += creator_cut;
+        msg!("Creator cut: {}", creator_cut);
+    }
+
+    let amount = share - creator_cut;
+    if ctx.accounts.token_lottery.vesting_slots > 0 {
+        let vesting_record = ctx
+            .accounts
+            .vesting_record
+            .as_mut()
+            .ok_or(ErrorCode::VestingRecordRequired)?;
+        vesting_record.bump = ctx.bumps.vesting_record.unwrap();
+        vesting_record.beneficiary = ctx.accounts.payer.key();
+        vesting_record.round_number = ctx.accounts.token_lottery.round_number;
+        vesting_record.total = amount;
+        vesting_record.claimed = 0;
+        vesting_record.start_slot = slot;
+        vesting_record.vesting_slots = ctx.accounts.token_lottery.vesting_slots;
+
+        msg!(
+            "Vesting {} lamports over {} slots",
+            vesting_record.total,
+            vesting_record.vesting_slots
+        );
+    } else {
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += amount;
+    }
+
+    ctx.accounts.token_lottery.lottery_pot_amount -= share;
+    ctx.accounts.token_lottery.winners_claimed[winner_index] = true;
+    if winner_index == 0 {
+        ctx.accounts.token_lottery.winner_address = ctx.accounts.payer.key();
+    }
+
+    if ctx.accounts.token_lottery.emit_events {
+        emit!(PrizeClaimed {
+            token_lottery: ctx.accounts.token_lottery.key(),
+            round_number: ctx.accounts.token_lottery.round_number,
+            winner: ctx.accounts.payer.key(),
+            amount,
+        });
+    }
+
+    Ok(())
+}
+
+/// Mints the winner's badge NFT and verifies it into `winner_badge_collection`
+/// through the lottery's delegated `collection_authority`.
+fn mint_winner_badge(ctx: &Context<ClaimPrize>, winning_ticket: u64) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let (
+        Some(badge_mint),
+        Some(badge_destination),
+        Some(badge_metadata),
+        Some(badge_master_edition),
+        Some(badge_collection_mint),
+        Some(badge_collection_metadata),
+        Some(badge_collection_master_edition),
+        Some(badge_collection_authority_record),
+    ) = (
+        accounts.badge_mint.as_ref(),
+        accounts.badge_destination.as_ref(),
+        accounts.badge_metadata.as_ref(),
+        accounts.badge_master_edition.as_ref(),
+        accounts.badge_collection_mint.as_ref(),
+        accounts.badge_collection_metadata.as_ref(),
+        accounts.badge_collection_master_edition.as_ref(),
+        accounts.badge_collection_authority_record.as_ref(),
+    )
+    else {
+        return Err(ErrorCode::BadgeAccountsRequired.into());
+    };
+
+    let lottery_id = accounts.token_lottery.lottery_id.to_le_bytes();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"collection_mint".as_ref(),
+        lottery_id.as_ref(),
+        &[ctx.bumps.collection_mint],
+    ]];
+
+    msg!("Minting winner badge");
+    mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            MintTo {
+                mint: badge_mint.to_account_info(),
+                to: badge_destination.to_account_info(),
+                authority: accounts.collection_mint.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        1,
+    )?;
+
+    create_metadata_accounts_v3(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.to_account_info(),
+            CreateMetadataAccountsV3 {
+                metadata: badge_metadata.to_account_info(),
+                mint: badge_mint.to_account_info(),
+                mint_authority: accounts.collection_mint.to_account_info(),
+                update_authority: accounts.collection_mint.to_account_info(),
+                payer: accounts.payer.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+                rent: accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        DataV2 {
+            name: accounts
+                .token_lottery
+                .badge_name(accounts.token_lottery.round_number, winning_ticket),
+            symbol: accounts.token_lottery.symbol.clone(),
+            uri: accounts.token_lottery.uri.clone(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            collection: None,
+            uses: None,
+        },
+        true,
+        true,
+        None,
+    )?;
+
+    create_master_edition_v3(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.to_account_info(),
+            CreateMasterEditionV3 {
+                payer: accounts.payer.to_account_info(),
+                mint: badge_mint.to_account_info(),
+                edition: badge_master_edition.to_account_info(),
+                mint_authority: accounts.collection_mint.to_account_info(),
+                update_authority: accounts.collection_mint.to_account_info(),
+                metadata: badge_metadata.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+                system_program: accounts.system_program.to_account_info(),
+                rent: accounts.rent.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        TICKET_MAX_SUPPLY,
+    )?;
+
+    let verify_signer_seeds: &[&[&[u8]]] = &[
+        &[
+            b"collection_mint".as_ref(),
+            lottery_id.as_ref(),
+            &[ctx.bumps.collection_mint],
+        ],
+        &[
+            b"collection_authority".as_ref(),
+            lottery_id.as_ref(),
+            &[ctx.bumps.collection_authority],
+        ],
+    ];
 
-    ctx.accounts.token_lottery.lottery_pot_amount = 0;
+    set_and_verify_sized_collection_item(
+        CpiContext::new_with_signer(
+            accounts.token_metadata_program.to_account_info(),
+            SetAndVerifySizedCollectionItem {
+                metadata: badge_metadata.to_account_info(),
+                collection_authority: accounts.collection_authority.to_account_info(),
+                payer: accounts.payer.to_account_info(),
+                update_authority: accounts.collection_mint.to_account_info(),
+                collection_mint: badge_collection_mint.to_account_info(),
+                collection_metadata: badge_collection_metadata.to_account_info(),
+                collection_master_edition: badge_collection_master_edition.to_account_info(),
+            },
+            verify_signer_seeds,
+        )
+        .with_remaining_accounts(vec![badge_collection_authority_record.to_account_info()]),
+        Some(badge_collection_authority_record.key()),
+    )?;
 
     Ok(())
 }
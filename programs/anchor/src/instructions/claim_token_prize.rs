@@ -0,0 +1,143 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::current_slot;
+
+/// Accounts required to claim the pot of one payment token.
+///
+/// Ensures:
+/// 1. Only the holder of the winning ticket can claim.
+/// 2. The tokens come from a token account of the lottery vault.
+#[derive(Accounts)]
+pub struct ClaimTokenPrize<'info> {
+    /// The winner receiving the tokens.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow owning the token pots.
+    #[account(
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The NFT mint of the winning ticket.
+    #[account(
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            token_lottery.winner.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    /// The payer's token account holding the winning ticket.
+    #[account(
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Mint of the payment token being claimed.
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// The vault's token account holding the pot.
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::authority = lottery_vault,
+        token::token_program = token_program,
+    )]
+    pub vault_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// The winner's token account receiving the pot.
+    #[account(
+        mut,
+        token::mint = payment_mint,
+        token::token_program = token_program,
+    )]
+    pub winner_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for transferring the tokens.
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Pays the pot collected in the payment token selected by `payment_index`
+/// to the holder of the winning ticket. Token pots are paid out in full
+/// after the claim cooldown, without vesting, until the claim deadline when
+/// `rollover_unclaimed` can roll them over. With several winners they go
+/// to the first, `winner`; `prize_split` only applies to the SOL pot.
+///
+/// # Arguments
+/// * `ctx` - Context containing `ClaimTokenPrize` accounts
+/// * `payment_index` - The payment token's `payment_index` in `buy_ticket`
+pub fn process_claim_token_prize(ctx: Context<ClaimTokenPrize>, payment_index: u8) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    require!(token_lottery.winner_chosen, ErrorCode::WinnerNotChosen);
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(
+        slot >= token_lottery.claimable_from_slot(),
+        ErrorCode::ClaimCooldownActive
+    );
+    require!(
+        !token_lottery.is_claim_expired(slot),
+        ErrorCode::ClaimExpired
+    );
+    require!(
+        ctx.accounts.destination.amount > 0,
+        ErrorCode::IncorrectTicket
+    );
+
+    let token_index = (payment_index as usize)
+        .checked_sub(1)
+        .filter(|index| *index < token_lottery.payment_tokens.len())
+        .ok_or(ErrorCode::InvalidPaymentToken)?;
+    require_keys_eq!(
+        ctx.accounts.payment_mint.key(),
+        token_lottery.payment_tokens[token_index].mint,
+        ErrorCode::InvalidPaymentToken
+    );
+    let amount = token_lottery.payment_token_pots[token_index];
+
+    let token_lottery_key = token_lottery.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault".as_ref(),
+        token_lottery_key.as_ref(),
+        &[ctx.accounts.lottery_vault.bump],
+    ]];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_payment_account.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.winner_payment_account.to_account_info(),
+                authority: ctx.accounts.lottery_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        ctx.accounts.payment_mint.decimals,
+    )?;
+
+    ctx.accounts.token_lottery.payment_token_pots[token_index] = 0;
+    msg!("Claimed {} of payment token {}", amount, payment_index);
+
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery, VestingRecord};
+use crate::time::current_slot;
+
+/// Accounts required to claim the vested part of a prize.
+///
+/// Ensures:
+/// 1. Only the beneficiary recorded at claim time can withdraw.
+/// 2. The vesting record belongs to this lottery.
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    /// The winner receiving the vested prize.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the vesting lamports.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The vesting schedule created by `claim_prize`.
+    #[account(
+        mut,
+        seeds = [
+            b"vesting".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            vesting_record.round_number.to_le_bytes().as_ref(),
+        ],
+        bump = vesting_record.bump,
+        constraint = vesting_record.beneficiary == payer.key() @ ErrorCode::NotAuthorized,
+    )]
+    pub vesting_record: Account<'info, VestingRecord>,
+}
+
+/// Pays out the part of the prize vested since the last claim.
+///
+/// Can be called repeatedly until the whole prize has vested.
+///
+/// # Arguments
+/// * `ctx` - Context containing `ClaimVested` accounts
+pub fn process_claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let vesting_record = &mut ctx.accounts.vesting_record;
+
+    let amount = vesting_record
+        .vested_at(slot)
+        .saturating_sub(vesting_record.claimed);
+    require!(amount > 0, ErrorCode::NothingVested);
+
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.payer.try_borrow_mut_lamports()? += amount;
+
+    vesting_record.claimed += amount;
+    msg!(
+        "Claimed {} vested lamports, {} of {} paid out",
+        amount,
+        vesting_record.claimed,
+        vesting_record.total
+    );
+
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery, VestingRecord};
+
+/// Accounts required to close a finished lottery.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can close it, and receives the rent.
+/// 2. The winner has been drawn, or the lottery cancelled, and everything
+///    owed has been paid out or refunded.
+#[derive(Accounts)]
+pub struct CloseLottery<'info> {
+    /// The lottery authority, receiving the rent of the closed accounts.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// The lottery vault.
+    #[account(
+        mut,
+        close = payer,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The prize vesting schedule of the current round. Required when
+    /// `vesting_slots` is set, to check the prize has fully vested.
+    #[account(
+        seeds = [
+            b"vesting".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+        ],
+        bump = vesting_record.bump,
+    )]
+    pub vesting_record: Option<Account<'info, VestingRecord>>,
+}
+
+/// Closes the lottery state and vault accounts once the prize has been paid
+/// out, or once every refund of a cancelled lottery has been settled,
+/// returning their rent and any lamports left in the vault to the authority.
+///
+/// The collection mint and its Metaplex accounts are left in place, since
+/// the tickets refer to them.
+///
+/// # Arguments
+/// * `ctx` - Context containing `CloseLottery` accounts
+pub fn process_close_lottery(ctx: Context<CloseLottery>) -> Result<()> {
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(
+        token_lottery.winner_chosen || token_lottery.cancelled,
+        ErrorCode::WinnerNotChosen
+    );
+    require!(
+        token_lottery.lottery_pot_amount == 0
+            && token_lottery.rollover_amount == 0
+            && token_lottery.payment_token_pots.iter().all(|pot| *pot == 0)
+            && token_lottery
+                .payment_token_rollovers
+                .iter()
+                .all(|rollover| *rollover == 0),
+        ErrorCode::PrizeNotClaimed
+    );
+    require!(token_lottery.accrued_fees == 0, ErrorCode::FeesNotWithdrawn);
+    if token_lottery.winner_chosen && token_lottery.vesting_slots > 0 {
+        let vesting_record = ctx
+            .accounts
+            .vesting_record
+            .as_ref()
+            .ok_or(ErrorCode::VestingRecordRequired)?;
+        require!(
+            vesting_record.claimed == vesting_record.total,
+            ErrorCode::PrizeNotClaimed
+        );
+    }
+
+    msg!("Closed lottery {}", token_lottery.lottery_id);
+
+    Ok(())
+}
@@ -11,13 +11,22 @@ use anchor_spl::{
     associated_token::AssociatedToken,
     token_interface::{mint_to, Mint, MintTo, TokenAccount, TokenInterface},
 };
-use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::randomness::load_randomness;
+use crate::state::TimingMode;
+use crate::time::schedule_time;
 
 /// Accounts required to commit a randomness account for the lottery.
 ///
 /// Ensures:
-/// 1. Only the authority of the lottery can commit the randomness.
+/// 1. Only the authority of the lottery can commit the randomness, until
+///    `public_draw_timeout_slots` after `lottery_end` when anyone can, so a
+///    missing authority can't hold up the public draw.
 /// 2. The randomness account is valid and has not been revealed previously.
+/// 3. The randomness was seeded at or after `lottery_end`, or once it has
+///    passed under `TimingMode::UnixTimestamp`.
+/// 4. No other randomness account is already committed; a stale commit has to
+///    be cleared with `rerequest_randomness` first.
 #[derive(Accounts)]
 pub struct CommitWinner<'info> {
     /// The account paying transaction fees.
@@ -27,12 +36,13 @@ pub struct CommitWinner<'info> {
     /// The main lottery state account.
     #[account(
         mut,
-        seeds = [b"token_lottery".as_ref()],
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
         bump = token_lottery.bump,
     )]
     pub token_lottery: Account<'info, TokenLottery>,
 
-    /// Randomness account from Switchboard.
+    /// Randomness account from Switchboard, or the `SlotHashes` sysvar
+    /// under `RandomnessSource::SlotHash`.
     /// CHECK: The account's data is validated manually within the handler.
     pub randomness_account_data: UncheckedAccount<'info>,
 
@@ -42,19 +52,41 @@ pub struct CommitWinner<'info> {
 
 pub fn process_commit_a_winner(ctx: Context<CommitWinner>) -> Result<()> {
     let clock = Clock::get()?;
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
     let token_lottery = &mut ctx.accounts.token_lottery;
-    if ctx.accounts.payer.key() != token_lottery.authority {
+    if ctx.accounts.payer.key() != token_lottery.authority
+        && !token_lottery.is_public_draw_open(now)
+    {
         return Err(ErrorCode::NotAuthorized.into());
     }
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(
+        token_lottery.randomness_account == Pubkey::default(),
+        ErrorCode::RandomnessAlreadyCommitted
+    );
 
-    let randomness_data =
-        RandomnessAccountData::parse(ctx.accounts.randomness_account_data.data.borrow()).unwrap();
+    // Slot-hash randomness is seeded by the commit slot itself
+    let randomness = load_randomness(
+        token_lottery.randomness_source,
+        &ctx.accounts.randomness_account_data,
+        clock.slot,
+    )?;
+    randomness.check_commit_freshness(&clock)?;
 
-    if randomness_data.seed_slot != clock.slot - 1 {
-        return Err(ErrorCode::RandomnessAlreadyRevealed.into());
-    }
+    // Randomness seeded before sales closed could be known while tickets are still sold
+    let seeded_after_sales = match token_lottery.timing_mode {
+        TimingMode::Slot => randomness.seed_slot() >= token_lottery.lottery_end,
+        // The freshness check puts the seed slot at or right before this
+        // one, so sales must have closed by now
+        TimingMode::UnixTimestamp => clock.unix_timestamp.max(0) as u64 > token_lottery.lottery_end,
+    };
+    require!(seeded_after_sales, ErrorCode::RandomnessPredatesDrawWindow);
 
     token_lottery.randomness_account = ctx.accounts.randomness_account_data.key();
+    token_lottery.randomness_seed_slot = randomness.seed_slot();
 
     Ok(())
 }
@@ -0,0 +1,56 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+use crate::time::schedule_time;
+
+/// Accounts required to close ticket sales before `lottery_end`.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can end sales.
+/// 2. Sales are still open, so `lottery_end` only ever moves earlier.
+#[derive(Accounts)]
+pub struct EndSalesEarly<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Moves `lottery_end` forward to the current time on the lottery schedule,
+/// e.g. once the pot target has been reached.
+///
+/// The remaining anti-snipe extension is used up so a late ticket can't
+/// reopen sales.
+///
+/// # Arguments
+/// * `ctx` - Context containing `EndSalesEarly` accounts
+pub fn process_end_sales_early(ctx: Context<EndSalesEarly>) -> Result<()> {
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(
+        now < token_lottery.lottery_end,
+        ErrorCode::SalesAlreadyEnded
+    );
+
+    token_lottery.lottery_end = now;
+    token_lottery.anti_snipe_extension = token_lottery.max_anti_snipe_extension;
+
+    msg!("Sales ended early at {}", now);
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::spl_token;
+use solana_program::program_pack::Pack;
+
+use crate::constants::{COLLECTION_AUTHORITY_RECORD_LEN, MAX_MASTER_EDITION_LEN, MAX_METADATA_LEN};
+use crate::state::{LotteryVault, TokenLottery};
+
+/// No accounts are needed to estimate the rent, only the `Rent` sysvar.
+#[derive(Accounts)]
+pub struct EstimateInitializationRent {}
+
+/// Sums the rent-exempt minimum of every account created by
+/// `initialize_config` and `initialize_lottery`:
+/// - the `TokenLottery` state account
+/// - the `LotteryVault` escrow
+/// - the collection mint
+/// - the collection token account
+/// - the collection metadata account
+/// - the collection master edition account
+/// - the collection authority record delegating ticket verification
+///
+/// Transaction fees and any Metaplex protocol fees are not included.
+pub fn estimate_initialization_rent(rent: &Rent) -> u64 {
+    [
+        8 + TokenLottery::INIT_SPACE,
+        8 + LotteryVault::INIT_SPACE,
+        spl_token::state::Mint::LEN,
+        spl_token::state::Account::LEN,
+        MAX_METADATA_LEN,
+        MAX_MASTER_EDITION_LEN,
+        COLLECTION_AUTHORITY_RECORD_LEN,
+    ]
+    .iter()
+    .map(|space| rent.minimum_balance(*space))
+    .sum()
+}
+
+/// Returns the lamports needed to cover rent when initializing a lottery,
+/// so clients can check the cost before calling `initialize_config` and
+/// `initialize_lottery`.
+pub fn process_estimate_initialization_rent(
+    _ctx: Context<EstimateInitializationRent>,
+) -> Result<u64> {
+    let estimate = estimate_initialization_rent(&Rent::get()?);
+    msg!("Estimated initialization rent: {}", estimate);
+    Ok(estimate)
+}
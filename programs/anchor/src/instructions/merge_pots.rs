@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::current_slot;
+
+/// Accounts required to merge the pots of cancelled lotteries into another
+/// lottery's pot.
+///
+/// Ensures:
+/// 1. Only the authority of the target lottery can merge.
+/// 2. The target's pot is still open: it isn't cancelled and its winner
+///    hasn't been drawn.
+///
+/// The merged lotteries are passed as remaining accounts and checked by
+/// `process_merge_pots`.
+#[derive(Accounts)]
+pub struct MergePots<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The lottery receiving the merged pots.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow of the target lottery, receiving the merged funds.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+}
+
+/// Moves the SOL pots of cancelled lotteries, left unclaimed after their
+/// refund deadline, into the pot of `token_lottery` for a larger draw.
+///
+/// Remaining accounts: for each merged lottery, its `TokenLottery` then its
+/// vault, both writable. Each must belong to the same authority as the
+/// target, be cancelled and be past its `refund_deadline_slot`. Only ticket
+/// revenue is merged: sponsor funds stay in the source pot for
+/// `refund_sponsorship`, and deferred fees and token pots are left for
+/// `sweep_unclaimed_refunds`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `MergePots` accounts
+pub fn process_merge_pots<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MergePots<'info>>,
+) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(!token_lottery.cancelled, ErrorCode::LotteryCancelled);
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(
+        ctx.remaining_accounts.len() >= 2 && ctx.remaining_accounts.len() % 2 == 0,
+        ErrorCode::IncorrectMergeAccounts
+    );
+
+    let mut merged = 0;
+    for accounts in ctx.remaining_accounts.chunks_exact(2) {
+        let (source_info, source_vault) = (&accounts[0], &accounts[1]);
+        require_keys_neq!(
+            source_info.key(),
+            token_lottery.key(),
+            ErrorCode::IncorrectMergeAccounts
+        );
+        let mut source = Account::<TokenLottery>::try_from(source_info)?;
+        require_keys_eq!(
+            source.authority,
+            token_lottery.authority,
+            ErrorCode::NotAuthorized
+        );
+        require!(source.cancelled, ErrorCode::LotteryNotCancelled);
+        require!(source.is_refund_expired(slot), ErrorCode::RefundNotExpired);
+        let (vault, _) = Pubkey::find_program_address(
+            &[b"vault".as_ref(), source_info.key.as_ref()],
+            ctx.program_id,
+        );
+        require_keys_eq!(source_vault.key(), vault, ErrorCode::IncorrectMergeAccounts);
+
+        let amount = source.ticket_revenue();
+        **source_vault.try_borrow_mut_lamports()? -= amount;
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? += amount;
+        source.lottery_pot_amount = source.sponsor_total;
+        source.exit(ctx.program_id)?;
+
+        merged += amount;
+        msg!(
+            "Merged {} lamports from lottery {}",
+            amount,
+            source.lottery_id
+        );
+    }
+
+    ctx.accounts.token_lottery.lottery_pot_amount += merged;
+
+    Ok(())
+}
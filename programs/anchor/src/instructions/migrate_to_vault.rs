@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+
+/// Accounts required to move a legacy lottery's pot into its vault.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can migrate it.
+/// 2. The vault is created at its PDA if the lottery predates it.
+#[derive(Accounts)]
+pub struct MigrateToVault<'info> {
+    /// The lottery authority, paying for the vault if it doesn't exist yet.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account, still holding the pot.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow the pot moves into.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + LotteryVault::INIT_SPACE,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// System program for creating the vault.
+    pub system_program: Program<'info, System>,
+}
+
+/// Moves `lottery_pot_amount` lamports from the `token_lottery` account of a
+/// lottery created before the vault into its `LotteryVault`, leaving the
+/// data account with only its rent.
+///
+/// `lottery_pot_amount` already counts these lamports, so it is unchanged;
+/// `validate_lottery` reconciles it with the vault afterwards. Fails with
+/// `NothingToMigrate` if the data account doesn't hold the pot beyond its
+/// rent, as for lotteries created with a vault.
+///
+/// # Arguments
+/// * `ctx` - Context containing `MigrateToVault` accounts
+pub fn process_migrate_to_vault(ctx: Context<MigrateToVault>) -> Result<()> {
+    if ctx.accounts.payer.key() != ctx.accounts.token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+
+    let amount = ctx.accounts.token_lottery.lottery_pot_amount;
+    let lottery_info = ctx.accounts.token_lottery.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(lottery_info.data_len());
+    require!(
+        amount > 0 && lottery_info.lamports().saturating_sub(rent_exempt_minimum) >= amount,
+        ErrorCode::NothingToMigrate
+    );
+
+    **lottery_info.try_borrow_mut_lamports()? -= amount;
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? += amount;
+    ctx.accounts.lottery_vault.bump = ctx.bumps.lottery_vault;
+
+    msg!("Moved pot of {} lamports to the vault", amount);
+
+    Ok(())
+}
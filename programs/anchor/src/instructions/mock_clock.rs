@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::state::MockClock;
+
+/// Accounts required to set the mock clock. Only built with `mock-clock`.
+#[derive(Accounts)]
+pub struct SetMockClock<'info> {
+    /// The account paying for the mock clock.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The slot override read by `current_slot`.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + MockClock::INIT_SPACE,
+        seeds = [b"mock_clock".as_ref()],
+        bump,
+    )]
+    pub mock_clock: Account<'info, MockClock>,
+
+    /// System program to create accounts.
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the slot reported to instructions that are passed the mock clock.
+///
+/// # Arguments
+/// * `ctx` - Context containing `SetMockClock` accounts
+/// * `slot` - The slot to report
+pub fn process_set_mock_clock(ctx: Context<SetMockClock>, slot: u64) -> Result<()> {
+    ctx.accounts.mock_clock.slot = slot;
+    msg!("Mock clock set to slot {}", slot);
+    Ok(())
+}
@@ -1,14 +1,109 @@
 pub use admin::*;
 pub mod admin;
 
+pub use authority_bond::*;
+pub mod authority_bond;
+
+pub use burn_pot::*;
+pub mod burn_pot;
+
+pub use buy_multiple_tickets::*;
+pub mod buy_multiple_tickets;
+
 pub use buy_ticket::*;
 pub mod buy_ticket;
 
+pub use cancel_lottery::*;
+pub mod cancel_lottery;
+
 pub use choose_winner::*;
 pub mod choose_winner;
 
+pub use claim_consolation::*;
+pub mod claim_consolation;
+
 pub use claim_prize::*;
 pub mod claim_prize;
 
+pub use claim_token_prize::*;
+pub mod claim_token_prize;
+
+pub use claim_vested::*;
+pub mod claim_vested;
+
+pub use close_lottery::*;
+pub mod close_lottery;
+
 pub use commit_winners::*;
 pub mod commit_winner;
+
+pub use end_sales_early::*;
+pub mod end_sales_early;
+
+pub use estimate_rent::*;
+pub mod estimate_rent;
+
+pub use merge_pots::*;
+pub mod merge_pots;
+
+pub use migrate_to_vault::*;
+pub mod migrate_to_vault;
+
+#[cfg(feature = "mock-clock")]
+pub use mock_clock::*;
+#[cfg(feature = "mock-clock")]
+pub mod mock_clock;
+
+pub use next_ticket::*;
+pub mod next_ticket;
+
+pub use pause_lottery::*;
+pub mod pause_lottery;
+
+pub use preview_close::*;
+pub mod preview_close;
+
+pub use refund_sponsorship::*;
+pub mod refund_sponsorship;
+
+pub use refund_ticket::*;
+pub mod refund_ticket;
+
+pub use rerequest_randomness::*;
+pub mod rerequest_randomness;
+
+pub use reset_lottery::*;
+pub mod reset_lottery;
+
+pub use rollover_unclaimed::*;
+pub mod rollover_unclaimed;
+
+pub use sponsor_pot::*;
+pub mod sponsor_pot;
+
+pub use sweep_unclaimed_refunds::*;
+pub mod sweep_unclaimed_refunds;
+
+pub use top_up_vault_rent::*;
+pub mod top_up_vault_rent;
+
+pub use transfer_authority::*;
+pub mod transfer_authority;
+
+pub use unlock_ticket::*;
+pub mod unlock_ticket;
+
+pub use update_config::*;
+pub mod update_config;
+
+pub use update_payment_mint::*;
+pub mod update_payment_mint;
+
+pub use validate_lottery::*;
+pub mod validate_lottery;
+
+pub use verify_draw::*;
+pub mod verify_draw;
+
+pub use withdraw_fees::*;
+pub mod withdraw_fees;
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::state::TokenLottery;
+
+/// Accounts required to read the number of the next ticket.
+#[derive(Accounts)]
+pub struct NextTicket<'info> {
+    /// The main lottery state account.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Returns the current `ticket_num`, the index the next ticket is minted
+/// under.
+///
+/// With `lottery_id` and `round_number`, it gives the seeds of the next
+/// ticket mint, so clients can derive its address and prepare the purchase
+/// ahead of time. Another purchase landing first takes that index.
+pub fn process_next_ticket_num(ctx: Context<NextTicket>) -> Result<u64> {
+    let ticket_num = ctx.accounts.token_lottery.ticket_num;
+    msg!("Next ticket: {}", ticket_num);
+    Ok(ticket_num)
+}
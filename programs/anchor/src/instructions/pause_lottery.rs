@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+
+/// Accounts required to pause or resume ticket sales.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can pause or resume it.
+#[derive(Accounts)]
+pub struct PauseLottery<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Stops ticket sales until `resume_lottery`, e.g. during an incident.
+///
+/// Unlike `cancel_lottery`, the round keeps its tickets and pot, and the
+/// sales window is not extended for the time spent paused.
+///
+/// # Arguments
+/// * `ctx` - Context containing `PauseLottery` accounts
+pub fn process_pause_lottery(ctx: Context<PauseLottery>) -> Result<()> {
+    set_paused(ctx, true)
+}
+
+/// Reopens ticket sales stopped by `pause_lottery`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `PauseLottery` accounts
+pub fn process_resume_lottery(ctx: Context<PauseLottery>) -> Result<()> {
+    set_paused(ctx, false)
+}
+
+fn set_paused(ctx: Context<PauseLottery>, paused: bool) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+
+    token_lottery.paused = paused;
+    msg!("Lottery paused: {}", paused);
+
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+
+use crate::events::ClosePreview;
+use crate::state::{LotteryVault, TokenLottery};
+
+/// Accounts closed by `close_lottery`, read to preview the lamports it
+/// returns. Anyone may preview, so no authority check is made.
+#[derive(Accounts)]
+pub struct PreviewClose<'info> {
+    /// The main lottery state account.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// The lottery vault.
+    #[account(
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+}
+
+/// Returns and emits the lamports `close_lottery` would return to the
+/// authority: the full balance of the state account and the vault. Once
+/// everything owed is paid out that is their rent, plus any bond still held
+/// in the vault.
+///
+/// Does not check the lottery can be closed yet.
+///
+/// # Arguments
+/// * `ctx` - Context containing `PreviewClose` accounts
+pub fn process_preview_close(ctx: Context<PreviewClose>) -> Result<u64> {
+    let reclaimable = ctx.accounts.token_lottery.to_account_info().lamports()
+        + ctx.accounts.lottery_vault.to_account_info().lamports();
+
+    msg!("Reclaimable on close: {}", reclaimable);
+    emit!(ClosePreview {
+        token_lottery: ctx.accounts.token_lottery.key(),
+        reclaimable,
+    });
+
+    Ok(reclaimable)
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, SponsorRecord, TokenLottery};
+
+/// Accounts required to return a sponsor's contribution to a cancelled round.
+///
+/// Ensures:
+/// 1. The lottery has been cancelled.
+/// 2. Only the sponsor can reclaim their contribution, and only once, since
+///    the `SponsorRecord` is closed.
+#[derive(Accounts)]
+pub struct RefundSponsorship<'info> {
+    /// The sponsor receiving their contribution back.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow the contribution is returned from.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// What the sponsor contributed to the cancelled round, closed to the
+    /// sponsor.
+    #[account(
+        mut,
+        close = payer,
+        seeds = [
+            b"sponsor".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump = sponsor_record.bump,
+    )]
+    pub sponsor_record: Account<'info, SponsorRecord>,
+}
+
+/// Returns a sponsor's contribution to a cancelled round, tracked in their
+/// `SponsorRecord`, separately from the ticket refunds of `refund_ticket`.
+///
+/// `sweep_unclaimed_refunds` never takes sponsor funds, so unlike ticket
+/// refunds this stays open after `refund_deadline_slot`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `RefundSponsorship` accounts
+pub fn process_refund_sponsorship(ctx: Context<RefundSponsorship>) -> Result<()> {
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    require!(token_lottery.cancelled, ErrorCode::LotteryNotCancelled);
+
+    // Bounded by what is left, in case the pot was burned
+    let amount = ctx
+        .accounts
+        .sponsor_record
+        .amount
+        .min(token_lottery.sponsor_total)
+        .min(token_lottery.lottery_pot_amount);
+    **ctx
+        .accounts
+        .lottery_vault
+        .to_account_info()
+        .try_borrow_mut_lamports()? -= amount;
+    **ctx.accounts.payer.try_borrow_mut_lamports()? += amount;
+
+    let token_lottery = &mut ctx.accounts.token_lottery;
+    token_lottery.sponsor_total -= amount;
+    token_lottery.lottery_pot_amount -= amount;
+    msg!("Returned {} sponsored lamports", amount);
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    burn, transfer_checked, Burn, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::constants::PAYMENT_INDEX_SOL;
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TicketRecord, TokenLottery};
+use crate::time::current_slot;
+
+/// Accounts required to refund a ticket of a cancelled lottery.
+///
+/// Ensures:
+/// 1. The lottery has been cancelled and its refund deadline hasn't passed.
+/// 2. The ticket is held by the payer, and is burned so it is refunded once.
+#[derive(Accounts)]
+#[instruction(ticket_index: u64)]
+pub struct RefundTicket<'info> {
+    /// The ticket holder receiving the refund.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow the refund is paid from.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The NFT mint of the refunded ticket.
+    #[account(
+        mut,
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            ticket_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    /// How the ticket was paid for.
+    #[account(
+        seeds = [
+            b"ticket_record".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            ticket_index.to_le_bytes().as_ref(),
+        ],
+        bump = ticket_record.bump,
+    )]
+    pub ticket_record: Account<'info, TicketRecord>,
+
+    /// The payer's token account holding the ticket, burned from.
+    #[account(
+        mut,
+        associated_token::mint = ticket_mint,
+        associated_token::authority = payer,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program for burning the ticket and refunding token payments.
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Mint of the payment token the ticket was paid with. Only for token payments.
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The vault's token account for the payment mint. Only for token payments.
+    #[account(
+        mut,
+        token::authority = lottery_vault,
+    )]
+    pub vault_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The payer's token account receiving the refund. Only for token payments.
+    #[account(mut)]
+    pub payer_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Burns a ticket of a cancelled lottery and refunds what it paid into the
+/// lottery, in the currency it was bought with.
+///
+/// SOL tickets get back their full price: the fee comes out of
+/// `accrued_fees`, where it was either held under `FEE_MODE_DEFERRED` or
+/// paid back by the authority on `cancel_lottery`. Refunds never reach into
+/// sponsor funds. Token tickets get back their
+/// payment token's full price. A ticket frozen by `resale_lock` has to be
+/// unlocked with `unlock_ticket` before it can be burned.
+///
+/// # Arguments
+/// * `ctx` - Context containing `RefundTicket` accounts
+/// * `ticket_index` - Index of the ticket being refunded
+pub fn process_refund_ticket(ctx: Context<RefundTicket>, ticket_index: u64) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    require!(
+        ctx.accounts.token_lottery.cancelled,
+        ErrorCode::LotteryNotCancelled
+    );
+    require!(
+        !ctx.accounts.token_lottery.is_refund_expired(slot),
+        ErrorCode::RefundExpired
+    );
+    require!(
+        ctx.accounts.destination.amount > 0,
+        ErrorCode::IncorrectTicket
+    );
+
+    burn(
+        CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.ticket_mint.to_account_info(),
+                from: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            },
+        ),
+        1,
+    )?;
+
+    let payment_index = ctx.accounts.ticket_record.payment_index;
+    if payment_index == PAYMENT_INDEX_SOL {
+        let token_lottery = &ctx.accounts.token_lottery;
+        let price = token_lottery.price;
+        let fee = token_lottery.fee_for(price);
+        let fee_refund = fee.min(token_lottery.accrued_fees);
+        let pot_refund = (price - fee).min(token_lottery.ticket_revenue());
+
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= pot_refund + fee_refund;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += pot_refund + fee_refund;
+
+        let token_lottery = &mut ctx.accounts.token_lottery;
+        token_lottery.lottery_pot_amount -= pot_refund;
+        token_lottery.accrued_fees -= fee_refund;
+        token_lottery.gross_sales -= pot_refund + fee_refund;
+        token_lottery.total_fees -= fee_refund;
+        msg!(
+            "Refunded ticket {}: {}",
+            ticket_index,
+            pot_refund + fee_refund
+        );
+        return Ok(());
+    }
+
+    let token_index = payment_index as usize - 1;
+    let payment_token = ctx.accounts.token_lottery.payment_tokens[token_index].clone();
+    let (Some(payment_mint), Some(vault_payment_account), Some(payer_payment_account)) = (
+        ctx.accounts.payment_mint.as_ref(),
+        ctx.accounts.vault_payment_account.as_ref(),
+        ctx.accounts.payer_payment_account.as_ref(),
+    ) else {
+        return Err(ErrorCode::PaymentAccountsRequired.into());
+    };
+    require_keys_eq!(
+        payment_mint.key(),
+        payment_token.mint,
+        ErrorCode::InvalidPaymentToken
+    );
+    let refund = payment_token
+        .price
+        .min(ctx.accounts.token_lottery.payment_token_pots[token_index]);
+
+    let token_lottery_key = ctx.accounts.token_lottery.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault".as_ref(),
+        token_lottery_key.as_ref(),
+        &[ctx.accounts.lottery_vault.bump],
+    ]];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: vault_payment_account.to_account_info(),
+                mint: payment_mint.to_account_info(),
+                to: payer_payment_account.to_account_info(),
+                authority: ctx.accounts.lottery_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        refund,
+        payment_mint.decimals,
+    )?;
+
+    ctx.accounts.token_lottery.payment_token_pots[token_index] -= refund;
+    msg!(
+        "Refunded ticket {}: {} of payment token {}",
+        ticket_index,
+        refund,
+        payment_index
+    );
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::randomness::load_randomness;
+use crate::state::{RandomnessSource, TokenLottery};
+
+/// Accounts required to discard a committed randomness account that never resolved.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can clear the commit.
+/// 2. The randomness account is the one currently committed to the lottery.
+/// 3. The lottery draws from Switchboard, not `RandomnessSource::SlotHash`.
+#[derive(Accounts)]
+pub struct RerequestRandomness<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// The stale randomness account from Switchboard.
+    /// CHECK: Must match the committed account; data is validated within the handler.
+    #[account(address = token_lottery.randomness_account @ ErrorCode::IncorrectRandomnessAccount)]
+    pub randomness_account_data: UncheckedAccount<'info>,
+}
+
+/// Clears a committed randomness account so a fresh one can be committed.
+///
+/// Only allowed when the committed randomness was never revealed and its
+/// validity window has passed. Randomness that was revealed cannot be
+/// discarded, otherwise the authority could re-roll a draw it didn't like.
+/// Slot-hash randomness never expires and is public from the slot after its
+/// commit, so it can't be discarded at all.
+///
+/// # Arguments
+/// * `ctx` - Context containing `RerequestRandomness` accounts
+pub fn process_rerequest_randomness(ctx: Context<RerequestRandomness>) -> Result<()> {
+    let clock = Clock::get()?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+    require!(
+        token_lottery.randomness_source != RandomnessSource::SlotHash,
+        ErrorCode::SlotHashNotRerequestable
+    );
+
+    let randomness = load_randomness(
+        token_lottery.randomness_source,
+        &ctx.accounts.randomness_account_data,
+        token_lottery.randomness_seed_slot,
+    )?;
+    require!(
+        randomness.has_expired(&clock),
+        ErrorCode::RandomnessStillValid
+    );
+
+    msg!(
+        "Clearing stale randomness account: {}",
+        token_lottery.randomness_account
+    );
+    token_lottery.randomness_account = Pubkey::default();
+    token_lottery.randomness_seed_slot = 0;
+
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::events::LotteryReset;
+use crate::state::TokenLottery;
+
+/// Accounts required to start the next round of a recurring lottery.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can start a new round.
+/// 2. The current round has been drawn and its prize and token pots paid out.
+#[derive(Accounts)]
+pub struct ResetLottery<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Starts a new round reusing the lottery's config and collection.
+///
+/// Clears the draw state and ticket counter and increments `round_number`,
+/// for at most `max_rounds` rounds and up to `MAX_ROUND_NUMBER`. The round is part of the ticket mint seeds and
+/// ticket names, so the new round's tickets don't collide with earlier
+/// rounds. Prizes rolled over by `rollover_unclaimed` seed the new round's
+/// SOL and token pots. The authority's bond carries over, see
+/// `withdraw_bond`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `ResetLottery` accounts
+/// * `start` - When the new round opens, in slots or UNIX timestamps per
+///   the lottery's `timing_mode`
+/// * `end` - When the new round closes, in slots or UNIX timestamps per
+///   the lottery's `timing_mode`
+pub fn process_reset_lottery(ctx: Context<ResetLottery>, start: u64, end: u64) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(token_lottery.winner_chosen, ErrorCode::WinnerNotChosen);
+    require!(
+        token_lottery.lottery_pot_amount == 0
+            && token_lottery.payment_token_pots.iter().all(|pot| *pot == 0),
+        ErrorCode::PrizeNotClaimed
+    );
+    require!(
+        token_lottery.has_round_capacity(),
+        ErrorCode::RoundLimitReached
+    );
+
+    token_lottery.round_number += 1;
+    token_lottery.lottery_start = start;
+    token_lottery.lottery_end = end;
+    token_lottery.anti_snipe_extension = 0;
+    token_lottery.ticket_num = 0;
+    token_lottery.winner = 0;
+    token_lottery.winner_mint = Pubkey::default();
+    token_lottery.winner_wallet = Pubkey::default();
+    token_lottery.winner_address = Pubkey::default();
+    token_lottery.revealed_randomness = [0; 32];
+    token_lottery.winner_chosen = false;
+    token_lottery.winner_chosen_slot = 0;
+    token_lottery.randomness_account = Pubkey::default();
+    token_lottery.randomness_seed_slot = 0;
+    token_lottery.sponsor_total = 0;
+    token_lottery.gross_sales = 0;
+    token_lottery.total_fees = 0;
+    token_lottery.instant_payouts = 0;
+    token_lottery.lottery_pot_amount = token_lottery.rollover_amount;
+    token_lottery.rollover_amount = 0;
+    token_lottery.payment_token_pots = token_lottery.payment_token_rollovers.clone();
+    token_lottery.payment_token_rollovers.fill(0);
+
+    msg!("Starting round: {}", token_lottery.round_number);
+    if token_lottery.emit_events {
+        emit!(LotteryReset {
+            token_lottery: token_lottery.key(),
+            round_number: token_lottery.round_number,
+            lottery_start: start,
+            lottery_end: end,
+        });
+    }
+
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+use crate::time::current_slot;
+
+/// Accounts required to roll an unclaimed prize into the next round.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can roll the prize over.
+/// 2. The claim deadline of the drawn round has passed.
+#[derive(Accounts)]
+pub struct RolloverUnclaimed<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Sets aside the pot left unclaimed after `claim_deadline_slots` as
+/// `rollover_amount`, and the token pots as `payment_token_rollovers`, so
+/// `reset_lottery` can start the next round with them.
+///
+/// Every round of a lottery shares the `LotteryVault` and its token
+/// accounts, so the funds stay where they are and only the accounting moves.
+///
+/// # Arguments
+/// * `ctx` - Context containing `RolloverUnclaimed` accounts
+pub fn process_rollover_unclaimed(ctx: Context<RolloverUnclaimed>) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(token_lottery.winner_chosen, ErrorCode::WinnerNotChosen);
+    require!(
+        token_lottery.is_claim_expired(slot),
+        ErrorCode::ClaimNotExpired
+    );
+
+    let amount = token_lottery.lottery_pot_amount;
+    token_lottery.rollover_amount += amount;
+    token_lottery.lottery_pot_amount = 0;
+    for index in 0..token_lottery.payment_token_pots.len() {
+        let pot = token_lottery.payment_token_pots[index];
+        token_lottery.payment_token_rollovers[index] += pot;
+        token_lottery.payment_token_pots[index] = 0;
+    }
+    msg!(
+        "Rolled {} unclaimed lamports over from round {}",
+        amount,
+        token_lottery.round_number
+    );
+
+    Ok(())
+}
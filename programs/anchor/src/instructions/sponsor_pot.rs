@@ -0,0 +1,105 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, SponsorRecord, TokenLottery};
+
+/// Accounts required to add sponsor funds to the pot.
+#[derive(Accounts)]
+pub struct SponsorPot<'info> {
+    /// The sponsor funding the pot.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the lottery's lamports.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// Tracks what this sponsor contributed in the current round, so it can
+    /// be returned separately from ticket revenue.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + SponsorRecord::INIT_SPACE,
+        seeds = [
+            b"sponsor".as_ref(),
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            token_lottery.round_number.to_le_bytes().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub sponsor_record: Account<'info, SponsorRecord>,
+
+    /// System program for lamports transfer.
+    pub system_program: Program<'info, System>,
+}
+
+/// Adds `amount` lamports from the sponsor to the lottery pot.
+///
+/// Sponsor funds are counted in `lottery_pot_amount` like ticket revenue,
+/// but are also tracked in `sponsor_total` and in a per-sponsor
+/// `SponsorRecord`, so a cancelled round can refund buyers with
+/// `refund_ticket` and return sponsor funds with `refund_sponsorship`
+/// independently.
+///
+/// # Arguments
+/// * `ctx` - Context containing `SponsorPot` accounts
+/// * `amount` - Lamports to add to the pot
+pub fn process_sponsor_pot(ctx: Context<SponsorPot>, amount: u64) -> Result<()> {
+    require!(amount > 0, ErrorCode::InvalidSponsorAmount);
+    require!(
+        amount >= ctx.accounts.token_lottery.min_sponsor_amount,
+        ErrorCode::SponsorAmountTooLow
+    );
+    require!(
+        !ctx.accounts.token_lottery.winner_chosen,
+        ErrorCode::WinnerChosen
+    );
+    require!(
+        !ctx.accounts.token_lottery.cancelled,
+        ErrorCode::LotteryCancelled
+    );
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.lottery_vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    let sponsor_record = &mut ctx.accounts.sponsor_record;
+    sponsor_record.bump = ctx.bumps.sponsor_record;
+    sponsor_record.sponsor = ctx.accounts.payer.key();
+    sponsor_record.round_number = ctx.accounts.token_lottery.round_number;
+    sponsor_record.amount += amount;
+
+    let token_lottery = &mut ctx.accounts.token_lottery;
+    token_lottery.lottery_pot_amount += amount;
+    token_lottery.sponsor_total += amount;
+
+    msg!(
+        "Sponsored pot with {} lamports, {} from tickets and {} from sponsors",
+        amount,
+        token_lottery.ticket_revenue(),
+        token_lottery.sponsor_total
+    );
+
+    Ok(())
+}
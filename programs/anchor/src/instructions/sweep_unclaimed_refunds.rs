@@ -0,0 +1,155 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+use crate::constants::PAYMENT_INDEX_SOL;
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+use crate::time::current_slot;
+
+/// Accounts required to reclaim the refunds of a cancelled lottery left
+/// unclaimed after its refund deadline.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can sweep, and receives the funds.
+/// 2. The lottery was cancelled and its refund deadline has passed.
+#[derive(Accounts)]
+pub struct SweepUnclaimedRefunds<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow the unclaimed refunds are swept from.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// Token program for sweeping a token pot. Only for token pots.
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+
+    /// Mint of the payment token being swept. Only for token pots.
+    pub payment_mint: Option<InterfaceAccount<'info, Mint>>,
+
+    /// The vault's token account for the payment mint. Only for token pots.
+    #[account(
+        mut,
+        token::authority = lottery_vault,
+    )]
+    pub vault_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The authority's token account receiving the pot. Only for token pots.
+    #[account(mut)]
+    pub payer_payment_account: Option<InterfaceAccount<'info, TokenAccount>>,
+}
+
+/// Pays what is left of a cancelled lottery's funds to the authority once
+/// `refund_deadline_slot` has passed, so the lottery can be closed.
+///
+/// With `PAYMENT_INDEX_SOL`, sweeps the ticket revenue left in the SOL pot
+/// and deferred fees still held. Sponsor funds stay in the pot for
+/// `refund_sponsorship`. Otherwise sweeps the pot of the payment token
+/// selected by `payment_index`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `SweepUnclaimedRefunds` accounts
+/// * `payment_index` - `PAYMENT_INDEX_SOL`, or the payment token's
+///   `payment_index` in `buy_ticket`
+pub fn process_sweep_unclaimed_refunds(
+    ctx: Context<SweepUnclaimedRefunds>,
+    payment_index: u8,
+) -> Result<()> {
+    let slot = current_slot(ctx.remaining_accounts)?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(token_lottery.cancelled, ErrorCode::LotteryNotCancelled);
+    require!(
+        token_lottery.is_refund_expired(slot),
+        ErrorCode::RefundNotExpired
+    );
+
+    if payment_index == PAYMENT_INDEX_SOL {
+        let amount = token_lottery.ticket_revenue() + token_lottery.accrued_fees;
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= amount;
+        **ctx.accounts.payer.try_borrow_mut_lamports()? += amount;
+
+        let token_lottery = &mut ctx.accounts.token_lottery;
+        token_lottery.lottery_pot_amount = token_lottery.sponsor_total;
+        token_lottery.accrued_fees = 0;
+        msg!("Swept {} unclaimed lamports", amount);
+        return Ok(());
+    }
+
+    let token_index = (payment_index as usize)
+        .checked_sub(1)
+        .filter(|index| *index < token_lottery.payment_tokens.len())
+        .ok_or(ErrorCode::InvalidPaymentToken)?;
+    let (
+        Some(token_program),
+        Some(payment_mint),
+        Some(vault_payment_account),
+        Some(payer_payment_account),
+    ) = (
+        ctx.accounts.token_program.as_ref(),
+        ctx.accounts.payment_mint.as_ref(),
+        ctx.accounts.vault_payment_account.as_ref(),
+        ctx.accounts.payer_payment_account.as_ref(),
+    )
+    else {
+        return Err(ErrorCode::PaymentAccountsRequired.into());
+    };
+    require_keys_eq!(
+        payment_mint.key(),
+        token_lottery.payment_tokens[token_index].mint,
+        ErrorCode::InvalidPaymentToken
+    );
+    let amount = token_lottery.payment_token_pots[token_index];
+
+    let token_lottery_key = token_lottery.key();
+    let signer_seeds: &[&[&[u8]]] = &[&[
+        b"vault".as_ref(),
+        token_lottery_key.as_ref(),
+        &[ctx.accounts.lottery_vault.bump],
+    ]];
+    transfer_checked(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            TransferChecked {
+                from: vault_payment_account.to_account_info(),
+                mint: payment_mint.to_account_info(),
+                to: payer_payment_account.to_account_info(),
+                authority: ctx.accounts.lottery_vault.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+        payment_mint.decimals,
+    )?;
+
+    ctx.accounts.token_lottery.payment_token_pots[token_index] = 0;
+    msg!(
+        "Swept {} unclaimed of payment token {}",
+        amount,
+        payment_index
+    );
+
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, TokenLottery};
+
+/// Accounts required to restore the rent exemption of a lottery vault.
+///
+/// Anyone may top up a vault, so no authority check is made.
+#[derive(Accounts)]
+pub struct TopUpVaultRent<'info> {
+    /// The account paying for the top-up.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow whose rent exemption is restored.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// System program for lamports transfer.
+    pub system_program: Program<'info, System>,
+}
+
+/// Transfers the lamports the vault lacks to be rent-exempt from the payer,
+/// e.g. after a rent increase. Fails with `VaultRentExempt` if nothing is
+/// missing.
+///
+/// # Arguments
+/// * `ctx` - Context containing `TopUpVaultRent` accounts
+pub fn process_top_up_vault_rent(ctx: Context<TopUpVaultRent>) -> Result<()> {
+    let vault_info = ctx.accounts.lottery_vault.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+    let shortfall = rent_exempt_minimum.saturating_sub(vault_info.lamports());
+    require!(shortfall > 0, ErrorCode::VaultRentExempt);
+
+    system_program::transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: vault_info,
+            },
+        ),
+        shortfall,
+    )?;
+    msg!("Topped up vault rent by {} lamports", shortfall);
+
+    Ok(())
+}
@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+
+/// Accounts required to propose a new lottery authority.
+///
+/// Ensures:
+/// 1. Only the current authority of the lottery can propose a new one.
+#[derive(Accounts)]
+pub struct TransferAuthority<'info> {
+    /// The current lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Accounts required to accept a proposed lottery authority.
+///
+/// Ensures:
+/// 1. Only the pending authority can accept the handoff.
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    /// The pending lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Proposes `new_authority` as the lottery authority.
+///
+/// The current authority stays in charge until `new_authority` signs
+/// `accept_authority`, so a mistyped address can't take over or lock out
+/// the lottery. Proposing again replaces the pending authority.
+///
+/// # Arguments
+/// * `ctx` - Context containing `TransferAuthority` accounts
+/// * `new_authority` - The key to hand the lottery over to
+pub fn process_transfer_authority(
+    ctx: Context<TransferAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(
+        new_authority != Pubkey::default() && new_authority != token_lottery.authority,
+        ErrorCode::InvalidNewAuthority
+    );
+
+    token_lottery.pending_authority = new_authority;
+    msg!("Pending authority: {}", new_authority);
+
+    Ok(())
+}
+
+/// Makes the pending authority the lottery authority.
+///
+/// # Arguments
+/// * `ctx` - Context containing `AcceptAuthority` accounts
+pub fn process_accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    require!(
+        token_lottery.pending_authority != Pubkey::default()
+            && ctx.accounts.payer.key() == token_lottery.pending_authority,
+        ErrorCode::NotPendingAuthority
+    );
+
+    token_lottery.authority = token_lottery.pending_authority;
+    token_lottery.pending_authority = Pubkey::default();
+    msg!("Authority transferred to {}", token_lottery.authority);
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{thaw_delegated_account, Metadata, ThawDelegatedAccount};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+use crate::time::schedule_time;
+
+/// Accounts required to unfreeze a ticket locked by `resale_lock`.
+///
+/// Anyone may unlock a ticket once its round has ended, so no authority
+/// check is made.
+#[derive(Accounts)]
+#[instruction(round: u64, ticket_index: u64)]
+pub struct UnlockTicket<'info> {
+    /// The account submitting the unlock.
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Mint of the locked ticket.
+    #[account(
+        seeds = [
+            token_lottery.lottery_id.to_le_bytes().as_ref(),
+            round.to_le_bytes().as_ref(),
+            ticket_index.to_le_bytes().as_ref(),
+        ],
+        bump,
+        mint::token_program = token_program,
+    )]
+    pub ticket_mint: InterfaceAccount<'info, Mint>,
+
+    /// The frozen token account holding the ticket.
+    #[account(
+        mut,
+        token::mint = ticket_mint,
+        token::token_program = token_program,
+    )]
+    pub ticket_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Metadata account of the ticket.
+    /// CHECK: Only passed through to the Metaplex program.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(), ticket_mint.key().as_ref()],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// Master edition of the ticket, holding its freeze authority.
+    /// CHECK: Validated by the Metaplex program during the thaw.
+    #[account(
+        seeds = [b"metadata", token_metadata_program.key().as_ref(),
+            ticket_mint.key().as_ref(), b"edition"],
+        bump,
+        seeds::program = token_metadata_program.key(),
+    )]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// Delegate that froze the ticket.
+    /// CHECK: Only used as a PDA signer, it holds no data.
+    #[account(
+        seeds = [b"collection_authority".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// Token program of the ticket.
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Metaplex token metadata program.
+    pub token_metadata_program: Program<'info, Metadata>,
+}
+
+/// Thaws a ticket frozen at purchase under `resale_lock`, so it can be
+/// transferred again.
+///
+/// Allowed once sales of the ticket's round have ended, or the round was
+/// cancelled so the ticket can be refunded. The lottery's delegate stays
+/// approved on the token account until the owner revokes it.
+///
+/// # Arguments
+/// * `ctx` - Context containing `UnlockTicket` accounts
+/// * `round` - The round the ticket was bought in
+/// * `ticket_index` - The ticket's index in that round
+pub fn process_unlock_ticket(
+    ctx: Context<UnlockTicket>,
+    round: u64,
+    _ticket_index: u64,
+) -> Result<()> {
+    let now = schedule_time(
+        ctx.accounts.token_lottery.timing_mode,
+        ctx.remaining_accounts,
+    )?;
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    require!(
+        round < token_lottery.round_number
+            || now > token_lottery.lottery_end
+            || token_lottery.cancelled,
+        ErrorCode::ResaleLocked
+    );
+
+    let lottery_id = token_lottery.lottery_id.to_le_bytes();
+    thaw_delegated_account(CpiContext::new_with_signer(
+        ctx.accounts.token_metadata_program.to_account_info(),
+        ThawDelegatedAccount {
+            metadata: ctx.accounts.metadata.to_account_info(),
+            delegate: ctx.accounts.collection_authority.to_account_info(),
+            token_account: ctx.accounts.ticket_account.to_account_info(),
+            edition: ctx.accounts.master_edition.to_account_info(),
+            mint: ctx.accounts.ticket_mint.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        },
+        &[&[
+            b"collection_authority".as_ref(),
+            lottery_id.as_ref(),
+            &[ctx.bumps.collection_authority],
+        ]],
+    ))?;
+    msg!(
+        "Unlocked ticket {} of round {}",
+        ctx.accounts.ticket_mint.key(),
+        round
+    );
+
+    Ok(())
+}
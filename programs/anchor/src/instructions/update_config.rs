@@ -0,0 +1,93 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::MAX_URI_LENGTH;
+use crate::error::ErrorCode;
+use crate::state::TokenLottery;
+use crate::time::schedule_time;
+
+/// Accounts required to change the config of a running lottery.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can change its config.
+/// 2. The winner of the current round has not been drawn yet.
+/// 3. The end and price only change before the round has sold a ticket.
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Settings changed by `update_config`. `None` leaves a setting unchanged.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+pub struct ConfigUpdate {
+    /// Metadata URI of tickets minted from now on, unless a `base_uri` is
+    /// set. Tickets already minted keep the URI they were minted with.
+    pub uri: Option<String>,
+
+    /// New end of the round, no earlier than the current time on the
+    /// lottery schedule.
+    pub lottery_end: Option<u64>,
+
+    /// New ticket price in lamports.
+    pub price: Option<u64>,
+}
+
+/// Applies the settings given in `update`.
+///
+/// `lottery_end` and `price` are the terms buyers paid under, so they can
+/// only change before the round starts or while no ticket has been sold;
+/// otherwise, or when the new end is already past, this fails with
+/// `InvalidConfigUpdate`.
+///
+/// # Arguments
+/// * `ctx` - Context containing `UpdateConfig` accounts
+/// * `update` - The settings to change
+pub fn process_update_config(ctx: Context<UpdateConfig>, update: ConfigUpdate) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(!token_lottery.winner_chosen, ErrorCode::WinnerChosen);
+
+    if let Some(uri) = update.uri {
+        require!(uri.len() <= MAX_URI_LENGTH, ErrorCode::UriTooLong);
+        msg!("Ticket URI updated to {}", uri);
+        token_lottery.uri = uri;
+    }
+
+    if update.lottery_end.is_some() || update.price.is_some() {
+        let now = schedule_time(token_lottery.timing_mode, ctx.remaining_accounts)?;
+        require!(
+            now < token_lottery.lottery_start || token_lottery.ticket_num == 0,
+            ErrorCode::InvalidConfigUpdate
+        );
+        if let Some(lottery_end) = update.lottery_end {
+            require!(
+                lottery_end >= now && lottery_end >= token_lottery.lottery_start,
+                ErrorCode::InvalidConfigUpdate
+            );
+            msg!("Lottery end moved to {}", lottery_end);
+            token_lottery.lottery_end = lottery_end;
+        }
+        if let Some(price) = update.price {
+            require!(
+                token_lottery.is_rounded_price(price),
+                ErrorCode::PriceNotRounded
+            );
+            msg!("Ticket price updated to {}", price);
+            token_lottery.price = price;
+        }
+    }
+
+    Ok(())
+}
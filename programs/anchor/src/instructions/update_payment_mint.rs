@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+use crate::constants::MAX_PAYMENT_TOKENS;
+use crate::error::ErrorCode;
+use crate::state::{LotteryVault, PaymentToken, TokenLottery};
+
+/// Accounts required to change the mint of an accepted payment token.
+///
+/// Ensures:
+/// 1. Only the authority of the lottery can change its payment tokens.
+/// 2. No ticket has been sold this round.
+/// 3. The vault holds a token account for the new mint.
+#[derive(Accounts)]
+pub struct UpdatePaymentMint<'info> {
+    /// The lottery authority, paying for the vault's token account.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow owning the token accounts the payments are collected in.
+    #[account(
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+
+    /// The new payment mint.
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    /// The vault's associated token account for the new mint, created if
+    /// missing.
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = lottery_vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_payment_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// Token program owning `payment_mint`.
+    pub token_program: Interface<'info, TokenInterface>,
+
+    /// Associated token program
+    pub associated_token_program: Program<'info, AssociatedToken>,
+
+    /// System program interface
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the mint and price of the payment token selected by
+/// `payment_index`, before the round has sold any ticket.
+///
+/// `payment_index` is the index `buy_ticket` takes: `i + 1` replaces the
+/// `i`th of `payment_tokens`, and one past the last adds a payment token, up
+/// to `MAX_PAYMENT_TOKENS`. A lottery configured for SOL only starts
+/// accepting the token this way. A replaced token must hold no pot or
+/// rollover, since those are in the old mint. Fails with
+/// `InvalidConfigUpdate` once a ticket has been sold.
+///
+/// # Arguments
+/// * `ctx` - Context containing `UpdatePaymentMint` accounts
+/// * `payment_index` - `i + 1` for the `i`th payment token
+/// * `price` - The ticket price, in base units of `payment_mint`
+pub fn process_update_payment_mint(
+    ctx: Context<UpdatePaymentMint>,
+    payment_index: u8,
+    price: u64,
+) -> Result<()> {
+    let token_lottery = &mut ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(
+        token_lottery.ticket_num == 0,
+        ErrorCode::InvalidConfigUpdate
+    );
+    require!(price > 0, ErrorCode::InvalidPaymentToken);
+
+    let token_index = (payment_index as usize)
+        .checked_sub(1)
+        .filter(|index| {
+            *index <= token_lottery.payment_tokens.len() && *index < MAX_PAYMENT_TOKENS as usize
+        })
+        .ok_or(ErrorCode::InvalidPaymentToken)?;
+    let payment_token = PaymentToken {
+        mint: ctx.accounts.payment_mint.key(),
+        price,
+    };
+    if token_index == token_lottery.payment_tokens.len() {
+        token_lottery.payment_tokens.push(payment_token);
+        token_lottery.payment_token_pots.push(0);
+        token_lottery.payment_token_rollovers.push(0);
+        token_lottery
+            .payment_token_decimals
+            .push(ctx.accounts.payment_mint.decimals);
+    } else {
+        require!(
+            token_lottery.payment_token_pots[token_index] == 0
+                && token_lottery.payment_token_rollovers[token_index] == 0,
+            ErrorCode::InvalidConfigUpdate
+        );
+        token_lottery.payment_tokens[token_index] = payment_token;
+        token_lottery.payment_token_decimals[token_index] = ctx.accounts.payment_mint.decimals;
+    }
+    msg!(
+        "Payment token {} set to {} at {}",
+        payment_index,
+        ctx.accounts.payment_mint.key(),
+        price
+    );
+
+    Ok(())
+}
@@ -0,0 +1,112 @@
+use anchor_lang::prelude::*;
+use anchor_spl::metadata::{mpl_token_metadata, MetadataAccount};
+
+use crate::events::{Anomaly, IntegrityReport};
+use crate::state::TokenLottery;
+
+/// Accounts checked by `validate_lottery`.
+///
+/// PDAs are deliberately left unconstrained so mismatches are reported
+/// instead of failing the instruction.
+#[derive(Accounts)]
+pub struct ValidateLottery<'info> {
+    /// The main lottery state account.
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// CHECK: Expected to be the collection mint PDA; validated within the handler.
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Expected to be the collection token account PDA; validated within the handler.
+    pub collection_token_account: UncheckedAccount<'info>,
+
+    /// CHECK: Expected to be the collection metadata PDA; validated within the handler.
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Expected to be the lottery vault PDA; validated within the handler.
+    pub lottery_vault: UncheckedAccount<'info>,
+}
+
+/// Recomputes every PDA of the lottery, checks the pot against the lamports
+/// actually held and checks the collection is initialized and verified.
+/// Emits an `IntegrityReport` listing any anomalies.
+///
+/// # Arguments
+/// * `ctx` - Context containing `ValidateLottery` accounts
+pub fn process_validate_lottery(ctx: Context<ValidateLottery>) -> Result<()> {
+    let program_id = ctx.program_id;
+    let token_lottery = &ctx.accounts.token_lottery;
+    let lottery_id = token_lottery.lottery_id.to_le_bytes();
+    let mut anomalies = Vec::new();
+
+    let (lottery_address, lottery_bump) = Pubkey::find_program_address(
+        &[b"token_lottery".as_ref(), lottery_id.as_ref()],
+        program_id,
+    );
+    if lottery_address != token_lottery.key() || lottery_bump != token_lottery.bump {
+        anomalies.push(Anomaly::LotteryBumpMismatch);
+    }
+
+    let (collection_mint, _) = Pubkey::find_program_address(
+        &[b"collection_mint".as_ref(), lottery_id.as_ref()],
+        program_id,
+    );
+    if collection_mint != ctx.accounts.collection_mint.key() {
+        anomalies.push(Anomaly::CollectionMintAddressMismatch);
+    }
+    if ctx.accounts.collection_mint.data_is_empty() {
+        anomalies.push(Anomaly::CollectionMintNotInitialized);
+    }
+
+    let (collection_token_account, _) = Pubkey::find_program_address(
+        &[b"collection_token_account".as_ref(), lottery_id.as_ref()],
+        program_id,
+    );
+    if collection_token_account != ctx.accounts.collection_token_account.key() {
+        anomalies.push(Anomaly::CollectionTokenAccountMismatch);
+    }
+
+    let (collection_metadata, _) =
+        mpl_token_metadata::accounts::Metadata::find_pda(&collection_mint);
+    let metadata = if collection_metadata == ctx.accounts.collection_metadata.key() {
+        MetadataAccount::try_deserialize(&mut &ctx.accounts.collection_metadata.data.borrow()[..])
+            .ok()
+    } else {
+        None
+    };
+    match metadata {
+        Some(metadata) => {
+            let verified = metadata.creators.as_ref().map_or(false, |creators| {
+                creators
+                    .iter()
+                    .any(|creator| creator.address == collection_mint && creator.verified)
+            });
+            if !verified {
+                anomalies.push(Anomaly::CollectionNotVerified);
+            }
+        }
+        None => anomalies.push(Anomaly::CollectionMetadataMissing),
+    }
+
+    let (lottery_vault, _) = Pubkey::find_program_address(
+        &[b"vault".as_ref(), token_lottery.key().as_ref()],
+        program_id,
+    );
+    if lottery_vault != ctx.accounts.lottery_vault.key() {
+        anomalies.push(Anomaly::VaultAddressMismatch);
+    }
+
+    let vault_info = ctx.accounts.lottery_vault.to_account_info();
+    let rent_exempt_minimum = Rent::get()?.minimum_balance(vault_info.data_len());
+    let pot_balance = vault_info.lamports().saturating_sub(rent_exempt_minimum);
+    if pot_balance < token_lottery.lottery_pot_amount {
+        anomalies.push(Anomaly::PotExceedsBalance);
+    }
+
+    msg!("Anomalies found: {}", anomalies.len());
+    emit!(IntegrityReport {
+        token_lottery: token_lottery.key(),
+        anomalies,
+    });
+
+    Ok(())
+}
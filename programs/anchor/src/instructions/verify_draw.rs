@@ -0,0 +1,55 @@
+use anchor_lang::prelude::*;
+
+use crate::error::ErrorCode;
+use crate::events::DrawVerified;
+use crate::randomness::derive_winner;
+use crate::state::TokenLottery;
+
+/// Accounts read by `verify_draw`. Anyone may call it.
+#[derive(Accounts)]
+pub struct VerifyDraw<'info> {
+    /// The lottery whose draw is verified.
+    #[account(
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+}
+
+/// Recomputes the winner from `revealed_randomness` and `ticket_num` with
+/// `derive_winner` and emits a `DrawVerified` event saying whether it
+/// matches the stored `winner`.
+///
+/// Passing the stored `revealed_randomness` and `ticket_num` proves the draw
+/// followed from the revealed randomness. Changes nothing on-chain.
+///
+/// # Arguments
+/// * `ctx` - Context containing `VerifyDraw` accounts
+/// * `revealed_randomness` - The random value to derive the winner from
+/// * `ticket_num` - The number of tickets in the draw
+pub fn process_verify_draw(
+    ctx: Context<VerifyDraw>,
+    revealed_randomness: [u8; 32],
+    ticket_num: u64,
+) -> Result<()> {
+    let token_lottery = &ctx.accounts.token_lottery;
+    require!(token_lottery.winner_chosen, ErrorCode::WinnerNotChosen);
+    require!(ticket_num > 0, ErrorCode::TicketNumOutOfBounds);
+
+    let derived_winner = derive_winner(&revealed_randomness, ticket_num);
+    let matches = derived_winner == token_lottery.winner;
+    msg!(
+        "Derived winner {} against stored winner {}",
+        derived_winner,
+        token_lottery.winner
+    );
+
+    emit!(DrawVerified {
+        token_lottery: token_lottery.key(),
+        winner: token_lottery.winner,
+        derived_winner,
+        matches,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+
+use crate::constants::FEE_MODE_DEFERRED;
+use crate::error::ErrorCode;
+use crate::events::FeesWithdrawn;
+use crate::state::{LotteryVault, TokenLottery};
+
+/// Accounts required to pay deferred fees out to the treasuries.
+///
+/// The treasury accounts must be passed as remaining accounts, in the order
+/// they were configured.
+#[derive(Accounts)]
+pub struct WithdrawFees<'info> {
+    /// The lottery authority.
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The main lottery state account.
+    #[account(
+        mut,
+        seeds = [b"token_lottery".as_ref(), token_lottery.lottery_id.to_le_bytes().as_ref()],
+        bump = token_lottery.bump,
+    )]
+    pub token_lottery: Account<'info, TokenLottery>,
+
+    /// Escrow holding the accrued fees.
+    #[account(
+        mut,
+        seeds = [b"vault".as_ref(), token_lottery.key().as_ref()],
+        bump = lottery_vault.bump,
+    )]
+    pub lottery_vault: Account<'info, LotteryVault>,
+}
+
+/// Splits the fees accrued under `FEE_MODE_DEFERRED` between the treasuries
+/// and resets `accrued_fees`. Rejected with `FeesNotDeferred` when fees are
+/// paid on purchase, since nothing is held back then.
+///
+/// # Arguments
+/// * `ctx` - Context containing `WithdrawFees` accounts
+pub fn process_withdraw_fees<'info>(
+    ctx: Context<'_, '_, 'info, 'info, WithdrawFees<'info>>,
+) -> Result<()> {
+    let token_lottery = &ctx.accounts.token_lottery;
+
+    if ctx.accounts.payer.key() != token_lottery.authority {
+        return Err(ErrorCode::NotAuthorized.into());
+    }
+    require!(
+        token_lottery.fee_mode == FEE_MODE_DEFERRED,
+        ErrorCode::FeesNotDeferred
+    );
+
+    let amount = token_lottery.accrued_fees;
+    let treasury_amounts = token_lottery.treasury_amounts(amount);
+    require!(
+        ctx.remaining_accounts.len() >= treasury_amounts.len(),
+        ErrorCode::IncorrectTreasury
+    );
+    for ((treasury, treasury_account), treasury_amount) in token_lottery
+        .treasuries
+        .iter()
+        .zip(ctx.remaining_accounts.iter())
+        .zip(treasury_amounts)
+    {
+        require_keys_eq!(
+            treasury_account.key(),
+            treasury.recipient,
+            ErrorCode::IncorrectTreasury
+        );
+        **ctx
+            .accounts
+            .lottery_vault
+            .to_account_info()
+            .try_borrow_mut_lamports()? -= treasury_amount;
+        **treasury_account.try_borrow_mut_lamports()? += treasury_amount;
+    }
+
+    let token_lottery = &mut ctx.accounts.token_lottery;
+    token_lottery.accrued_fees = 0;
+
+    msg!("Withdrew {} lamports of fees", amount);
+    if token_lottery.emit_events {
+        emit!(FeesWithdrawn {
+            token_lottery: token_lottery.key(),
+            fee_mode: token_lottery.fee_mode,
+            amount,
+        });
+    }
+
+    Ok(())
+}
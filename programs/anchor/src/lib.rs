@@ -1,10 +1,13 @@
 use anchor_lang::prelude::*;
 use instructions::*;
 
-mod constants;
+pub mod constants;
 mod error;
+mod events;
 mod instructions;
+mod randomness;
 mod state;
+mod time;
 
 declare_id!("2RTh2Y4e2N421EbSnUYTKdGqDHJH7etxZb3VrWDMpNMY");
 
@@ -14,30 +17,193 @@ pub mod token_lottery {
 
     pub fn initialize_config(
         ctx: Context<InitializeConifg>,
+        lottery_id: u64,
         start: u64,
         end: u64,
         price: u64,
+        params: ConfigParams,
     ) -> Result<()> {
-        process_initialize_config(ctx, start, end, price)
+        process_initialize_config(ctx, lottery_id, start, end, price, params)
+    }
+
+    pub fn estimate_initialization_rent(ctx: Context<EstimateInitializationRent>) -> Result<u64> {
+        process_estimate_initialization_rent(ctx)
     }
 
     pub fn initialize_lottery(ctx: Context<InitializeLottery>) -> Result<()> {
         process_initialize_lottery(ctx)
     }
 
-    pub fn buy_ticket(ctx: Context<BuyTicket>) -> Result<()> {
-        process_buy_ticket(ctx)
+    pub fn burn_pot(ctx: Context<BurnPot>) -> Result<()> {
+        process_burn_pot(ctx)
+    }
+
+    pub fn buy_ticket<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyTicket<'info>>,
+        amount: u64,
+        payment_index: u8,
+    ) -> Result<()> {
+        process_buy_ticket(ctx, amount, payment_index)
+    }
+
+    pub fn buy_multiple_tickets<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyMultipleTickets<'info>>,
+        amount: u64,
+        count: u8,
+    ) -> Result<()> {
+        process_buy_multiple_tickets(ctx, amount, count)
+    }
+
+    pub fn cancel_lottery(ctx: Context<CancelLottery>) -> Result<()> {
+        process_cancel_lottery(ctx)
+    }
+
+    pub fn refund_ticket(ctx: Context<RefundTicket>, ticket_index: u64) -> Result<()> {
+        process_refund_ticket(ctx, ticket_index)
+    }
+
+    pub fn refund_sponsorship(ctx: Context<RefundSponsorship>) -> Result<()> {
+        process_refund_sponsorship(ctx)
+    }
+
+    pub fn sweep_unclaimed_refunds(
+        ctx: Context<SweepUnclaimedRefunds>,
+        payment_index: u8,
+    ) -> Result<()> {
+        process_sweep_unclaimed_refunds(ctx, payment_index)
+    }
+
+    pub fn merge_pots<'info>(ctx: Context<'_, '_, 'info, 'info, MergePots<'info>>) -> Result<()> {
+        process_merge_pots(ctx)
+    }
+
+    pub fn pause_lottery(ctx: Context<PauseLottery>) -> Result<()> {
+        process_pause_lottery(ctx)
+    }
+
+    pub fn resume_lottery(ctx: Context<PauseLottery>) -> Result<()> {
+        process_resume_lottery(ctx)
+    }
+
+    pub fn close_lottery(ctx: Context<CloseLottery>) -> Result<()> {
+        process_close_lottery(ctx)
+    }
+
+    pub fn preview_close(ctx: Context<PreviewClose>) -> Result<u64> {
+        process_preview_close(ctx)
     }
 
     pub fn commit_a_winner(ctx: Context<CommitWinner>) -> Result<()> {
         process_commit_a_winner(ctx)
     }
 
+    pub fn rerequest_randomness(ctx: Context<RerequestRandomness>) -> Result<()> {
+        process_rerequest_randomness(ctx)
+    }
+
     pub fn choose_a_winner(ctx: Context<ChooseWinner>) -> Result<()> {
         process_choose_a_winner(ctx)
     }
 
-    pub fn claim_prize(ctx: Context<ClaimPrize>) -> Result<()> {
-        process_claim_prize(ctx)
+    pub fn claim_prize(ctx: Context<ClaimPrize>, winner_index: u8) -> Result<()> {
+        process_claim_prize(ctx, winner_index)
+    }
+
+    pub fn claim_token_prize(ctx: Context<ClaimTokenPrize>, payment_index: u8) -> Result<()> {
+        process_claim_token_prize(ctx, payment_index)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        process_claim_vested(ctx)
+    }
+
+    pub fn claim_consolation(ctx: Context<ClaimConsolation>, ticket_index: u64) -> Result<()> {
+        process_claim_consolation(ctx, ticket_index)
+    }
+
+    pub fn sponsor_pot(ctx: Context<SponsorPot>, amount: u64) -> Result<()> {
+        process_sponsor_pot(ctx, amount)
+    }
+
+    pub fn end_sales_early(ctx: Context<EndSalesEarly>) -> Result<()> {
+        process_end_sales_early(ctx)
+    }
+
+    pub fn reset_lottery(ctx: Context<ResetLottery>, start: u64, end: u64) -> Result<()> {
+        process_reset_lottery(ctx, start, end)
+    }
+
+    pub fn rollover_unclaimed(ctx: Context<RolloverUnclaimed>) -> Result<()> {
+        process_rollover_unclaimed(ctx)
+    }
+
+    pub fn unlock_ticket(ctx: Context<UnlockTicket>, round: u64, ticket_index: u64) -> Result<()> {
+        process_unlock_ticket(ctx, round, ticket_index)
+    }
+
+    pub fn update_config(ctx: Context<UpdateConfig>, update: ConfigUpdate) -> Result<()> {
+        process_update_config(ctx, update)
+    }
+
+    pub fn update_payment_mint(
+        ctx: Context<UpdatePaymentMint>,
+        payment_index: u8,
+        price: u64,
+    ) -> Result<()> {
+        process_update_payment_mint(ctx, payment_index, price)
+    }
+
+    pub fn migrate_to_vault(ctx: Context<MigrateToVault>) -> Result<()> {
+        process_migrate_to_vault(ctx)
+    }
+
+    pub fn next_ticket_num(ctx: Context<NextTicket>) -> Result<u64> {
+        process_next_ticket_num(ctx)
+    }
+
+    pub fn transfer_authority(
+        ctx: Context<TransferAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        process_transfer_authority(ctx, new_authority)
+    }
+
+    pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+        process_accept_authority(ctx)
+    }
+
+    pub fn slash_bond(ctx: Context<SlashBond>) -> Result<()> {
+        process_slash_bond(ctx)
+    }
+
+    pub fn withdraw_bond(ctx: Context<WithdrawBond>) -> Result<()> {
+        process_withdraw_bond(ctx)
+    }
+
+    pub fn top_up_vault_rent(ctx: Context<TopUpVaultRent>) -> Result<()> {
+        process_top_up_vault_rent(ctx)
+    }
+
+    pub fn validate_lottery(ctx: Context<ValidateLottery>) -> Result<()> {
+        process_validate_lottery(ctx)
+    }
+
+    pub fn verify_draw(
+        ctx: Context<VerifyDraw>,
+        revealed_randomness: [u8; 32],
+        ticket_num: u64,
+    ) -> Result<()> {
+        process_verify_draw(ctx, revealed_randomness, ticket_num)
+    }
+
+    pub fn withdraw_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFees<'info>>,
+    ) -> Result<()> {
+        process_withdraw_fees(ctx)
+    }
+
+    #[cfg(feature = "mock-clock")]
+    pub fn set_mock_clock(ctx: Context<SetMockClock>, slot: u64) -> Result<()> {
+        process_set_mock_clock(ctx, slot)
     }
 }
@@ -6,10 +6,21 @@ pub mod constants;
 /// Errors are returned via the Anchor framework when instructions fail.
 pub mod error;
 
+/// Module defining the Anchor events emitted by the program for off-chain indexers.
+pub mod events;
+
 /// Module containing all instruction handlers for the program,
 /// such as initializing a lottery, buying tickets, and choosing a winner.
 pub mod instructions;
 
+/// Module abstracting the randomness providers used for the draw,
+/// each with its own freshness rules.
+pub mod randomness;
+
 /// Module defining the on-chain state structures for the program,
 /// including the `TokenLottery` account and any related PDAs.s
 pub mod state;
+
+/// Module resolving the current slot, which the `mock-clock` feature can
+/// override for deterministic tests.
+pub mod time;
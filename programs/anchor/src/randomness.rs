@@ -0,0 +1,413 @@
+use std::cell::Ref;
+use std::cmp::Ordering;
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::{hash, hashv};
+use anchor_lang::solana_program::sysvar::slot_hashes;
+use switchboard_on_demand::accounts::RandomnessAccountData;
+
+use crate::constants::RANDOMNESS_VALIDITY_SLOTS;
+use crate::error::ErrorCode;
+use crate::state::RandomnessSource;
+
+/// A source of verifiable randomness for the draw.
+///
+/// Each provider owns its own freshness rules, since providers differ in how
+/// their seed slot relates to the slot the randomness is committed in.
+pub trait RandomnessProvider {
+    /// Returns the slot the randomness was seeded from.
+    fn seed_slot(&self) -> u64;
+
+    /// Checks the randomness is fresh enough to be committed to the lottery,
+    /// i.e. its value could not be known yet.
+    fn check_commit_freshness(&self, clock: &Clock) -> Result<()>;
+
+    /// Returns `true` if committed randomness can no longer be revealed.
+    fn has_expired(&self, clock: &Clock) -> bool;
+
+    /// Returns the revealed random value.
+    fn reveal(&self, clock: &Clock) -> Result<[u8; 32]>;
+}
+
+/// Randomness from a Switchboard on-demand randomness account.
+pub struct SwitchboardRandomness<'a> {
+    data: Ref<'a, RandomnessAccountData>,
+}
+
+impl<'a> SwitchboardRandomness<'a> {
+    /// Parses a Switchboard randomness account, failing with
+    /// `InvalidRandomnessAccount` if it isn't one.
+    pub fn load(account: &'a AccountInfo) -> Result<Self> {
+        let data = RandomnessAccountData::parse(account.data.borrow())
+            .map_err(|_| error!(ErrorCode::InvalidRandomnessAccount))?;
+        Ok(Self { data })
+    }
+}
+
+impl RandomnessProvider for SwitchboardRandomness<'_> {
+    fn seed_slot(&self) -> u64 {
+        self.data.seed_slot
+    }
+
+    /// Switchboard randomness must be committed in the slot right after its
+    /// seed slot, before the oracle can reveal it.
+    fn check_commit_freshness(&self, clock: &Clock) -> Result<()> {
+        if self.data.seed_slot != clock.slot - 1 {
+            return Err(ErrorCode::RandomnessAlreadyRevealed.into());
+        }
+        Ok(())
+    }
+
+    /// Switchboard reveals against the seed slot's hash, which drops out of
+    /// the `SlotHashes` sysvar after `RANDOMNESS_VALIDITY_SLOTS`.
+    fn has_expired(&self, clock: &Clock) -> bool {
+        self.data.reveal_slot == 0 && clock.slot > self.data.seed_slot + RANDOMNESS_VALIDITY_SLOTS
+    }
+
+    fn reveal(&self, clock: &Clock) -> Result<[u8; 32]> {
+        let value = self
+            .data
+            .get_value(clock)
+            .map_err(|_| error!(ErrorCode::RandomnessNotResolved))?;
+        randomness_value(value.as_ref())
+    }
+}
+
+/// Randomness from the hash of the slot it was committed in, read from the
+/// `SlotHashes` sysvar once that slot has completed.
+///
+/// Needs no oracle, but is much weaker than Switchboard: the leader of the
+/// seed slot produces its hash and sees the commit transaction, so it can
+/// bias the draw by reordering or dropping transactions, or by skipping the
+/// slot altogether. Only use it where no slot leader stands to gain more from
+/// the draw than the cost of manipulating it.
+///
+/// The hash is public from the next slot on, so the authority knows the
+/// winner before drawing. `rerequest_randomness` refuses to discard such a
+/// commit; a draw that never happens can only end in `cancel_lottery`.
+pub struct SlotHashRandomness {
+    seed_slot: u64,
+    value: Option<[u8; 32]>,
+}
+
+impl SlotHashRandomness {
+    /// Reads the hash of `seed_slot` from the `SlotHashes` sysvar, failing
+    /// with `InvalidRandomnessAccount` if `account` isn't the sysvar.
+    pub fn load(account: &AccountInfo, seed_slot: u64) -> Result<Self> {
+        require_keys_eq!(
+            account.key(),
+            slot_hashes::ID,
+            ErrorCode::InvalidRandomnessAccount
+        );
+        let data = account.try_borrow_data()?;
+        Ok(Self {
+            seed_slot,
+            value: find_slot_hash(&data, seed_slot),
+        })
+    }
+}
+
+impl RandomnessProvider for SlotHashRandomness {
+    fn seed_slot(&self) -> u64 {
+        self.seed_slot
+    }
+
+    /// The seed slot is the commit slot itself, whose hash isn't known until
+    /// the slot completes.
+    fn check_commit_freshness(&self, clock: &Clock) -> Result<()> {
+        if self.seed_slot != clock.slot {
+            return Err(ErrorCode::RandomnessAlreadyRevealed.into());
+        }
+        Ok(())
+    }
+
+    fn has_expired(&self, clock: &Clock) -> bool {
+        clock.slot > self.seed_slot + RANDOMNESS_VALIDITY_SLOTS
+    }
+
+    fn reveal(&self, _clock: &Clock) -> Result<[u8; 32]> {
+        self.value.ok_or(ErrorCode::RandomnessNotResolved.into())
+    }
+}
+
+/// Finds the hash of `slot` in raw `SlotHashes` sysvar data, a `u64` count
+/// followed by `(slot, hash)` entries, newest first. Searching the raw bytes
+/// avoids deserializing all of the sysvar's entries.
+fn find_slot_hash(data: &[u8], slot: u64) -> Option<[u8; 32]> {
+    const ENTRY_LEN: usize = 8 + 32;
+    let count = u64::from_le_bytes(data.get(..8)?.try_into().ok()?) as usize;
+    let entries = data.get(8..count.checked_mul(ENTRY_LEN)?.checked_add(8)?)?;
+
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let middle = (low + high) / 2;
+        let entry = &entries[middle * ENTRY_LEN..(middle + 1) * ENTRY_LEN];
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        match entry_slot.cmp(&slot) {
+            Ordering::Equal => return entry[8..].try_into().ok(),
+            // Older slots come later
+            Ordering::Greater => low = middle + 1,
+            Ordering::Less => high = middle,
+        }
+    }
+    None
+}
+
+/// Loads the randomness committed in `account` for the lottery's `source`.
+/// `seed_slot` is only read for `RandomnessSource::SlotHash`, whose seed is
+/// the commit slot rather than stored in the account.
+pub fn load_randomness<'a>(
+    source: RandomnessSource,
+    account: &'a AccountInfo,
+    seed_slot: u64,
+) -> Result<Box<dyn RandomnessProvider + 'a>> {
+    Ok(match source {
+        RandomnessSource::Switchboard => Box::new(SwitchboardRandomness::load(account)?),
+        RandomnessSource::SlotHash => Box::new(SlotHashRandomness::load(account, seed_slot)?),
+    })
+}
+
+/// Checks a revealed value is a full 32 bytes before it is indexed into,
+/// so a short buffer from the oracle fails cleanly instead of panicking.
+pub fn randomness_value(value: &[u8]) -> Result<[u8; 32]> {
+    value
+        .try_into()
+        .map_err(|_| error!(ErrorCode::InvalidRandomnessValue))
+}
+
+/// Maps a revealed random value to the winning ticket index out of
+/// `ticket_num` tickets. Public so anyone can recompute a draw with
+/// `verify_draw` or off-chain.
+///
+/// Reads the value as little-endian `u64`s and takes the first one below
+/// the largest multiple of `ticket_num`, so every ticket is equally likely.
+/// If all four are rejected, which is vanishingly rare, the value is hashed
+/// and sampled again, keeping the draw a pure function of the randomness.
+pub fn derive_winner(revealed_randomness: &[u8; 32], ticket_num: u64) -> u64 {
+    let zone = u64::MAX - u64::MAX % ticket_num;
+    let mut buffer = *revealed_randomness;
+    loop {
+        for chunk in buffer.chunks_exact(8) {
+            let value = u64::from_le_bytes(chunk.try_into().unwrap());
+            if value < zone {
+                return value % ticket_num;
+            }
+        }
+        buffer = hash(&buffer).to_bytes();
+    }
+}
+
+/// Draws `num_winners` distinct winning ticket indices out of `ticket_num`
+/// tickets, which must be at least `num_winners`.
+///
+/// The first winner is `derive_winner` of the value itself, so single-winner
+/// draws are unchanged. The `i`th winner is derived from the hash of the value
+/// and `i`, out of the tickets that have not won yet.
+pub fn derive_winners(
+    revealed_randomness: &[u8; 32],
+    ticket_num: u64,
+    num_winners: usize,
+) -> Vec<u64> {
+    let mut winners: Vec<u64> = Vec::with_capacity(num_winners);
+    for index in 0..num_winners {
+        let value = if index == 0 {
+            *revealed_randomness
+        } else {
+            hashv(&[
+                revealed_randomness.as_ref(),
+                (index as u64).to_le_bytes().as_ref(),
+            ])
+            .to_bytes()
+        };
+        let mut winner = derive_winner(&value, ticket_num - index as u64);
+
+        // Skip past the tickets that already won
+        let mut drawn = winners.clone();
+        drawn.sort_unstable();
+        for taken in drawn {
+            if taken <= winner {
+                winner += 1;
+            }
+        }
+        winners.push(winner);
+    }
+    winners
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A provider whose randomness may be committed up to a few slots after
+    /// its seed slot, unlike Switchboard's strict next-slot rule.
+    struct MockRandomness {
+        seed_slot: u64,
+        value: Option<[u8; 32]>,
+    }
+
+    impl RandomnessProvider for MockRandomness {
+        fn seed_slot(&self) -> u64 {
+            self.seed_slot
+        }
+
+        fn check_commit_freshness(&self, clock: &Clock) -> Result<()> {
+            if clock.slot < self.seed_slot || clock.slot - self.seed_slot > 3 {
+                return Err(ErrorCode::RandomnessAlreadyRevealed.into());
+            }
+            Ok(())
+        }
+
+        fn has_expired(&self, clock: &Clock) -> bool {
+            self.value.is_none() && clock.slot > self.seed_slot + 10
+        }
+
+        fn reveal(&self, _clock: &Clock) -> Result<[u8; 32]> {
+            self.value.ok_or(ErrorCode::RandomnessNotResolved.into())
+        }
+    }
+
+    fn clock_at(slot: u64) -> Clock {
+        Clock {
+            slot,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn freshness_rules_are_provider_specific() {
+        let provider = MockRandomness {
+            seed_slot: 100,
+            value: None,
+        };
+
+        assert!(provider.check_commit_freshness(&clock_at(101)).is_ok());
+        assert!(provider.check_commit_freshness(&clock_at(103)).is_ok());
+        assert!(provider.check_commit_freshness(&clock_at(104)).is_err());
+        assert!(provider.check_commit_freshness(&clock_at(99)).is_err());
+
+        assert!(!provider.has_expired(&clock_at(110)));
+        assert!(provider.has_expired(&clock_at(111)));
+        assert!(provider.reveal(&clock_at(111)).is_err());
+    }
+
+    #[test]
+    fn revealed_mock_value_is_returned() {
+        let provider = MockRandomness {
+            seed_slot: 100,
+            value: Some([7; 32]),
+        };
+
+        assert!(!provider.has_expired(&clock_at(1_000)));
+        assert_eq!(provider.reveal(&clock_at(1_000)).unwrap(), [7; 32]);
+    }
+
+    fn slot_hashes_data(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        let mut data = (entries.len() as u64).to_le_bytes().to_vec();
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+
+    #[test]
+    fn slot_hash_is_found_among_skipped_slots() {
+        let data = slot_hashes_data(&[
+            (110, [4; 32]),
+            (107, [3; 32]),
+            (106, [2; 32]),
+            (100, [1; 32]),
+        ]);
+
+        assert_eq!(find_slot_hash(&data, 110), Some([4; 32]));
+        assert_eq!(find_slot_hash(&data, 106), Some([2; 32]));
+        assert_eq!(find_slot_hash(&data, 100), Some([1; 32]));
+        // Skipped, not completed yet, and dropped out of the sysvar
+        assert_eq!(find_slot_hash(&data, 108), None);
+        assert_eq!(find_slot_hash(&data, 111), None);
+        assert_eq!(find_slot_hash(&data, 99), None);
+        // Truncated data
+        assert_eq!(find_slot_hash(&data[..50], 100), None);
+        assert_eq!(find_slot_hash(&[], 100), None);
+    }
+
+    #[test]
+    fn slot_hash_randomness_is_seeded_by_the_commit_slot() {
+        let pending = SlotHashRandomness {
+            seed_slot: 100,
+            value: None,
+        };
+        assert!(pending.check_commit_freshness(&clock_at(100)).is_ok());
+        assert!(pending.check_commit_freshness(&clock_at(101)).is_err());
+        assert!(pending.reveal(&clock_at(100)).is_err());
+        assert!(!pending.has_expired(&clock_at(100 + RANDOMNESS_VALIDITY_SLOTS)));
+        assert!(pending.has_expired(&clock_at(101 + RANDOMNESS_VALIDITY_SLOTS)));
+
+        let revealed = SlotHashRandomness {
+            seed_slot: 100,
+            value: Some([9; 32]),
+        };
+        assert_eq!(revealed.reveal(&clock_at(101)).unwrap(), [9; 32]);
+    }
+
+    #[test]
+    fn short_randomness_value_is_rejected() {
+        assert_eq!(randomness_value(&[7; 32]).unwrap(), [7; 32]);
+        assert!(randomness_value(&[7; 31]).is_err());
+        assert!(randomness_value(&[]).is_err());
+    }
+
+    #[test]
+    fn derive_winner_is_deterministic() {
+        let mut randomness = [0; 32];
+        randomness[0] = 13;
+
+        assert_eq!(derive_winner(&randomness, 5), 3);
+        assert_eq!(derive_winner(&randomness, 5), derive_winner(&randomness, 5));
+        assert_eq!(derive_winner(&randomness, 1), 0);
+    }
+
+    #[test]
+    fn derive_winner_covers_large_ticket_counts_uniformly() {
+        const TICKETS: u64 = 1_000;
+        const DRAWS: u64 = 200_000;
+        let mut counts = vec![0u64; TICKETS as usize];
+        for draw in 0..DRAWS {
+            let randomness = hash(&draw.to_le_bytes()).to_bytes();
+            let winner = derive_winner(&randomness, TICKETS);
+            assert!(winner < TICKETS);
+            counts[winner as usize] += 1;
+        }
+
+        // Indices past 255 are reachable, unlike with a single byte
+        assert!(counts.iter().all(|count| *count > 0));
+        let expected = DRAWS / TICKETS;
+        assert!(counts
+            .iter()
+            .all(|count| *count > expected / 2 && *count < expected * 2));
+    }
+
+    #[test]
+    fn derive_winners_draws_distinct_tickets() {
+        for draw in 0..1_000u64 {
+            let randomness = hash(&draw.to_le_bytes()).to_bytes();
+            let winners = derive_winners(&randomness, 4, 4);
+            assert_eq!(winners[0], derive_winner(&randomness, 4));
+
+            let mut sorted = winners.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, vec![0, 1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn derive_winner_resamples_values_in_the_biased_zone() {
+        // Every u64 of this value is u64::MAX, which is rejected for 3 tickets
+        let randomness = [0xff; 32];
+        let expected = {
+            let rehashed = hash(&randomness).to_bytes();
+            u64::from_le_bytes(rehashed[..8].try_into().unwrap()) % 3
+        };
+        assert_eq!(derive_winner(&randomness, 3), expected);
+    }
+}
@@ -1,28 +1,87 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::constants::{
+    BADGE_NAME, BASIS_POINTS, MAX_COLLECTION_SIZE, MAX_NAME_LENGTH, MAX_PAYMENT_TOKENS,
+    MAX_ROUND_NUMBER, MAX_SYMBOL_LENGTH, MAX_TICKET_NUM, MAX_TREASURIES, MAX_URI_LENGTH,
+    MAX_WINNERS, PAYMENT_INDEX_SOL, SOL_DECIMALS,
+};
+
+/// A recipient of part of the ticket fee.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct TreasuryShare {
+    /// The account receiving this share of the fee.
+    pub recipient: Pubkey,
+
+    /// The share of the fee, in basis points of the fee.
+    pub share_bps: u16,
+}
+
+/// An SPL token accepted for tickets alongside SOL.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Default, InitSpace)]
+pub struct PaymentToken {
+    /// The accepted mint.
+    pub mint: Pubkey,
+
+    /// The ticket price, in base units of `mint`.
+    pub price: u64,
+}
+
+/// The clock the lottery schedule is measured on.
+///
+/// Under `UnixTimestamp`, `lottery_start` and `lottery_end` are UNIX
+/// timestamps, and the durations measured from them, `anti_snipe_slots`,
+/// `max_anti_snipe_extension`, `public_draw_timeout_slots` and
+/// `draw_deadline_slots`, are in seconds. Durations measured from the draw,
+/// like `claim_delay_slots`, are always in slots.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum TimingMode {
+    /// Slots of the `Clock` sysvar, the default.
+    #[default]
+    Slot,
+    /// `Clock::unix_timestamp`, in seconds.
+    UnixTimestamp,
+}
+
+/// Where the draw's randomness comes from. See `crate::randomness`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default, PartialEq, Eq, InitSpace)]
+pub enum RandomnessSource {
+    /// A Switchboard on-demand randomness account, the default.
+    #[default]
+    Switchboard,
+    /// The hash of the commit slot, read from the `SlotHashes` sysvar. Weaker
+    /// than Switchboard, see `SlotHashRandomness`.
+    SlotHash,
+}
 
 #[account]
-#[derive(InitSpace)]
+#[derive(InitSpace, Default)]
 pub struct TokenLottery {
     /// The bump seed used for deriving the PDA address of this account.
     pub bump: u8,
 
+    /// Identifies this lottery among the lotteries of the deployment.
+    /// Part of the seeds of the lottery and every account derived from it.
+    pub lottery_id: u64,
+
     /// The index or identifier of the winning ticket.
-    /// Defaults to `0` until a winner is selected.
+    /// Defaults to `0` until a winner is selected. With several winners this
+    /// is the first, `winners[0]`.
     pub winner: u64,
 
     /// A flag indicating whether the winner has been chosen.
     /// `true` once the random draw has been completed.
     pub winner_chosen: bool,
 
-    /// The UNIX timestamp marking when the lottery started.
+    /// When ticket sales open, on the clock chosen by `timing_mode`.
     pub lottery_start: u64,
 
-    /// The UNIX timestamp marking when the lottery ends.
+    /// When ticket sales close, on the clock chosen by `timing_mode`.
     pub lottery_end: u64,
 
     /// The total amount of SOL (in lamports) accumulated in the lottery pot.
-    /// This field stores metadata only — the actual SOL should be held
-    /// in a separate escrow or vault account for safety.
+    /// This field stores metadata only — the actual SOL is held in the
+    /// `LotteryVault` escrow.
     pub lottery_pot_amount: u64,
 
     /// The total number of tickets issued for this lottery.
@@ -37,7 +96,960 @@ pub struct TokenLottery {
 
     /// The authority or admin responsible for managing this lottery.
     pub authority: Pubkey,
+
+    /// The slot at which the winner was drawn.
+    pub winner_chosen_slot: u64,
+
+    /// Slots after `winner_chosen_slot` during which the prize cannot be claimed.
+    pub claim_delay_slots: u64,
+
+    /// The fee taken from every ticket price, in basis points.
+    pub fee_basis_points: u16,
+
+    /// The treasuries the fee is split between. Shares sum to `BASIS_POINTS`.
+    #[max_len(MAX_TREASURIES)]
+    pub treasuries: Vec<TreasuryShare>,
+
+    /// Lamports paid from the pot to a loser who burns their ticket.
+    pub consolation_amount: u64,
+
+    /// A keeper trusted to draw the winner alongside the authority, without
+    /// waiting for `min_confirmations`. `Pubkey::default()` means no keeper
+    /// is configured.
+    pub trusted_keeper: Pubkey,
+
+    /// The current round, incremented by `reset_lottery`.
+    /// Part of the ticket mint seeds so rounds never share a ticket mint.
+    pub round_number: u64,
+
+    /// Lamports a buyer must hold on top of the ticket price to buy a ticket.
+    pub min_buyer_balance: u64,
+
+    /// Whether the authority is barred from buying tickets in its own lottery.
+    pub authority_cannot_buy: bool,
+
+    /// Chance, in basis points, that a ticket wins an instant prize on purchase.
+    pub instant_win_bps: u16,
+
+    /// Lamports paid from the pot for an instant win.
+    pub instant_win_prize: u64,
+
+    /// Ticket prices must be a multiple of this many lamports. `0` allows any price.
+    pub round_price_to: u64,
+
+    /// Lamports added to the pot by sponsors this round.
+    /// Included in `lottery_pot_amount`, kept apart from ticket revenue.
+    pub sponsor_total: u64,
+
+    /// Lamports paid for SOL tickets this round, fees included, less refunds.
+    /// Until prizes are paid out, `lottery_pot_amount` equals
+    /// `gross_sales - total_fees + sponsor_total - instant_payouts`, plus the
+    /// rollover the round started with.
+    pub gross_sales: u64,
+
+    /// Ticket fees taken from `gross_sales` this round, less refunded fees.
+    pub total_fees: u64,
+
+    /// Slots over which the prize vests after it is claimed. `0` pays the
+    /// prize out in full on claim.
+    pub vesting_slots: u64,
+
+    /// Smallest contribution `sponsor_pot` accepts, in lamports.
+    pub min_sponsor_amount: u64,
+
+    /// The mint of the winning ticket, set with `winner`.
+    pub winner_mint: Pubkey,
+
+    /// The wallet that bought the winning ticket, read from its `TicketRecord`.
+    /// `Pubkey::default()` if the record was not passed to the draw.
+    pub winner_wallet: Pubkey,
+
+    /// Tickets bought within this many slots of `lottery_end` extend it.
+    pub anti_snipe_slots: u64,
+
+    /// Cap on the total anti-snipe extension of a round, in slots.
+    pub max_anti_snipe_extension: u64,
+
+    /// Slots `lottery_end` has been extended by this round.
+    pub anti_snipe_extension: u64,
+
+    /// The random value the winner was derived from, kept so anyone can
+    /// recompute the draw with `verify_draw`.
+    pub revealed_randomness: [u8; 32],
+
+    /// Whether fees are paid on purchase (`FEE_MODE_IMMEDIATE`) or held
+    /// until `withdraw_fees` (`FEE_MODE_DEFERRED`).
+    pub fee_mode: u8,
+
+    /// Fees held in the vault under `FEE_MODE_DEFERRED`, or paid back by the
+    /// authority on cancellation for refunds. Not part of the pot.
+    pub accrued_fees: u64,
+
+    /// SPL tokens accepted for tickets alongside SOL.
+    #[max_len(MAX_PAYMENT_TOKENS)]
+    pub payment_tokens: Vec<PaymentToken>,
+
+    /// Tokens collected this round for each of `payment_tokens`, held in
+    /// token accounts of the vault and paid out by `claim_token_prize`.
+    #[max_len(MAX_PAYMENT_TOKENS)]
+    pub payment_token_pots: Vec<u64>,
+
+    /// Metadata URI of the collection, and of newly minted tickets unless
+    /// `base_uri` is set. Set by `initialize_config` and can be changed with
+    /// `update_config`.
+    #[max_len(MAX_URI_LENGTH)]
+    pub uri: String,
+
+    /// Set by `cancel_lottery`. Blocks ticket sales and the draw, and lets
+    /// ticket holders reclaim their payment with `refund_ticket`.
+    pub cancelled: bool,
+
+    /// Slots after `lottery_end` from which anyone may draw the winner with
+    /// the committed randomness. `0` keeps the draw to the authority and
+    /// trusted keeper.
+    pub public_draw_timeout_slots: u64,
+
+    /// Collection the winner's badge NFT is minted into on `claim_prize`.
+    /// `Pubkey::default()` mints no badge.
+    pub winner_badge_collection: Pubkey,
+
+    /// Number of distinct winners drawn each round, at most `MAX_WINNERS`.
+    pub num_winners: u8,
+
+    /// Share of the pot paid to each winner, in basis points, by winner index.
+    pub prize_split: [u16; MAX_WINNERS],
+
+    /// The winning ticket indices of the round. The first `num_winners` are
+    /// set by the draw.
+    pub winners: [u64; MAX_WINNERS],
+
+    /// Whether each winner has claimed their share of the pot.
+    pub winners_claimed: [bool; MAX_WINNERS],
+
+    /// Tickets that must be sold for the round to succeed.
+    pub min_tickets: u64,
+
+    /// Whether the pot is burned with `burn_pot` if the round ends below
+    /// `min_tickets`, instead of being refunded.
+    pub burn_on_failure: bool,
+
+    /// Tickets sold per round at most. `0` leaves the supply unlimited, up
+    /// to `MAX_TICKET_NUM`.
+    pub max_tickets: u64,
+
+    /// Tickets a single wallet may buy per round. `0` disables the limit.
+    pub max_tickets_per_wallet: u64,
+
+    /// Lamports a single wallet may spend on tickets per round. `0`
+    /// disables the cap.
+    pub max_spend_per_wallet: u64,
+
+    /// Whether ticket sales, draws, claims, resets and fee withdrawals emit
+    /// events.
+    pub emit_events: bool,
+
+    /// Whether ticket sales are paused by the authority.
+    pub paused: bool,
+
+    /// Authority proposed by `transfer_authority`, waiting to sign
+    /// `accept_authority`. `Pubkey::default()` when none is pending.
+    pub pending_authority: Pubkey,
+
+    /// Lamports of the authority's bond held in the vault, not part of the
+    /// pot until slashed.
+    pub authority_bond: u64,
+
+    /// Slots after `lottery_end` by which the winners must be drawn before
+    /// `slash_bond` is allowed.
+    pub draw_deadline_slots: u64,
+
+    /// Slots after `winner_chosen_slot` during which the winners can claim.
+    /// `0` never expires the claim.
+    pub claim_deadline_slots: u64,
+
+    /// Unclaimed prize lamports rolled over by `rollover_unclaimed`, added to
+    /// the pot of the next round by `reset_lottery`.
+    pub rollover_amount: u64,
+
+    /// Whether tickets are frozen from purchase until `lottery_end`.
+    pub resale_lock: bool,
+
+    /// Seed slot of the committed randomness, checked again at the reveal so
+    /// a re-committed randomness account can't be used.
+    pub randomness_seed_slot: u64,
+
+    /// The wallet that claimed the prize of `winner`, so the winner can be
+    /// read without resolving the ticket's owner. `Pubkey::default()` until
+    /// claimed.
+    pub winner_address: Pubkey,
+
+    /// Whether the schedule is in slots or UNIX timestamps.
+    pub timing_mode: TimingMode,
+
+    /// Slots that must pass after the randomness seed slot before the draw,
+    /// except for the trusted keeper.
+    pub min_confirmations: u64,
+
+    /// Name of the collection, and prefix of every ticket name.
+    #[max_len(MAX_NAME_LENGTH)]
+    pub name: String,
+
+    /// Metadata symbol of the collection and the tickets.
+    #[max_len(MAX_SYMBOL_LENGTH)]
+    pub symbol: String,
+
+    /// Where the randomness of the draw comes from.
+    pub randomness_source: RandomnessSource,
+
+    /// Directory of per-ticket metadata, see `ticket_uri`. Empty to give
+    /// every ticket the shared `uri`.
+    #[max_len(MAX_URI_LENGTH)]
+    pub base_uri: String,
+
+    /// Rounds the lottery runs for at most, counting the first. `0` allows
+    /// rounds up to `MAX_ROUND_NUMBER`.
+    pub max_rounds: u64,
+
+    /// Receives `creator_share_bps` of each SOL prize when it is claimed.
+    pub creator: Pubkey,
+
+    /// Share of each SOL prize paid to `creator`, in basis points.
+    pub creator_share_bps: u16,
+
+    /// Slots after cancellation during which tickets can be refunded. `0`
+    /// keeps refunds open indefinitely.
+    pub refund_window_slots: u64,
+
+    /// Last slot `refund_ticket` pays out, set by `cancel_lottery` from
+    /// `refund_window_slots`. `0` if refunds never expire.
+    pub refund_deadline_slot: u64,
+
+    /// Unclaimed token pots rolled over by `rollover_unclaimed`, by payment
+    /// token, added to the token pots of the next round by `reset_lottery`.
+    #[max_len(MAX_PAYMENT_TOKENS)]
+    pub payment_token_rollovers: Vec<u64>,
+
+    /// Share of the tickets sold that win, in basis points. When set, the
+    /// draw replaces `num_winners` and `prize_split` with
+    /// `fractional_num_winners` winners splitting the pot evenly.
+    pub winner_fraction_bps: u16,
+
+    /// Decimals of each of `payment_tokens`' mints, for displaying token
+    /// amounts. Read from the mint by the first `buy_ticket` paying with it,
+    /// or by `update_payment_mint`, and `0` before that.
+    #[max_len(MAX_PAYMENT_TOKENS)]
+    pub payment_token_decimals: Vec<u8>,
+
+    /// Lamports paid from the pot as instant prizes this round.
+    pub instant_payouts: u64,
+}
+
+/// Escrow for the pot, sponsorships, vesting prizes and deferred fees, so
+/// payouts never touch the rent of the `TokenLottery` state account.
+#[account]
+#[derive(InitSpace)]
+pub struct LotteryVault {
+    /// The bump seed used for deriving the PDA address of this account.
+    pub bump: u8,
+}
+
+/// The buyer of a single ticket.
+#[account]
+#[derive(InitSpace)]
+pub struct TicketRecord {
+    /// The bump seed used for deriving the PDA address of this account.
+    pub bump: u8,
+
+    /// The wallet that bought the ticket.
+    pub buyer: Pubkey,
+
+    /// The ticket's NFT mint.
+    pub ticket_mint: Pubkey,
+
+    /// The round the ticket was bought in.
+    pub round_number: u64,
+
+    /// The ticket's index within the round.
+    pub ticket_index: u64,
+
+    /// The `payment_index` the ticket was paid with, for refunds.
+    pub payment_index: u8,
+}
+
+/// The tickets a wallet bought in the current round of a lottery, for
+/// `max_tickets_per_wallet` and `max_spend_per_wallet`.
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerRecord {
+    /// The bump seed used for deriving the PDA address of this account.
+    pub bump: u8,
+
+    /// The round `tickets` counts. A record left from an earlier round
+    /// starts over.
+    pub round_number: u64,
+
+    /// Tickets bought in `round_number`.
+    pub tickets: u64,
+
+    /// Lamports spent on tickets in `round_number`.
+    pub spent: u64,
+}
+
+/// A slot override for deterministic tests. Only built with `mock-clock`.
+#[cfg(feature = "mock-clock")]
+#[account]
+#[derive(InitSpace)]
+pub struct MockClock {
+    /// The slot reported by `current_slot` when this account is passed.
+    pub slot: u64,
+}
+
+/// The lamports a single sponsor added to the pot in one round.
+#[account]
+#[derive(InitSpace)]
+pub struct SponsorRecord {
+    /// The bump seed used for deriving the PDA address of this account.
+    pub bump: u8,
+
+    /// The account that sponsored the pot.
+    pub sponsor: Pubkey,
+
+    /// The round the contribution was made in.
+    pub round_number: u64,
+
+    /// The total lamports contributed by `sponsor` in `round_number`.
+    pub amount: u64,
+}
+
+/// A prize paid out in installments over `vesting_slots`.
+#[account]
+#[derive(InitSpace)]
+pub struct VestingRecord {
+    /// The bump seed used for deriving the PDA address of this account.
+    pub bump: u8,
+
+    /// The winner receiving the vested prize.
+    pub beneficiary: Pubkey,
+
+    /// The round the prize was won in.
+    pub round_number: u64,
+
+    /// The full prize, in lamports.
+    pub total: u64,
+
+    /// Lamports already paid out by `claim_vested`.
+    pub claimed: u64,
+
+    /// The slot vesting started at.
+    pub start_slot: u64,
+
+    /// Slots until the full prize has vested.
+    pub vesting_slots: u64,
+}
+
+impl VestingRecord {
+    /// Returns the part of the prize vested at `slot`, growing linearly
+    /// from `start_slot` until all of it vests after `vesting_slots`.
+    pub fn vested_at(&self, slot: u64) -> u64 {
+        let elapsed = slot.saturating_sub(self.start_slot);
+        if elapsed >= self.vesting_slots {
+            return self.total;
+        }
+        (self.total as u128 * elapsed as u128 / self.vesting_slots as u128) as u64
+    }
+}
+
+impl TokenLottery {
+    /// Returns `true` if `ticket_num` is still a valid seed for the next
+    /// ticket mint.
+    pub fn has_ticket_capacity(&self) -> bool {
+        self.ticket_num < MAX_TICKET_NUM
+    }
+
+    /// Returns `true` if the ticket at `ticket_index` is verified into the
+    /// sized collection. See `MAX_COLLECTION_SIZE`.
+    pub fn is_tracked_in_collection(ticket_index: u64) -> bool {
+        ticket_index < MAX_COLLECTION_SIZE
+    }
+
+    /// Returns the name of ticket `ticket_index` of round `round_number`.
+    ///
+    /// Every round of a recurring lottery mints into the same collection, so
+    /// later rounds carry the round after the `name` prefix,
+    /// `<round>-<ticket>`, to keep names unique. Round 0 keeps the plain
+    /// `<ticket>` it always had.
+    pub fn ticket_name(&self, round_number: u64, ticket_index: u64) -> String {
+        if round_number == 0 {
+            format!("{}{}", self.name, ticket_index)
+        } else {
+            format!("{}{}-{}", self.name, round_number, ticket_index)
+        }
+    }
+
+    /// Returns the name of the badge minted for winning ticket `ticket_index`
+    /// of round `round_number`.
+    ///
+    /// Badge collections can be shared between lotteries, so the name carries
+    /// the lottery after the `BADGE_NAME` prefix, then the round and ticket
+    /// like `ticket_name`: `<lottery>-<ticket>` in round 0, and
+    /// `<lottery>-<round>-<ticket>` later.
+    pub fn badge_name(&self, round_number: u64, ticket_index: u64) -> String {
+        if round_number == 0 {
+            format!("{}{}-{}", BADGE_NAME, self.lottery_id, ticket_index)
+        } else {
+            format!(
+                "{}{}-{}-{}",
+                BADGE_NAME, self.lottery_id, round_number, ticket_index
+            )
+        }
+    }
+
+    /// Returns the metadata URI of ticket `ticket_index`,
+    /// `<base_uri>/<ticket>.json` so each ticket can have its own artwork,
+    /// or the shared `uri` when no `base_uri` is set.
+    pub fn ticket_uri(&self, ticket_index: u64) -> String {
+        if self.base_uri.is_empty() {
+            self.uri.clone()
+        } else {
+            format!("{}/{}.json", self.base_uri, ticket_index)
+        }
+    }
+
+    /// Returns `true` if another round can be started, within `max_rounds`
+    /// and `MAX_ROUND_NUMBER`.
+    pub fn has_round_capacity(&self) -> bool {
+        self.round_number < MAX_ROUND_NUMBER
+            && (self.max_rounds == 0 || self.round_number + 1 < self.max_rounds)
+    }
+
+    /// Returns the first slot at which the prize may be claimed.
+    pub fn claimable_from_slot(&self) -> u64 {
+        self.winner_chosen_slot
+            .saturating_add(self.claim_delay_slots)
+    }
+
+    /// Returns `true` if `claim_deadline_slots` have passed since the draw at
+    /// `slot`, so unclaimed prizes can no longer be claimed.
+    pub fn is_claim_expired(&self, slot: u64) -> bool {
+        let deadline = self
+            .winner_chosen_slot
+            .saturating_add(self.claim_deadline_slots);
+        self.claim_deadline_slots > 0 && slot > deadline
+    }
+
+    /// Returns `true` if the refund deadline of a cancelled lottery has
+    /// passed at `slot`, so what is left can be swept by the authority.
+    pub fn is_refund_expired(&self, slot: u64) -> bool {
+        self.refund_deadline_slot > 0 && slot > self.refund_deadline_slot
+    }
+
+    /// Returns `true` if `price` is a multiple of `round_price_to`.
+    pub fn is_rounded_price(&self, price: u64) -> bool {
+        self.round_price_to == 0 || price % self.round_price_to == 0
+    }
+
+    /// Returns `true` if `key` is the configured trusted keeper.
+    pub fn is_trusted_keeper(&self, key: &Pubkey) -> bool {
+        self.trusted_keeper != Pubkey::default() && self.trusted_keeper == *key
+    }
+
+    /// Returns `true` if anyone may draw the winner at `slot`, because the
+    /// authority has let `public_draw_timeout_slots` pass since `lottery_end`.
+    pub fn is_public_draw_open(&self, slot: u64) -> bool {
+        let opens_at = self
+            .lottery_end
+            .saturating_add(self.public_draw_timeout_slots);
+        self.public_draw_timeout_slots > 0 && slot >= opens_at
+    }
+
+    /// Returns `true` if the authority missed `draw_deadline_slots` after
+    /// `lottery_end` without drawing or cancelling, so its bond can be
+    /// slashed.
+    pub fn is_draw_overdue(&self, slot: u64) -> bool {
+        let deadline = self.lottery_end.saturating_add(self.draw_deadline_slots);
+        self.authority_bond > 0 && !self.winner_chosen && !self.cancelled && slot > deadline
+    }
+
+    /// Returns `true` if the ticket bought at `slot` with index `ticket_num`
+    /// wins an instant prize.
+    ///
+    /// The roll is derived from public values, so a buyer can predict it and
+    /// choose when to buy. Only use it for prizes small enough that gaming
+    /// the roll is not worth it.
+    pub fn is_instant_win(&self, slot: u64, ticket_num: u64) -> bool {
+        let hash = hashv(&[&slot.to_le_bytes(), &ticket_num.to_le_bytes()]);
+        let roll = u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap());
+        roll % (BASIS_POINTS as u64) < self.instant_win_bps as u64
+    }
+
+    /// Returns how many slots a ticket bought at `slot` extends `lottery_end`
+    /// by, within the remaining `max_anti_snipe_extension`.
+    pub fn anti_snipe_extension_at(&self, slot: u64) -> u64 {
+        if self.lottery_end.saturating_sub(slot) >= self.anti_snipe_slots {
+            return 0;
+        }
+        self.anti_snipe_slots.min(
+            self.max_anti_snipe_extension
+                .saturating_sub(self.anti_snipe_extension),
+        )
+    }
+
+    /// Returns `true` if randomness seeded at `seed_slot` has been confirmed
+    /// for `min_confirmations` slots at `slot`.
+    pub fn is_randomness_confirmed(&self, seed_slot: u64, slot: u64) -> bool {
+        slot >= seed_slot.saturating_add(self.min_confirmations)
+    }
+
+    /// Returns `true` if `ticket_index` was sold this round, i.e. is below
+    /// `ticket_num`.
+    pub fn is_sold_ticket(&self, ticket_index: u64) -> bool {
+        ticket_index < self.ticket_num
+    }
+
+    /// Returns `true` if `ticket_index` is one of the winning tickets.
+    pub fn is_winning_ticket(&self, ticket_index: u64) -> bool {
+        self.winners[..self.num_winners as usize].contains(&ticket_index)
+    }
+
+    /// Returns the part of the pot winner `winner_index` is paid: their share
+    /// of the pot out of the shares not claimed yet. The pot shrinks as
+    /// winners claim, so the last winner to claim receives all that is left.
+    pub fn prize_share(&self, winner_index: usize) -> u64 {
+        let unclaimed_bps: u64 = (0..self.num_winners as usize)
+            .filter(|index| !self.winners_claimed[*index])
+            .map(|index| self.prize_split[index] as u64)
+            .sum();
+        if unclaimed_bps == 0 {
+            return 0;
+        }
+        (self.lottery_pot_amount as u128 * self.prize_split[winner_index] as u128
+            / unclaimed_bps as u128) as u64
+    }
+
+    /// Returns the decimals of amounts paid with `payment_index`:
+    /// `SOL_DECIMALS` for SOL, or the recorded decimals of the payment token.
+    pub fn payment_decimals(&self, payment_index: u8) -> u8 {
+        if payment_index == PAYMENT_INDEX_SOL {
+            return SOL_DECIMALS;
+        }
+        self.payment_token_decimals
+            .get(payment_index as usize - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns the number of winners `winner_fraction_bps` gives for
+    /// `ticket_num` tickets, at least 1 and at most `MAX_WINNERS`.
+    pub fn fractional_num_winners(&self) -> u8 {
+        let winners =
+            self.ticket_num as u128 * self.winner_fraction_bps as u128 / BASIS_POINTS as u128;
+        winners.clamp(1, MAX_WINNERS as u128) as u8
+    }
+
+    /// Returns a `prize_split` sharing the pot evenly between `num_winners`
+    /// winners, the rounding remainder going to the first.
+    pub fn even_prize_split(num_winners: u8) -> [u16; MAX_WINNERS] {
+        let mut split = [0; MAX_WINNERS];
+        let share = BASIS_POINTS / num_winners as u16;
+        split[..num_winners as usize].fill(share);
+        split[0] += BASIS_POINTS - share * num_winners as u16;
+        split
+    }
+
+    /// Returns the part of a prize of `amount` paid to `creator` when it is
+    /// claimed. The winner receives the rest.
+    pub fn creator_cut(&self, amount: u64) -> u64 {
+        (amount as u128 * self.creator_share_bps as u128 / BASIS_POINTS as u128) as u64
+    }
+
+    /// Returns the part of the pot that came from ticket sales rather than
+    /// sponsors.
+    pub fn ticket_revenue(&self) -> u64 {
+        self.lottery_pot_amount.saturating_sub(self.sponsor_total)
+    }
+
+    /// Returns the part of `amount` taken as a fee.
+    pub fn fee_for(&self, amount: u64) -> u64 {
+        (amount as u128 * self.fee_basis_points as u128 / BASIS_POINTS as u128) as u64
+    }
+
+    /// Splits `fee` between the treasuries according to their shares.
+    /// Rounding dust goes to the last treasury so the parts always sum to `fee`.
+    pub fn treasury_amounts(&self, fee: u64) -> Vec<u64> {
+        let mut amounts: Vec<u64> = self
+            .treasuries
+            .iter()
+            .map(|treasury| {
+                (fee as u128 * treasury.share_bps as u128 / BASIS_POINTS as u128) as u64
+            })
+            .collect();
+        let distributed: u64 = amounts.iter().sum();
+        if let Some(last) = amounts.last_mut() {
+            *last += fee - distributed;
+        }
+        amounts
+    }
 }
 
 // @self-notes: defining all the state programs/accounst here (#[account] is used for it)
 // it defined the state programs here
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{fits_badge_name, fits_base_uri, fits_name, NAME, URI};
+
+    #[test]
+    fn inflated_ticket_counter_has_no_capacity() {
+        let mut token_lottery = TokenLottery {
+            ticket_num: MAX_TICKET_NUM - 1,
+            ..Default::default()
+        };
+        assert!(token_lottery.has_ticket_capacity());
+
+        token_lottery.ticket_num = MAX_TICKET_NUM;
+        assert!(!token_lottery.has_ticket_capacity());
+
+        token_lottery.ticket_num = u64::MAX;
+        assert!(!token_lottery.has_ticket_capacity());
+    }
+
+    #[test]
+    fn tickets_past_collection_size_are_untracked() {
+        assert!(TokenLottery::is_tracked_in_collection(0));
+        assert!(TokenLottery::is_tracked_in_collection(
+            MAX_COLLECTION_SIZE - 1
+        ));
+        assert!(!TokenLottery::is_tracked_in_collection(MAX_COLLECTION_SIZE));
+        assert!(MAX_COLLECTION_SIZE < MAX_TICKET_NUM);
+    }
+
+    #[test]
+    fn randomness_waits_for_min_confirmations() {
+        let mut token_lottery = TokenLottery::default();
+        assert!(token_lottery.is_randomness_confirmed(100, 100));
+
+        token_lottery.min_confirmations = 10;
+        assert!(!token_lottery.is_randomness_confirmed(100, 109));
+        assert!(token_lottery.is_randomness_confirmed(100, 110));
+        assert!(!token_lottery.is_randomness_confirmed(u64::MAX, u64::MAX - 1));
+    }
+
+    #[test]
+    fn ticket_names_are_unique_per_round() {
+        let mut token_lottery = TokenLottery {
+            name: NAME.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(token_lottery.ticket_name(0, 7), "Token Lottery Ticket #7");
+        assert_eq!(token_lottery.ticket_name(2, 7), "Token Lottery Ticket #2-7");
+        assert_ne!(
+            token_lottery.ticket_name(1, 0),
+            token_lottery.ticket_name(2, 0)
+        );
+        assert_ne!(
+            token_lottery.ticket_name(1, 10),
+            token_lottery.ticket_name(11, 0)
+        );
+        assert!(
+            token_lottery
+                .ticket_name(MAX_ROUND_NUMBER, MAX_TICKET_NUM - 1)
+                .len()
+                <= MAX_NAME_LENGTH
+        );
+
+        token_lottery.round_number = MAX_ROUND_NUMBER;
+        assert!(!token_lottery.has_round_capacity());
+
+        token_lottery.max_rounds = 3;
+        token_lottery.round_number = 1;
+        assert!(token_lottery.has_round_capacity());
+        token_lottery.round_number = 2;
+        assert!(!token_lottery.has_round_capacity());
+    }
+
+    #[test]
+    fn badge_names_carry_the_lottery_and_round() {
+        let token_lottery = TokenLottery {
+            lottery_id: 42,
+            ..Default::default()
+        };
+        assert_eq!(token_lottery.badge_name(0, 7), "Winner #42-7");
+        assert_eq!(token_lottery.badge_name(2, 7), "Winner #42-2-7");
+        assert_ne!(
+            token_lottery.badge_name(0, 7),
+            TokenLottery {
+                lottery_id: 4,
+                ..Default::default()
+            }
+            .badge_name(0, 27)
+        );
+
+        let largest = TokenLottery {
+            lottery_id: 10u64.pow(13) - 1,
+            ..Default::default()
+        };
+        assert!(fits_badge_name(largest.lottery_id));
+        assert!(
+            largest
+                .badge_name(MAX_ROUND_NUMBER, MAX_TICKET_NUM - 1)
+                .len()
+                <= MAX_NAME_LENGTH
+        );
+        assert!(!fits_badge_name(10u64.pow(13)));
+    }
+
+    #[test]
+    fn names_that_fit_every_ticket_are_accepted() {
+        assert!(fits_name(NAME));
+        assert!(fits_name(&"x".repeat(22)));
+        assert!(!fits_name(&"x".repeat(23)));
+    }
+
+    #[test]
+    fn ticket_uris_are_per_serial_under_a_base_uri() {
+        let mut token_lottery = TokenLottery {
+            uri: URI.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(token_lottery.ticket_uri(7), URI);
+
+        token_lottery.base_uri = "https://example.com/tickets".to_string();
+        assert_eq!(
+            token_lottery.ticket_uri(7),
+            "https://example.com/tickets/7.json"
+        );
+        assert_ne!(token_lottery.ticket_uri(1), token_lottery.ticket_uri(10));
+    }
+
+    #[test]
+    fn base_uris_that_fit_every_ticket_are_accepted() {
+        assert!(fits_base_uri(""));
+        assert!(fits_base_uri(&"x".repeat(MAX_URI_LENGTH - 12)));
+        assert!(!fits_base_uri(&"x".repeat(MAX_URI_LENGTH - 11)));
+    }
+
+    #[test]
+    fn instant_win_rolls_follow_probability() {
+        let mut token_lottery = TokenLottery::default();
+        assert!((0..1_000).all(|ticket| !token_lottery.is_instant_win(42, ticket)));
+
+        token_lottery.instant_win_bps = BASIS_POINTS;
+        assert!((0..1_000).all(|ticket| token_lottery.is_instant_win(42, ticket)));
+
+        token_lottery.instant_win_bps = 2_500;
+        let wins = (0..10_000)
+            .filter(|ticket| token_lottery.is_instant_win(42, *ticket))
+            .count();
+        assert!((2_000..3_000).contains(&wins));
+    }
+
+    #[test]
+    fn fee_is_split_between_treasuries() {
+        let token_lottery = TokenLottery {
+            fee_basis_points: 1_000,
+            treasuries: vec![
+                TreasuryShare {
+                    recipient: Pubkey::new_unique(),
+                    share_bps: 6_000,
+                },
+                TreasuryShare {
+                    recipient: Pubkey::new_unique(),
+                    share_bps: 4_000,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let fee = token_lottery.fee_for(10_001);
+        assert_eq!(fee, 1_000);
+        assert_eq!(token_lottery.treasury_amounts(fee), vec![600, 400]);
+        assert_eq!(token_lottery.treasury_amounts(7), vec![4, 3]);
+    }
+
+    #[test]
+    fn prize_vests_linearly() {
+        let vesting = VestingRecord {
+            bump: 0,
+            beneficiary: Pubkey::new_unique(),
+            round_number: 0,
+            total: 1_000,
+            claimed: 0,
+            start_slot: 100,
+            vesting_slots: 40,
+        };
+
+        assert_eq!(vesting.vested_at(90), 0);
+        assert_eq!(vesting.vested_at(100), 0);
+        assert_eq!(vesting.vested_at(110), 250);
+        assert_eq!(vesting.vested_at(139), 975);
+        assert_eq!(vesting.vested_at(140), 1_000);
+        assert_eq!(vesting.vested_at(u64::MAX), 1_000);
+    }
+
+    #[test]
+    fn late_tickets_extend_lottery_end_up_to_the_cap() {
+        let mut token_lottery = TokenLottery {
+            lottery_end: 100,
+            anti_snipe_slots: 10,
+            max_anti_snipe_extension: 15,
+            ..Default::default()
+        };
+
+        assert_eq!(token_lottery.anti_snipe_extension_at(90), 0);
+        assert_eq!(token_lottery.anti_snipe_extension_at(91), 10);
+        assert_eq!(token_lottery.anti_snipe_extension_at(100), 10);
+
+        token_lottery.anti_snipe_extension = 10;
+        assert_eq!(token_lottery.anti_snipe_extension_at(100), 5);
+
+        token_lottery.anti_snipe_extension = 15;
+        assert_eq!(token_lottery.anti_snipe_extension_at(100), 0);
+
+        token_lottery.anti_snipe_slots = 0;
+        token_lottery.anti_snipe_extension = 0;
+        assert_eq!(token_lottery.anti_snipe_extension_at(100), 0);
+    }
+
+    #[test]
+    fn public_draw_opens_after_the_timeout() {
+        let mut token_lottery = TokenLottery {
+            lottery_end: 100,
+            ..Default::default()
+        };
+        assert!(!token_lottery.is_public_draw_open(u64::MAX));
+
+        token_lottery.public_draw_timeout_slots = 50;
+        assert!(!token_lottery.is_public_draw_open(149));
+        assert!(token_lottery.is_public_draw_open(150));
+    }
+
+    #[test]
+    fn bond_is_slashable_once_the_draw_deadline_passes() {
+        let mut token_lottery = TokenLottery {
+            lottery_end: 100,
+            draw_deadline_slots: 20,
+            ..Default::default()
+        };
+        assert!(!token_lottery.is_draw_overdue(u64::MAX));
+
+        token_lottery.authority_bond = 1;
+        assert!(!token_lottery.is_draw_overdue(120));
+        assert!(token_lottery.is_draw_overdue(121));
+
+        token_lottery.winner_chosen = true;
+        assert!(!token_lottery.is_draw_overdue(121));
+
+        token_lottery.winner_chosen = false;
+        token_lottery.cancelled = true;
+        assert!(!token_lottery.is_draw_overdue(121));
+    }
+
+    #[test]
+    fn claims_expire_after_the_deadline() {
+        let mut token_lottery = TokenLottery {
+            winner_chosen_slot: 100,
+            ..Default::default()
+        };
+        assert!(!token_lottery.is_claim_expired(u64::MAX));
+
+        token_lottery.claim_deadline_slots = 50;
+        assert!(!token_lottery.is_claim_expired(150));
+        assert!(token_lottery.is_claim_expired(151));
+    }
+
+    #[test]
+    fn refunds_expire_after_the_deadline() {
+        let mut token_lottery = TokenLottery::default();
+        assert!(!token_lottery.is_refund_expired(u64::MAX));
+
+        token_lottery.refund_deadline_slot = 150;
+        assert!(!token_lottery.is_refund_expired(150));
+        assert!(token_lottery.is_refund_expired(151));
+    }
+
+    #[test]
+    fn winner_must_be_a_sold_ticket() {
+        let token_lottery = TokenLottery {
+            ticket_num: 3,
+            ..Default::default()
+        };
+        assert!(token_lottery.is_sold_ticket(2));
+        assert!(!token_lottery.is_sold_ticket(3));
+    }
+
+    #[test]
+    fn creator_cut_is_taken_from_the_prize() {
+        let mut token_lottery = TokenLottery::default();
+        assert_eq!(token_lottery.creator_cut(9_000), 0);
+
+        token_lottery.creator_share_bps = 1_000;
+        assert_eq!(token_lottery.creator_cut(9_000), 900);
+        assert_eq!(token_lottery.creator_cut(9_999), 999);
+        assert_eq!(token_lottery.creator_cut(u64::MAX), u64::MAX / 10);
+
+        token_lottery.creator_share_bps = BASIS_POINTS;
+        assert_eq!(token_lottery.creator_cut(9_000), 9_000);
+    }
+
+    #[test]
+    fn prize_shares_pay_out_the_whole_pot() {
+        let mut token_lottery = TokenLottery {
+            lottery_pot_amount: 1_001,
+            num_winners: 3,
+            prize_split: [6_000, 3_000, 1_000, 0],
+            ..Default::default()
+        };
+
+        let mut paid = 0;
+        for winner_index in [1, 0, 2] {
+            let share = token_lottery.prize_share(winner_index);
+            token_lottery.lottery_pot_amount -= share;
+            token_lottery.winners_claimed[winner_index] = true;
+            paid += share;
+        }
+        assert_eq!(paid, 1_001);
+        assert_eq!(token_lottery.lottery_pot_amount, 0);
+    }
+
+    #[test]
+    fn payment_decimals_follow_the_payment_index() {
+        let token_lottery = TokenLottery {
+            payment_token_decimals: vec![6, 0],
+            ..Default::default()
+        };
+        assert_eq!(token_lottery.payment_decimals(PAYMENT_INDEX_SOL), 9);
+        assert_eq!(token_lottery.payment_decimals(1), 6);
+        assert_eq!(token_lottery.payment_decimals(2), 0);
+        assert_eq!(token_lottery.payment_decimals(3), 0);
+    }
+
+    #[test]
+    fn winner_fraction_scales_with_sales() {
+        let mut token_lottery = TokenLottery {
+            ticket_num: 1_000,
+            winner_fraction_bps: 100,
+            ..Default::default()
+        };
+        // 1% of 1000 tickets is 10 winners, bounded by the winner arrays
+        assert_eq!(token_lottery.fractional_num_winners(), MAX_WINNERS as u8);
+
+        token_lottery.ticket_num = 300;
+        assert_eq!(token_lottery.fractional_num_winners(), 3);
+        token_lottery.ticket_num = 99;
+        assert_eq!(token_lottery.fractional_num_winners(), 1);
+        token_lottery.ticket_num = 1;
+        assert_eq!(token_lottery.fractional_num_winners(), 1);
+    }
+
+    #[test]
+    fn even_prize_split_sums_to_basis_points() {
+        assert_eq!(TokenLottery::even_prize_split(1), [10_000, 0, 0, 0]);
+        assert_eq!(TokenLottery::even_prize_split(2), [5_000, 5_000, 0, 0]);
+        assert_eq!(TokenLottery::even_prize_split(3), [3_334, 3_333, 3_333, 0]);
+        assert_eq!(
+            TokenLottery::even_prize_split(4),
+            [2_500, 2_500, 2_500, 2_500]
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+
+#[cfg(feature = "mock-clock")]
+use crate::state::MockClock;
+use crate::state::TimingMode;
+
+/// Returns the current slot used for the lottery schedule.
+///
+/// With the `mock-clock` feature, a `MockClock` account among `accounts`
+/// overrides the `Clock` sysvar, so tests can move through the lottery
+/// lifecycle deterministically. Randomness freshness is always checked
+/// against the sysvar, since the oracle validates against the real slot.
+#[cfg_attr(not(feature = "mock-clock"), allow(unused_variables))]
+pub fn current_slot(accounts: &[AccountInfo]) -> Result<u64> {
+    #[cfg(feature = "mock-clock")]
+    {
+        let (mock_clock, _) = Pubkey::find_program_address(&[b"mock_clock".as_ref()], &crate::ID);
+        if let Some(account) = accounts.iter().find(|account| account.key() == mock_clock) {
+            return Ok(MockClock::try_deserialize(&mut &account.data.borrow()[..])?.slot);
+        }
+    }
+
+    Ok(Clock::get()?.slot)
+}
+
+/// Returns the current time on the lottery schedule: the slot from
+/// `current_slot`, or the `Clock` UNIX timestamp under
+/// `TimingMode::UnixTimestamp`. The mock clock only overrides slots.
+pub fn schedule_time(timing_mode: TimingMode, accounts: &[AccountInfo]) -> Result<u64> {
+    match timing_mode {
+        TimingMode::Slot => current_slot(accounts),
+        TimingMode::UnixTimestamp => Ok(Clock::get()?.unix_timestamp.max(0) as u64),
+    }
+}
@@ -0,0 +1,130 @@
+use anchor::constants::PAYMENT_INDEX_SOL;
+use anchor::cpi::accounts::BuyTicket;
+use anchor::program::TokenLottery;
+use anchor_lang::prelude::*;
+
+declare_id!("QC77xgnBmtRCq8ybrxYAL37y1PVznvQMvKuHQDDfPoj");
+
+/// A minimal aggregator used by the tests to buy lottery tickets through CPI,
+/// with a PDA of this program as the payer.
+#[program]
+pub mod cpi_buyer {
+    use super::*;
+
+    pub fn buy_ticket<'info>(
+        ctx: Context<'_, '_, '_, 'info, BuyTicketFor<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let signer_seeds: &[&[&[u8]]] = &[&[b"buyer".as_ref(), &[ctx.bumps.buyer]]];
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_lottery_program.to_account_info(),
+            BuyTicket {
+                payer: ctx.accounts.buyer.to_account_info(),
+                token_lottery: ctx.accounts.token_lottery.to_account_info(),
+                lottery_vault: ctx.accounts.lottery_vault.to_account_info(),
+                ticket_mint: ctx.accounts.ticket_mint.to_account_info(),
+                destination: ctx.accounts.destination.to_account_info(),
+                ticket_record: ctx.accounts.ticket_record.to_account_info(),
+                buyer_record: ctx.accounts.buyer_record.to_account_info(),
+                metadata: ctx.accounts.metadata.to_account_info(),
+                master_edition: ctx.accounts.master_edition.to_account_info(),
+                collection_metadata: ctx.accounts.collection_metadata.to_account_info(),
+                collection_master_edition: ctx.accounts.collection_master_edition.to_account_info(),
+                collection_mint: ctx.accounts.collection_mint.to_account_info(),
+                collection_authority: ctx.accounts.collection_authority.to_account_info(),
+                collection_authority_record: ctx
+                    .accounts
+                    .collection_authority_record
+                    .to_account_info(),
+                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_metadata_program: ctx.accounts.token_metadata_program.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                payment_mint: None,
+                payer_payment_account: None,
+                vault_payment_account: None,
+            },
+            signer_seeds,
+        )
+        .with_remaining_accounts(ctx.remaining_accounts.to_vec());
+
+        anchor::cpi::buy_ticket(cpi_ctx, amount, PAYMENT_INDEX_SOL)
+    }
+}
+
+/// The `buy_ticket` accounts, with a PDA of this program as the payer.
+/// Everything but the payer is validated by the lottery program.
+#[derive(Accounts)]
+pub struct BuyTicketFor<'info> {
+    /// CHECK: System-owned PDA funding the ticket, signed for with its seeds.
+    #[account(mut, seeds = [b"buyer".as_ref()], bump)]
+    pub buyer: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub token_lottery: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub lottery_vault: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub ticket_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub destination: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub ticket_record: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub buyer_record: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub collection_metadata: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub collection_master_edition: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    #[account(mut)]
+    pub collection_mint: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    pub collection_authority: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    pub collection_authority_record: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    pub associated_token_program: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    pub token_program: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+
+    /// CHECK: Validated by the lottery program.
+    pub token_metadata_program: UncheckedAccount<'info>,
+
+    /// CHECK: Validated by the lottery program.
+    pub rent: UncheckedAccount<'info>,
+
+    pub token_lottery_program: Program<'info, TokenLottery>,
+}
@@ -0,0 +1,174 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenLottery } from "../target/types/token_lottery";
+import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { assert } from "chai";
+
+// Only runs against a program built with `--features mock-clock`. The mock
+// clock account is a singleton, so run this file on its own validator.
+
+describe("token-lottery with mock clock", function () {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.TokenLottery as Program<TokenLottery>;
+
+  const TICKET_PRICE = 10000;
+  const START_SLOT = 1_000_000_000;
+  const END_SLOT = START_SLOT + 100;
+  const LOTTERY_ID = 0;
+
+  const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("token_lottery"),
+      new anchor.BN(LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+
+  const mockClockAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("mock_clock")],
+    program.programId
+  )[0];
+  const mockClockAccount = {
+    pubkey: mockClockAddress,
+    isWritable: false,
+    isSigner: false,
+  };
+
+  before(function () {
+    if (!program.idl.instructions.some((ix) => ix.name === "setMockClock")) {
+      this.skip();
+    }
+  });
+
+  async function setMockClock(slot: number) {
+    await (program.methods as any)
+      .setMockClock(new anchor.BN(slot))
+      .rpc({ commitment: "confirmed" });
+  }
+
+  async function buyTicket() {
+    const buyTicketIx = await program.methods
+      .buyTicket(new anchor.BN(TICKET_PRICE), 0)
+      .accountsPartial({
+        tokenLottery: tokenLotteryAddress,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .remainingAccounts([mockClockAccount])
+      .instruction();
+    const computeIx = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+      units: 300000,
+    });
+    const tx = new anchor.web3.Transaction().add(buyTicketIx).add(computeIx);
+    return anchor.web3.sendAndConfirmTransaction(connection, tx, [
+      wallet.payer,
+    ]);
+  }
+
+  it("Initializes a lottery scheduled in the future", async () => {
+    await setMockClock(START_SLOT - 10);
+
+    await program.methods
+      .initializeConfig(
+        new anchor.BN(LOTTERY_ID),
+        new anchor.BN(START_SLOT),
+        new anchor.BN(END_SLOT),
+        new anchor.BN(TICKET_PRICE),
+        {
+          claimDelaySlots: new anchor.BN(0),
+          feeBasisPoints: 0,
+          treasuries: [],
+          consolationAmount: new anchor.BN(0),
+          trustedKeeper: anchor.web3.PublicKey.default,
+          minBuyerBalance: new anchor.BN(0),
+          authorityCannotBuy: false,
+          instantWinBps: 0,
+          instantWinPrize: new anchor.BN(0),
+          roundPriceTo: new anchor.BN(0),
+          vestingSlots: new anchor.BN(0),
+          requireFutureStart: true,
+          minSponsorAmount: new anchor.BN(0),
+          antiSnipeSlots: new anchor.BN(0),
+          maxAntiSnipeExtension: new anchor.BN(0),
+          feeMode: 0,
+          paymentTokens: [],
+          publicDrawTimeoutSlots: new anchor.BN(0),
+          winnerBadgeCollection: anchor.web3.PublicKey.default,
+          numWinners: 1,
+          prizeSplit: [10000, 0, 0, 0],
+          minTickets: new anchor.BN(0),
+          burnOnFailure: false,
+          maxTickets: new anchor.BN(0),
+          maxTicketsPerWallet: new anchor.BN(0),
+          maxSpendPerWallet: new anchor.BN(0),
+          emitEvents: true,
+          authorityBond: new anchor.BN(0),
+          drawDeadlineSlots: new anchor.BN(0),
+          claimDeadlineSlots: new anchor.BN(0),
+          resaleLock: false,
+          timingMode: { slot: {} },
+          minConfirmations: new anchor.BN(0),
+          name: "Token Lottery Ticket #",
+          symbol: "TICKET",
+          uri: "Token Lottery",
+          randomnessSource: { switchboard: {} },
+          baseUri: "",
+          maxRounds: new anchor.BN(0),
+          creator: anchor.web3.PublicKey.default,
+          creatorShareBps: 0,
+          refundWindowSlots: new anchor.BN(0),
+          winnerFractionBps: 0,
+        }
+      )
+      .remainingAccounts([mockClockAccount])
+      .rpc();
+
+    const initLotteryIx = await program.methods
+      .initializeLottery()
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction().add(initLotteryIx),
+      [wallet.payer]
+    );
+  });
+
+  it("Rejects tickets before the start slot", async () => {
+    try {
+      await buyTicket();
+      assert.fail("ticket bought before the start slot");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryNotOpen");
+    }
+  });
+
+  it("Sells tickets once the mock clock reaches the start slot", async () => {
+    await setMockClock(START_SLOT);
+    await buyTicket();
+
+    await setMockClock(END_SLOT);
+    await buyTicket();
+
+    const lottery = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(lottery.ticketNum.toNumber(), 2);
+  });
+
+  it("Rejects tickets after the end slot", async () => {
+    await setMockClock(END_SLOT + 1);
+    try {
+      await buyTicket();
+      assert.fail("ticket bought after the end slot");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryNotOpen");
+    }
+  });
+});
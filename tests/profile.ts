@@ -0,0 +1,125 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { TokenLottery } from "../target/types/token_lottery";
+import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
+import { assert } from "chai";
+
+// Only meaningful against a program built with `--features profile`; the
+// test skips itself when the handlers log no compute units.
+
+describe("token-lottery with profiling", function () {
+  const provider = anchor.AnchorProvider.env();
+  const connection = provider.connection;
+  const wallet = provider.wallet as anchor.Wallet;
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.TokenLottery as Program<TokenLottery>;
+
+  const TICKET_PRICE = 10000;
+  const LOTTERY_ID = 1000;
+
+  const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("token_lottery"),
+      new anchor.BN(LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+
+  it("Logs the compute units of buy_ticket", async function () {
+    const slot = await connection.getSlot();
+    await program.methods
+      .initializeConfig(
+        new anchor.BN(LOTTERY_ID),
+        new anchor.BN(0),
+        new anchor.BN(slot + 1000),
+        new anchor.BN(TICKET_PRICE),
+        {
+          claimDelaySlots: new anchor.BN(0),
+          feeBasisPoints: 0,
+          treasuries: [],
+          consolationAmount: new anchor.BN(0),
+          trustedKeeper: anchor.web3.PublicKey.default,
+          minBuyerBalance: new anchor.BN(0),
+          authorityCannotBuy: false,
+          instantWinBps: 0,
+          instantWinPrize: new anchor.BN(0),
+          roundPriceTo: new anchor.BN(0),
+          vestingSlots: new anchor.BN(0),
+          requireFutureStart: false,
+          minSponsorAmount: new anchor.BN(0),
+          antiSnipeSlots: new anchor.BN(0),
+          maxAntiSnipeExtension: new anchor.BN(0),
+          feeMode: 0,
+          paymentTokens: [],
+          publicDrawTimeoutSlots: new anchor.BN(0),
+          winnerBadgeCollection: anchor.web3.PublicKey.default,
+          numWinners: 1,
+          prizeSplit: [10000, 0, 0, 0],
+          minTickets: new anchor.BN(0),
+          burnOnFailure: false,
+          maxTickets: new anchor.BN(0),
+          maxTicketsPerWallet: new anchor.BN(0),
+          maxSpendPerWallet: new anchor.BN(0),
+          emitEvents: true,
+          authorityBond: new anchor.BN(0),
+          drawDeadlineSlots: new anchor.BN(0),
+          claimDeadlineSlots: new anchor.BN(0),
+          resaleLock: false,
+          timingMode: { slot: {} },
+          minConfirmations: new anchor.BN(0),
+          name: "Token Lottery Ticket #",
+          symbol: "TICKET",
+          uri: "Token Lottery",
+          randomnessSource: { switchboard: {} },
+          baseUri: "",
+          maxRounds: new anchor.BN(0),
+          creator: anchor.web3.PublicKey.default,
+          creatorShareBps: 0,
+          refundWindowSlots: new anchor.BN(0),
+          winnerFractionBps: 0,
+        }
+      )
+      .rpc();
+    await program.methods
+      .initializeLottery()
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const buyTicketIx = await program.methods
+      .buyTicket(new anchor.BN(TICKET_PRICE), 0)
+      .accountsPartial({
+        tokenLottery: tokenLotteryAddress,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+    const computeIx = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+      units: 300000,
+    });
+    const tx = new anchor.web3.Transaction().add(buyTicketIx).add(computeIx);
+    const sig = await anchor.web3.sendAndConfirmTransaction(connection, tx, [
+      wallet.payer,
+    ]);
+
+    const txInfo = await connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const consumption = txInfo.meta.logMessages.filter((log) =>
+      log.includes("Program consumption")
+    );
+    if (consumption.length === 0) {
+      this.skip();
+    }
+    // One log at the start and one at the end of the handler
+    assert.equal(consumption.length, 2);
+
+    const lottery = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(lottery.ticketNum.toNumber(), 1);
+  });
+});
@@ -2,8 +2,19 @@ import * as anchor from "@coral-xyz/anchor";
 import * as sb from "@switchboard-xyz/on-demand";
 import { Program } from "@coral-xyz/anchor";
 import { TokenLottery } from "../target/types/token_lottery";
+import { CpiBuyer } from "../target/types/cpi_buyer";
 import { TOKEN_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-import { getAssociatedTokenAddressSync } from "@solana/spl-token";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  createMint,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getOrCreateAssociatedTokenAccount,
+  mintTo,
+  transfer,
+} from "@solana/spl-token";
+import { assert } from "chai";
 
 //writing the test over here
 // we can run via bankrun also over here
@@ -35,12 +46,105 @@ describe("token-lottery", () => {
     switchboardProgram = new anchor.Program(switchboardIDL, provider);
   });
 
-  async function buyTicket() {
+  const TICKET_PRICE = 10000;
+  const CLAIM_DELAY_SLOTS = 20;
+  const CONSOLATION_AMOUNT = 1000;
+  const ROUND_PRICE_TO = 1000;
+  const VESTING_SLOTS = 20;
+  const MIN_SPONSOR_AMOUNT = 10000;
+  const ANTI_SNIPE_SLOTS = 10;
+  const FEE_MODE_IMMEDIATE = 0;
+  const MIN_BUYER_BALANCE = anchor.web3.LAMPORTS_PER_SOL / 2;
+  const FEE_BASIS_POINTS = 1000;
+  const TICKET_FEE = (TICKET_PRICE * FEE_BASIS_POINTS) / 10000;
+  const treasuries = [
+    { keypair: anchor.web3.Keypair.generate(), shareBps: 6000 },
+    { keypair: anchor.web3.Keypair.generate(), shareBps: 4000 },
+  ];
+  const PAYMENT_INDEX_SOL = 0;
+  const paymentTokens = [
+    { mint: anchor.web3.Keypair.generate(), price: 1000 },
+    { mint: anchor.web3.Keypair.generate(), price: 2500 },
+  ];
+
+  const LOTTERY_ID = 0;
+  const lotteryIdSeed = new anchor.BN(LOTTERY_ID).toArrayLike(Buffer, "le", 8);
+
+  const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("token_lottery"), lotteryIdSeed],
+    program.programId
+  )[0];
+
+  const OTHER_LOTTERY_ID = LOTTERY_ID + 1;
+  const otherLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("token_lottery"),
+      new anchor.BN(OTHER_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+
+  const lotteryVaultAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("vault"), tokenLotteryAddress.toBuffer()],
+    program.programId
+  )[0];
+
+  async function waitForSlot(target: number) {
+    while ((await connection.getSlot()) < target) {
+      await new Promise((resolve) => setTimeout(resolve, 400));
+    }
+  }
+
+  const collectionMintAddress = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from("collection_mint"), lotteryIdSeed],
+    program.programId
+  )[0];
+
+  function ticketMintAddress(
+    round: number | anchor.BN,
+    index: number | anchor.BN
+  ) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        lotteryIdSeed,
+        new anchor.BN(round).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  }
+
+  async function getEvents(signature: string) {
+    const txInfo = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const parser = new anchor.EventParser(
+      program.programId,
+      new anchor.BorshCoder(program.idl)
+    );
+    return Array.from(parser.parseLogs(txInfo.meta.logMessages));
+  }
+
+  async function buyTicket(
+    amount = TICKET_PRICE,
+    buyer = wallet.payer,
+    lottery = tokenLotteryAddress
+  ) {
     const buyTicketIx = await program.methods
-      .buyTicket()
-      .accounts({
+      .buyTicket(new anchor.BN(amount), PAYMENT_INDEX_SOL)
+      .accountsPartial({
+        tokenLottery: lottery,
+        payer: buyer.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
+      .remainingAccounts(
+        treasuries.map(({ keypair }) => ({
+          pubkey: keypair.publicKey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
       .instruction();
 
     const blockhashContext = await connection.getLatestBlockhash();
@@ -56,26 +160,149 @@ describe("token-lottery", () => {
     const tx = new anchor.web3.Transaction({
       blockhash: blockhashContext.blockhash,
       lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
-      feePayer: wallet.payer.publicKey,
+      feePayer: buyer.publicKey,
     })
       .add(buyTicketIx)
       .add(computeIx)
       .add(priorityIx);
 
     const sig = await anchor.web3.sendAndConfirmTransaction(connection, tx, [
-      wallet.payer,
+      buyer,
     ]);
     console.log("buy ticket ", sig);
+    return sig;
+  }
+
+  async function fundedKeypair(lamports: number) {
+    const keypair = anchor.web3.Keypair.generate();
+    const airdropSig = await connection.requestAirdrop(
+      keypair.publicKey,
+      lamports
+    );
+    await connection.confirmTransaction(airdropSig);
+    return keypair;
+  }
+
+  function configParams(overrides = {}) {
+    return {
+      claimDelaySlots: new anchor.BN(CLAIM_DELAY_SLOTS),
+      feeBasisPoints: FEE_BASIS_POINTS,
+      treasuries: treasuries.map(({ keypair, shareBps }) => ({
+        recipient: keypair.publicKey,
+        shareBps,
+      })),
+      consolationAmount: new anchor.BN(CONSOLATION_AMOUNT),
+      trustedKeeper: anchor.web3.PublicKey.default,
+      minBuyerBalance: new anchor.BN(MIN_BUYER_BALANCE),
+      authorityCannotBuy: false,
+      instantWinBps: 0,
+      instantWinPrize: new anchor.BN(0),
+      roundPriceTo: new anchor.BN(ROUND_PRICE_TO),
+      vestingSlots: new anchor.BN(VESTING_SLOTS),
+      requireFutureStart: false,
+      minSponsorAmount: new anchor.BN(MIN_SPONSOR_AMOUNT),
+      antiSnipeSlots: new anchor.BN(ANTI_SNIPE_SLOTS),
+      maxAntiSnipeExtension: new anchor.BN(ANTI_SNIPE_SLOTS * 2),
+      feeMode: FEE_MODE_IMMEDIATE,
+      paymentTokens: paymentTokens.map(({ mint, price }) => ({
+        mint: mint.publicKey,
+        price: new anchor.BN(price),
+      })),
+      publicDrawTimeoutSlots: new anchor.BN(0),
+      winnerBadgeCollection: anchor.web3.PublicKey.default,
+      numWinners: 1,
+      prizeSplit: [10000, 0, 0, 0],
+      minTickets: new anchor.BN(0),
+      burnOnFailure: false,
+      maxTickets: new anchor.BN(0),
+      maxTicketsPerWallet: new anchor.BN(0),
+      maxSpendPerWallet: new anchor.BN(0),
+      emitEvents: true,
+      authorityBond: new anchor.BN(0),
+      drawDeadlineSlots: new anchor.BN(0),
+      claimDeadlineSlots: new anchor.BN(0),
+      resaleLock: false,
+      timingMode: { slot: {} },
+      minConfirmations: new anchor.BN(0),
+      name: "Token Lottery Ticket #",
+      symbol: "TICKET",
+      uri: "Token Lottery",
+      randomnessSource: { switchboard: {} },
+      baseUri: "",
+      maxRounds: new anchor.BN(0),
+      creator: anchor.web3.PublicKey.default,
+      creatorShareBps: 0,
+      refundWindowSlots: new anchor.BN(0),
+      winnerFractionBps: 0,
+      ...overrides,
+    };
   }
 
+  it("Rejects initializing the lottery before the config", async () => {
+    try {
+      await program.methods
+        .initializeLottery()
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("lottery should not initialize without a config");
+    } catch (err) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+  });
+
+  it("Rejects a price that is not a multiple of round_price_to", async () => {
+    const slot = await connection.getSlot();
+    try {
+      await program.methods
+        .initializeConfig(
+          new anchor.BN(LOTTERY_ID),
+          new anchor.BN(0),
+          new anchor.BN(slot + 10),
+          new anchor.BN(TICKET_PRICE + ROUND_PRICE_TO / 2),
+          configParams()
+        )
+        .rpc();
+      assert.fail("unrounded price should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "PriceNotRounded");
+    }
+  });
+
+  it("Rejects a past start when a future start is required", async () => {
+    const slot = await connection.getSlot();
+    try {
+      await program.methods
+        .initializeConfig(
+          new anchor.BN(LOTTERY_ID),
+          new anchor.BN(slot - 1),
+          new anchor.BN(slot + 10),
+          new anchor.BN(TICKET_PRICE),
+          configParams({ requireFutureStart: true })
+        )
+        .rpc();
+      assert.fail("past start should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "StartNotInFuture");
+    }
+  });
+
   it("Is initialized!", async () => {
+    // Treasuries must be rent-exempt before they can receive small fee transfers.
+    for (const { keypair } of treasuries) {
+      const airdropSig = await connection.requestAirdrop(
+        keypair.publicKey,
+        anchor.web3.LAMPORTS_PER_SOL
+      );
+      await connection.confirmTransaction(airdropSig);
+    }
+
     const slot = await connection.getSlot();
     console.log("Current slot", slot);
 
-    const mint = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("collection_mint")],
-      program.programId
-    )[0];
+    const mint = collectionMintAddress;
 
     const metadata = anchor.web3.PublicKey.findProgramAddressSync(
       [
@@ -98,49 +325,3164 @@ describe("token-lottery", () => {
 
     const initConfigIx = await program.methods
       .initializeConfig(
+        new anchor.BN(LOTTERY_ID),
         new anchor.BN(0),
         new anchor.BN(slot + 10),
-        new anchor.BN(10000)
+        new anchor.BN(TICKET_PRICE),
+        // The lottery's own collection already delegates to its
+        // `collection_authority`, so it stands in for a badge collection.
+        configParams({ winnerBadgeCollection: collectionMintAddress })
       )
       .instruction();
 
     const initLotteryIx = await program.methods
       .initializeLottery()
       .accounts({
+        tokenLottery: tokenLotteryAddress,
+        masterEdition: masterEdition,
+        metadata: metadata,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .instruction();
+
+    const blockhashContext = await connection.getLatestBlockhash();
+
+    const tx = new anchor.web3.Transaction({
+      blockhash: blockhashContext.blockhash,
+      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
+      feePayer: wallet.payer.publicKey,
+    })
+      .add(initConfigIx)
+      .add(initLotteryIx);
+
+    const sig = await anchor.web3.sendAndConfirmTransaction(connection, tx, [
+      wallet.payer,
+    ]);
+    console.log(sig);
+  });
+
+  // Initializes the config and collection of another lottery next to the
+  // main one, ending `duration` slots from now.
+  async function initializeOtherLottery(
+    lotteryId: number,
+    duration = 10,
+    overrides = {}
+  ) {
+    const id = new anchor.BN(lotteryId);
+    const seed = id.toArrayLike(Buffer, "le", 8);
+    const lottery = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("token_lottery"), seed],
+      program.programId
+    )[0];
+    const collectionMint = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_mint"), seed],
+      program.programId
+    )[0];
+    const metadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMint.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const masterEdition = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+    const slot = await connection.getSlot();
+    const initConfigIx = await program.methods
+      .initializeConfig(
+        id,
+        new anchor.BN(0),
+        new anchor.BN(slot + duration),
+        new anchor.BN(TICKET_PRICE),
+        configParams(overrides)
+      )
+      .instruction();
+    const initLotteryIx = await program.methods
+      .initializeLottery()
+      .accounts({
+        tokenLottery: lottery,
         masterEdition: masterEdition,
         metadata: metadata,
         tokenProgram: TOKEN_PROGRAM_ID,
       })
-      .instruction();
+      .instruction();
+    const tx = new anchor.web3.Transaction()
+      .add(initConfigIx)
+      .add(initLotteryIx);
+    await anchor.web3.sendAndConfirmTransaction(connection, tx, [
+      wallet.payer,
+    ]);
+    return { lottery, collectionMint };
+  }
+
+  it("Initializes a second lottery alongside the first", async () => {
+    const { lottery, collectionMint } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID
+    );
+    assert.ok(lottery.equals(otherLotteryAddress));
+
+    const first = await program.account.tokenLottery.fetch(tokenLotteryAddress);
+    const second = await program.account.tokenLottery.fetch(
+      otherLotteryAddress
+    );
+    assert.equal(first.lotteryId.toNumber(), LOTTERY_ID);
+    assert.equal(second.lotteryId.toNumber(), OTHER_LOTTERY_ID);
+    assert.notEqual(
+      collectionMint.toBase58(),
+      collectionMintAddress.toBase58()
+    );
+    const collection = await connection.getAccountInfo(collectionMint);
+    assert.isNotNull(collection);
+  });
+
+  it("Refunds tickets of a cancelled lottery, then closes it", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 1,
+      1000
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    await program.methods
+      .cancelLottery()
+      .accounts({ tokenLottery: lottery })
+      .rpc({ commitment: "confirmed" });
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("ticket bought from a cancelled lottery");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryCancelled");
+    }
+
+    const refund = () =>
+      program.methods
+        .refundTicket(new anchor.BN(0))
+        .accountsPartial({
+          tokenLottery: lottery,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    const cancelled = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(
+      cancelled.lotteryPotAmount.toNumber(),
+      TICKET_PRICE - TICKET_FEE
+    );
+    // The fee already paid to the treasury is covered by the authority
+    assert.equal(cancelled.accruedFees.toNumber(), TICKET_FEE);
+    const close = () =>
+      program.methods
+        .closeLottery()
+        .accountsPartial({ tokenLottery: lottery, vestingRecord: null })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await close();
+      assert.fail("closed a cancelled lottery before its refunds");
+    } catch (err) {
+      assert.include(err.toString(), "PrizeNotClaimed");
+    }
+    const signature = await refund();
+    const refunded = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(refunded.lotteryPotAmount.toNumber(), 0);
+    assert.equal(refunded.accruedFees.toNumber(), 0);
+    // The holder, paying the transaction fee, gets the full price back
+    const { meta } = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    assert.equal(
+      meta.postBalances[0] - meta.preBalances[0] + meta.fee,
+      TICKET_PRICE
+    );
+
+    try {
+      await refund();
+      assert.fail("a ticket should only be refunded once");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectTicket");
+    }
+
+    // With every ticket refunded, the cancelled lottery can be closed
+    await close();
+    assert.isNull(await connection.getAccountInfo(lottery, "confirmed"));
+  });
+
+  const REFUND_WINDOW_SLOTS = 20;
+  const REFUND_SPONSOR_AMOUNT = 50000;
+  const REFUND_LOTTERY_ID = OTHER_LOTTERY_ID + 28;
+  const refundLottery = anchor.web3.PublicKey.findProgramAddressSync(
+    [
+      Buffer.from("token_lottery"),
+      new anchor.BN(REFUND_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+    ],
+    program.programId
+  )[0];
+  const refundTicket = (ticketIndex: number) =>
+    program.methods
+      .refundTicket(new anchor.BN(ticketIndex))
+      .accountsPartial({
+        tokenLottery: refundLottery,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" });
+  const sweepRefunds = () =>
+    program.methods
+      .sweepUnclaimedRefunds(PAYMENT_INDEX_SOL)
+      .accountsPartial({
+        tokenLottery: refundLottery,
+        tokenProgram: null,
+        paymentMint: null,
+        vaultPaymentAccount: null,
+        payerPaymentAccount: null,
+      })
+      .rpc({ commitment: "confirmed" });
+
+  it("Refunds a ticket before the refund deadline", async () => {
+    await initializeOtherLottery(REFUND_LOTTERY_ID, 1000, {
+      refundWindowSlots: new anchor.BN(REFUND_WINDOW_SLOTS),
+    });
+    await program.methods
+      .sponsorPot(new anchor.BN(REFUND_SPONSOR_AMOUNT))
+      .accounts({ tokenLottery: refundLottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    await buyTicket(TICKET_PRICE, wallet.payer, refundLottery);
+    await buyTicket(TICKET_PRICE, wallet.payer, refundLottery);
+    await program.methods
+      .cancelLottery()
+      .accounts({ tokenLottery: refundLottery })
+      .rpc({ commitment: "confirmed" });
+    const { refundDeadlineSlot } = await program.account.tokenLottery.fetch(
+      refundLottery
+    );
+    assert.isAbove(refundDeadlineSlot.toNumber(), 0);
+
+    await refundTicket(0);
+    const { lotteryPotAmount } = await program.account.tokenLottery.fetch(
+      refundLottery
+    );
+    assert.equal(
+      lotteryPotAmount.toNumber(),
+      TICKET_PRICE - TICKET_FEE + REFUND_SPONSOR_AMOUNT
+    );
+
+    try {
+      await sweepRefunds();
+      assert.fail("swept refunds before the deadline");
+    } catch (err) {
+      assert.include(err.toString(), "RefundNotExpired");
+    }
+  });
+
+  it("Rejects a refund after the refund deadline", async () => {
+    const { refundDeadlineSlot } = await program.account.tokenLottery.fetch(
+      refundLottery
+    );
+    await waitForSlot(refundDeadlineSlot.toNumber() + 1);
+    try {
+      await refundTicket(1);
+      assert.fail("refunded a ticket after the deadline");
+    } catch (err) {
+      assert.include(err.toString(), "RefundExpired");
+    }
+  });
+
+  it("Sweeps unclaimed refunds to the authority", async () => {
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), refundLottery.toBuffer()],
+      program.programId
+    )[0];
+    const { lotteryPotAmount, accruedFees } =
+      await program.account.tokenLottery.fetch(refundLottery);
+    const vaultBefore = await connection.getBalance(vault, "confirmed");
+    await sweepRefunds();
+
+    // Only ticket revenue and the unrefunded fee cover are swept, the
+    // sponsor's funds stay refundable
+    assert.equal(accruedFees.toNumber(), TICKET_FEE);
+    assert.equal(
+      vaultBefore - (await connection.getBalance(vault, "confirmed")),
+      lotteryPotAmount.toNumber() - REFUND_SPONSOR_AMOUNT + TICKET_FEE
+    );
+    const swept = await program.account.tokenLottery.fetch(refundLottery);
+    assert.equal(swept.lotteryPotAmount.toNumber(), REFUND_SPONSOR_AMOUNT);
+    assert.equal(swept.sponsorTotal.toNumber(), REFUND_SPONSOR_AMOUNT);
+
+    await program.methods
+      .refundSponsorship()
+      .accounts({ tokenLottery: refundLottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    const refunded = await program.account.tokenLottery.fetch(refundLottery);
+    assert.equal(refunded.lotteryPotAmount.toNumber(), 0);
+
+    await program.methods
+      .closeLottery()
+      .accountsPartial({ tokenLottery: refundLottery, vestingRecord: null })
+      .rpc({ commitment: "confirmed" });
+    assert.isNull(await connection.getAccountInfo(refundLottery, "confirmed"));
+  });
+
+  it("Merges the pots of two cancelled lotteries into a third", async () => {
+    const MERGE_WINDOW_SLOTS = 2;
+    const MERGE_SPONSOR_AMOUNT = 50000;
+    const vaultOf = (lottery: anchor.web3.PublicKey) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("vault"), lottery.toBuffer()],
+        program.programId
+      )[0];
+    const sources = [];
+    for (const offset of [34, 35]) {
+      const { lottery } = await initializeOtherLottery(
+        OTHER_LOTTERY_ID + offset,
+        1000,
+        { refundWindowSlots: new anchor.BN(MERGE_WINDOW_SLOTS) }
+      );
+      await program.methods
+        .sponsorPot(new anchor.BN(MERGE_SPONSOR_AMOUNT))
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      await program.methods
+        .cancelLottery()
+        .accounts({ tokenLottery: lottery })
+        .rpc({ commitment: "confirmed" });
+      sources.push(lottery);
+    }
+    const { lottery: target } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 36,
+      1000
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, target);
+
+    const mergeAccounts = (lotteries: anchor.web3.PublicKey[]) =>
+      lotteries.flatMap((lottery) =>
+        [lottery, vaultOf(lottery)].map((pubkey) => ({
+          pubkey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      );
+    const mergePots = (lotteries: anchor.web3.PublicKey[]) =>
+      program.methods
+        .mergePots()
+        .accounts({ tokenLottery: target })
+        .remainingAccounts(mergeAccounts(lotteries))
+        .rpc({ commitment: "confirmed" });
+    try {
+      await program.methods
+        .mergePots()
+        .accounts({ tokenLottery: target })
+        .remainingAccounts(mergeAccounts(sources).slice(0, 3))
+        .rpc({ commitment: "confirmed" });
+      assert.fail("merged a lottery without its vault");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectMergeAccounts");
+    }
+    try {
+      await mergePots(sources);
+      assert.fail("merged pots that could still be refunded");
+    } catch (err) {
+      assert.include(err.toString(), "RefundNotExpired");
+    }
+    try {
+      await mergePots([target]);
+      assert.fail("merged a lottery into itself");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectMergeAccounts");
+    }
+
+    const { refundDeadlineSlot } = await program.account.tokenLottery.fetch(
+      sources[1]
+    );
+    await waitForSlot(refundDeadlineSlot.toNumber() + 1);
+    const targetBefore = await program.account.tokenLottery.fetch(target);
+    const vaultBefore = await connection.getBalance(
+      vaultOf(target),
+      "confirmed"
+    );
+    await mergePots(sources);
+
+    const merged = await program.account.tokenLottery.fetch(target);
+    assert.equal(
+      merged.lotteryPotAmount.toNumber(),
+      targetBefore.lotteryPotAmount.toNumber() +
+        2 * (TICKET_PRICE - TICKET_FEE)
+    );
+    assert.equal(
+      (await connection.getBalance(vaultOf(target), "confirmed")) -
+        vaultBefore,
+      2 * (TICKET_PRICE - TICKET_FEE)
+    );
+    // Sponsor funds aren't merged, they stay refundable to the sponsor
+    for (const source of sources) {
+      const { lotteryPotAmount } = await program.account.tokenLottery.fetch(
+        source
+      );
+      assert.equal(lotteryPotAmount.toNumber(), MERGE_SPONSOR_AMOUNT);
+
+      await program.methods
+        .refundSponsorship()
+        .accounts({ tokenLottery: source, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+      const refunded = await program.account.tokenLottery.fetch(source);
+      assert.equal(refunded.lotteryPotAmount.toNumber(), 0);
+      assert.equal(refunded.sponsorTotal.toNumber(), 0);
+    }
+  });
+
+  it("Returns ticket and sponsor funds separately on cancellation", async () => {
+    const SPONSOR_AMOUNT = anchor.web3.LAMPORTS_PER_SOL / 100;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 29,
+      1000
+    );
+    const sponsor = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    await program.methods
+      .sponsorPot(new anchor.BN(SPONSOR_AMOUNT))
+      .accounts({ tokenLottery: lottery, payer: sponsor.publicKey })
+      .signers([sponsor])
+      .rpc({ commitment: "confirmed" });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    await program.methods
+      .cancelLottery()
+      .accounts({ tokenLottery: lottery })
+      .rpc({ commitment: "confirmed" });
+
+    // The ticket refund leaves the sponsor's funds in the pot
+    await program.methods
+      .refundTicket(new anchor.BN(0))
+      .accountsPartial({
+        tokenLottery: lottery,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc({ commitment: "confirmed" });
+    const refunded = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(refunded.lotteryPotAmount.toNumber(), SPONSOR_AMOUNT);
+    assert.equal(refunded.sponsorTotal.toNumber(), SPONSOR_AMOUNT);
+
+    const refundSponsorship = () =>
+      program.methods
+        .refundSponsorship()
+        .accounts({ tokenLottery: lottery, payer: sponsor.publicKey })
+        .signers([sponsor])
+        .rpc({ commitment: "confirmed" });
+    const sponsorBefore = await connection.getBalance(
+      sponsor.publicKey,
+      "confirmed"
+    );
+    await refundSponsorship();
+    const returned = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(returned.lotteryPotAmount.toNumber(), 0);
+    assert.equal(returned.sponsorTotal.toNumber(), 0);
+    // The sponsor also gets the rent of the closed record back
+    assert.isAtLeast(
+      (await connection.getBalance(sponsor.publicKey, "confirmed")) -
+        sponsorBefore,
+      SPONSOR_AMOUNT
+    );
+
+    try {
+      await refundSponsorship();
+      assert.fail("a sponsorship should only be returned once");
+    } catch (err) {
+      assert.include(err.toString(), "AccountNotInitialized");
+    }
+  });
+
+  it("Stops selling tickets at the maximum supply", async () => {
+    const MAX_TICKETS = 2;
+    const { lottery } = await initializeOtherLottery(OTHER_LOTTERY_ID + 7, 1000, {
+      maxTickets: new anchor.BN(MAX_TICKETS),
+    });
+    for (let i = 0; i < MAX_TICKETS; i++) {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    }
+
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("ticket bought past the maximum supply");
+    } catch (err) {
+      assert.include(err.toString(), "SoldOut");
+    }
+    const { ticketNum } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(ticketNum.toNumber(), MAX_TICKETS);
+  });
+
+  it("Limits the tickets a single wallet can buy", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 8,
+      1000,
+      { maxTicketsPerWallet: new anchor.BN(1) }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("wallet bought past its ticket limit");
+    } catch (err) {
+      assert.include(err.toString(), "WalletLimitExceeded");
+    }
+
+    const otherBuyer = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    await buyTicket(TICKET_PRICE, otherBuyer, lottery);
+    const buyerRecord = await program.account.buyerRecord.fetch(
+      buyerRecordAddress(lottery, wallet.publicKey)
+    );
+    assert.equal(buyerRecord.tickets.toNumber(), 1);
+  });
+
+  it("Caps the lamports a single wallet can spend", async () => {
+    // Room for two tickets and half of a third
+    const MAX_SPEND = 2 * TICKET_PRICE + TICKET_PRICE / 2;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 9,
+      1000,
+      { maxSpendPerWallet: new anchor.BN(MAX_SPEND) }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("wallet spent past its cap");
+    } catch (err) {
+      assert.include(err.toString(), "WalletSpendCapExceeded");
+    }
+
+    const buyerRecord = await program.account.buyerRecord.fetch(
+      buyerRecordAddress(lottery, wallet.publicKey)
+    );
+    assert.equal(buyerRecord.spent.toNumber(), 2 * TICKET_PRICE);
+    const { ticketNum } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(ticketNum.toNumber(), 2);
+  });
+
+  it("Reconciles sales, fees and sponsor funds with the vault", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 3,
+      1000
+    );
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    await program.methods
+      .sponsorPot(new anchor.BN(MIN_SPONSOR_AMOUNT))
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const accounts = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(accounts.grossSales.toNumber(), 2 * TICKET_PRICE);
+    assert.equal(accounts.totalFees.toNumber(), 2 * TICKET_FEE);
+    assert.equal(accounts.sponsorTotal.toNumber(), MIN_SPONSOR_AMOUNT);
+    assert.equal(accounts.instantPayouts.toNumber(), 0);
+    assert.equal(
+      accounts.lotteryPotAmount.toNumber(),
+      accounts.grossSales.toNumber() -
+        accounts.totalFees.toNumber() +
+        accounts.sponsorTotal.toNumber() -
+        accounts.instantPayouts.toNumber()
+    );
+
+    const vaultInfo = await connection.getAccountInfo(vault);
+    const rent = await connection.getMinimumBalanceForRentExemption(
+      vaultInfo.data.length
+    );
+    assert.equal(
+      vaultInfo.lamports - rent,
+      accounts.lotteryPotAmount.toNumber() + accounts.accruedFees.toNumber()
+    );
+  });
+
+  it("Tracks instant prizes paid out of the pot", async () => {
+    const INSTANT_WIN_PRIZE = 1000;
+    // Every ticket wins
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 40,
+      1000,
+      {
+        instantWinBps: 10000,
+        instantWinPrize: new anchor.BN(INSTANT_WIN_PRIZE),
+      }
+    );
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+
+    for (let i = 0; i < 2; i++) {
+      const signature = await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      const instantWin = (await getEvents(signature)).find(
+        (event) => event.name === "instantWin"
+      );
+      assert.equal(instantWin.data.ticketNum.toNumber(), i);
+      assert.equal(instantWin.data.amount.toNumber(), INSTANT_WIN_PRIZE);
+    }
+
+    const accounts = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(accounts.instantPayouts.toNumber(), 2 * INSTANT_WIN_PRIZE);
+    assert.equal(
+      accounts.lotteryPotAmount.toNumber(),
+      2 * (TICKET_PRICE - TICKET_FEE - INSTANT_WIN_PRIZE)
+    );
+    assert.equal(
+      accounts.lotteryPotAmount.toNumber(),
+      accounts.grossSales.toNumber() -
+        accounts.totalFees.toNumber() +
+        accounts.sponsorTotal.toNumber() -
+        accounts.instantPayouts.toNumber()
+    );
+
+    const vaultInfo = await connection.getAccountInfo(vault);
+    const rent = await connection.getMinimumBalanceForRentExemption(
+      vaultInfo.data.length
+    );
+    assert.equal(
+      vaultInfo.lamports - rent,
+      accounts.lotteryPotAmount.toNumber() + accounts.accruedFees.toNumber()
+    );
+  });
+
+  it("Hands the authority over in two steps", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 13,
+      1000
+    );
+    const newAuthority = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    const transferAuthority = (key: anchor.web3.PublicKey) =>
+      program.methods
+        .transferAuthority(key)
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+    const acceptAuthority = (signer: anchor.web3.Keypair) =>
+      program.methods
+        .acceptAuthority()
+        .accounts({ tokenLottery: lottery, payer: signer.publicKey })
+        .signers([signer])
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await transferAuthority(anchor.web3.PublicKey.default);
+      assert.fail("authority handed to the default key");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidNewAuthority");
+    }
+
+    await transferAuthority(newAuthority.publicKey);
+    try {
+      await acceptAuthority(wallet.payer);
+      assert.fail("handoff accepted by the wrong key");
+    } catch (err) {
+      assert.include(err.toString(), "NotPendingAuthority");
+    }
+    let config = await program.account.tokenLottery.fetch(lottery);
+    assert.ok(config.authority.equals(wallet.publicKey));
+
+    await acceptAuthority(newAuthority);
+    config = await program.account.tokenLottery.fetch(lottery);
+    assert.ok(config.authority.equals(newAuthority.publicKey));
+    assert.ok(config.pendingAuthority.equals(anchor.web3.PublicKey.default));
+
+    try {
+      await program.methods
+        .pauseLottery()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc();
+      assert.fail("previous authority paused the lottery");
+    } catch (err) {
+      assert.include(err.toString(), "NotAuthorized");
+    }
+  });
+
+  it("Skips events when they are disabled", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 12,
+      1000,
+      { emitEvents: false }
+    );
+    const buySig = await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    assert.isEmpty(await getEvents(buySig));
+    const { ticketNum } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(ticketNum.toNumber(), 1);
+  });
+
+  it("Only tops up a vault that is not rent-exempt", async () => {
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), tokenLotteryAddress.toBuffer()],
+      program.programId
+    )[0];
+    const vaultInfo = await connection.getAccountInfo(vault);
+    const rent = await connection.getMinimumBalanceForRentExemption(
+      vaultInfo.data.length
+    );
+    assert.isAtLeast(vaultInfo.lamports, rent);
+
+    // Payouts leave the rent in place, so a live vault is never short of it
+    try {
+      await program.methods
+        .topUpVaultRent()
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      assert.fail("a rent-exempt vault was topped up");
+    } catch (err) {
+      assert.include(err.toString(), "VaultRentExempt");
+    }
+  });
+
+  it("Migrates a legacy pot into the vault", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 10,
+      1000
+    );
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const migrate = () =>
+      program.methods
+        .migrateToVault()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await migrate();
+      assert.fail("a lottery with a vault has nothing to migrate");
+    } catch (err) {
+      assert.include(err.toString(), "NothingToMigrate");
+    }
+
+    // New lotteries never hold the pot themselves, so fund the data account
+    // with it to stand in for one created before the vault.
+    const { lotteryPotAmount } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: lottery,
+          lamports: lotteryPotAmount.toNumber(),
+        })
+      ),
+      [wallet.payer]
+    );
+    const vaultBefore = await connection.getBalance(vault);
+    await migrate();
+
+    assert.equal(
+      await connection.getBalance(vault),
+      vaultBefore + lotteryPotAmount.toNumber()
+    );
+    const lotteryInfo = await connection.getAccountInfo(lottery);
+    assert.equal(
+      lotteryInfo.lamports,
+      await connection.getMinimumBalanceForRentExemption(
+        lotteryInfo.data.length
+      )
+    );
+  });
+
+  it("Is buying tickets!", async () => {
+    const buySig = await buyTicket();
+    const purchase = (await getEvents(buySig)).find(
+      (event) => event.name === "ticketPurchased"
+    );
+    assert.ok(purchase.data.buyer.equals(wallet.publicKey));
+    assert.equal(purchase.data.ticketNum.toNumber(), 0);
+    assert.equal(purchase.data.price.toNumber(), TICKET_PRICE);
+    assert.equal(purchase.data.paymentIndex, PAYMENT_INDEX_SOL);
+    assert.equal(purchase.data.decimals, 9);
+
+    await buyTicket();
+    await buyTicket();
+    await buyTicket();
+    await buyTicket();
+  });
+
+  it("Rejects buyers below the minimum balance", async () => {
+    const buyer = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL / 10);
+    try {
+      await buyTicket(TICKET_PRICE, buyer);
+      assert.fail("underfunded buyer should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "BuyerBalanceTooLow");
+    }
+  });
+
+  function decodeMetadataCreators(data: Buffer) {
+    // Metadata: key, update_authority, mint, name, symbol, uri,
+    // seller_fee_basis_points, creators: Option<Vec<Creator>>
+    let offset = 1 + 32 + 32;
+    for (let i = 0; i < 3; i++) {
+      offset += 4 + data.readUInt32LE(offset);
+    }
+    offset += 2;
+    if (data.readUInt8(offset) === 0) {
+      return [];
+    }
+    offset += 1;
+    const count = data.readUInt32LE(offset);
+    offset += 4;
+    const creators = [];
+    for (let i = 0; i < count; i++) {
+      creators.push({
+        address: new anchor.web3.PublicKey(data.subarray(offset, offset + 32)),
+        verified: data.readUInt8(offset + 32) === 1,
+      });
+      offset += 34;
+    }
+    return creators;
+  }
+
+  it("Verifies the collection creator on init", async () => {
+    const collectionMetadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMintAddress.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const { data } = await connection.getAccountInfo(collectionMetadata);
+
+    const creators = decodeMetadataCreators(data);
+    assert.equal(creators.length, 1);
+    assert.ok(creators[0].address.equals(collectionMintAddress));
+    assert.isTrue(creators[0].verified);
+  });
+
+  const collectionAuthorityAddress =
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("collection_authority"), lotteryIdSeed],
+      program.programId
+    )[0];
+
+  const collectionAuthorityRecordAddress =
+    anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMintAddress.toBuffer(),
+        Buffer.from("collection_authority"),
+        collectionAuthorityAddress.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+  function decodeMetadataCollection(data: Buffer) {
+    // Metadata: ..., creators, primary_sale_happened, is_mutable,
+    // edition_nonce: Option<u8>, token_standard: Option<u8>,
+    // collection: Option<Collection { verified, key }>
+    let offset = 1 + 32 + 32;
+    for (let i = 0; i < 3; i++) {
+      offset += 4 + data.readUInt32LE(offset);
+    }
+    offset += 2;
+    if (data.readUInt8(offset) === 1) {
+      offset += 1;
+      offset += 4 + data.readUInt32LE(offset) * 34;
+    } else {
+      offset += 1;
+    }
+    offset += 2;
+    for (let i = 0; i < 2; i++) {
+      offset += data.readUInt8(offset) === 1 ? 2 : 1;
+    }
+    if (data.readUInt8(offset) === 0) {
+      return null;
+    }
+    return {
+      verified: data.readUInt8(offset + 1) === 1,
+      key: new anchor.web3.PublicKey(data.subarray(offset + 2, offset + 34)),
+    };
+  }
+
+  it("Verifies tickets through the delegated collection authority", async () => {
+    const record = await connection.getAccountInfo(
+      collectionAuthorityRecordAddress
+    );
+    assert.isNotNull(record, "collection authority record should exist");
+    assert.ok(record.owner.equals(TOKEN_METADATA_PROGRAM_ID));
+
+    const ticketMetadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        ticketMintAddress(0, 0).toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const { data } = await connection.getAccountInfo(ticketMetadata);
+    const collection = decodeMetadataCollection(data);
+    assert.isNotNull(collection);
+    assert.ok(collection.key.equals(collectionMintAddress));
+    assert.isTrue(collection.verified);
+  });
+
+  it("Estimates the rent charged for initialization", async () => {
+    const estimate = await program.methods
+      .estimateInitializationRent()
+      .view();
+
+    const collectionTokenAccount =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("collection_token_account"), lotteryIdSeed],
+        program.programId
+      )[0];
+    const collectionMetadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMintAddress.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const collectionMasterEdition =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          collectionMintAddress.toBuffer(),
+          Buffer.from("edition"),
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+
+    let actual = 0;
+    for (const address of [
+      tokenLotteryAddress,
+      lotteryVaultAddress,
+      collectionMintAddress,
+      collectionTokenAccount,
+      collectionMetadata,
+      collectionMasterEdition,
+      collectionAuthorityRecordAddress,
+    ]) {
+      const { data } = await connection.getAccountInfo(address);
+      actual += await connection.getMinimumBalanceForRentExemption(
+        data.length
+      );
+    }
+    assert.equal(estimate.toNumber(), actual);
+  });
+
+  it("Mints tickets as non-printable 1/1 editions", async () => {
+    const ticketMint = ticketMintAddress(0, 0);
+    const ticketMasterEdition = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        ticketMint.toBuffer(),
+        Buffer.from("edition"),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+
+    // MasterEditionV2: key (u8), supply (u64), max_supply (Option<u64>)
+    const { data } = await connection.getAccountInfo(ticketMasterEdition);
+    assert.equal(data.readUInt8(9), 1, "max_supply should be set");
+    assert.equal(data.readBigUInt64LE(10), BigInt(0));
+
+    const mintInfo = await connection.getParsedAccountInfo(ticketMint);
+    const parsedMint = (mintInfo.value.data as anchor.web3.ParsedAccountData)
+      .parsed.info;
+    assert.equal(parsedMint.supply, "1");
+    assert.equal(parsedMint.mintAuthority, ticketMasterEdition.toBase58());
+  });
+
+  it("Creates tickets under the collection mint's authority", async () => {
+    const mintInfo = await connection.getParsedAccountInfo(
+      ticketMintAddress(0, 0)
+    );
+    const parsedMint = (mintInfo.value.data as anchor.web3.ParsedAccountData)
+      .parsed.info;
+    // The mint authority moves to the master edition once the edition is
+    // created; the freeze authority stays with the collection mint.
+    assert.equal(parsedMint.freezeAuthority, collectionMintAddress.toBase58());
+  });
+
+  it("Returns the overpayment on a ticket purchase", async () => {
+    const vaultBalanceBefore = await connection.getBalance(
+      lotteryVaultAddress
+    );
+    const stateBalanceBefore = await connection.getBalance(
+      tokenLotteryAddress
+    );
+
+    const signature = await buyTicket(TICKET_PRICE + 5000);
+
+    const vaultBalanceAfter = await connection.getBalance(lotteryVaultAddress);
+    assert.equal(
+      vaultBalanceAfter - vaultBalanceBefore,
+      TICKET_PRICE - TICKET_FEE
+    );
+    // The state account only ever holds its rent
+    assert.equal(
+      await connection.getBalance(tokenLotteryAddress),
+      stateBalanceBefore
+    );
+
+    // The payer, at index 0 as fee payer, pays the price, the transaction
+    // fee and the rent of the ticket accounts: the 5000 on top comes back
+    const { meta } = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    assert.include(
+      meta.logMessages,
+      "Program log: Returned overpayment of 5000"
+    );
+    const rent = meta.postBalances.reduce(
+      (sum, balance, index) =>
+        meta.preBalances[index] === 0 ? sum + balance : sum,
+      0
+    );
+    assert.equal(
+      meta.preBalances[0] - meta.postBalances[0],
+      TICKET_PRICE + meta.fee + rent
+    );
+  });
+
+  it("Splits the ticket fee between treasuries", async () => {
+    const balancesBefore = await Promise.all(
+      treasuries.map(({ keypair }) => connection.getBalance(keypair.publicKey))
+    );
+    const potBefore = (
+      await program.account.tokenLottery.fetch(tokenLotteryAddress)
+    ).lotteryPotAmount;
+
+    await buyTicket();
+
+    const balancesAfter = await Promise.all(
+      treasuries.map(({ keypair }) => connection.getBalance(keypair.publicKey))
+    );
+    assert.equal(balancesAfter[0] - balancesBefore[0], (TICKET_FEE * 60) / 100);
+    assert.equal(balancesAfter[1] - balancesBefore[1], (TICKET_FEE * 40) / 100);
+
+    // The pot and the fee add up to the gross ticket price
+    const potAfter = (
+      await program.account.tokenLottery.fetch(tokenLotteryAddress)
+    ).lotteryPotAmount;
+    const feePaid = balancesAfter.reduce(
+      (total, balance, index) => total + balance - balancesBefore[index],
+      0
+    );
+    assert.equal(potAfter.sub(potBefore).toNumber() + feePaid, TICKET_PRICE);
+  });
+
+  it("Counts a ticket once after the minting CPIs", async () => {
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+
+    await buyTicket();
+
+    const after = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(after.ticketNum.toNumber(), before.ticketNum.toNumber() + 1);
+    assert.ok(
+      after.grossSales.sub(before.grossSales).eq(new anchor.BN(TICKET_PRICE))
+    );
+    const ticketAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(
+        ticketMintAddress(before.roundNumber, before.ticketNum),
+        wallet.publicKey
+      )
+    );
+    assert.equal(Number(ticketAccount.amount), 1);
+  });
+
+  // Token accounts of the vault holding each payment token's pot
+  const vaultPaymentAccounts: anchor.web3.PublicKey[] = [];
+
+  it("Buys tickets with two accepted payment tokens", async () => {
+    for (const [index, { mint, price }] of paymentTokens.entries()) {
+      await createMint(
+        connection,
+        wallet.payer,
+        wallet.publicKey,
+        null,
+        6,
+        mint
+      );
+      const payerAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        mint.publicKey,
+        wallet.publicKey
+      );
+      await mintTo(
+        connection,
+        wallet.payer,
+        mint.publicKey,
+        payerAccount.address,
+        wallet.payer,
+        price
+      );
+      const vaultAccount = await getOrCreateAssociatedTokenAccount(
+        connection,
+        wallet.payer,
+        mint.publicKey,
+        lotteryVaultAddress,
+        true
+      );
+      vaultPaymentAccounts.push(vaultAccount.address);
+
+      const buyIx = await program.methods
+        .buyTicket(new anchor.BN(price), index + 1)
+        .accountsPartial({
+          tokenLottery: tokenLotteryAddress,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          paymentMint: mint.publicKey,
+          payerPaymentAccount: payerAccount.address,
+          vaultPaymentAccount: vaultAccount.address,
+        })
+        .instruction();
+      const computeIx = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+        units: 300000,
+      });
+      const buySig = await anchor.web3.sendAndConfirmTransaction(
+        connection,
+        new anchor.web3.Transaction().add(buyIx).add(computeIx),
+        [wallet.payer]
+      );
+      // Token prices are in base units of the 6 decimal mints
+      const purchase = (await getEvents(buySig)).find(
+        (event) => event.name === "ticketPurchased"
+      );
+      assert.equal(purchase.data.price.toNumber(), price);
+      assert.equal(purchase.data.paymentIndex, index + 1);
+      assert.equal(purchase.data.decimals, 6);
+
+      const vaultBalance = await getAccount(connection, vaultAccount.address);
+      assert.equal(Number(vaultBalance.amount), price);
+      const payerBalance = await getAccount(connection, payerAccount.address);
+      assert.equal(Number(payerBalance.amount), 0);
+    }
+
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.deepEqual(
+      lotteryConfig.paymentTokenPots.map((pot) => pot.toNumber()),
+      paymentTokens.map(({ price }) => price)
+    );
+    assert.deepEqual(lotteryConfig.paymentTokenDecimals, [6, 6]);
+  });
+
+  async function ticketUri(round: anchor.BN, index: anchor.BN) {
+    const metadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        ticketMintAddress(round, index).toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const { data } = await connection.getAccountInfo(metadata);
+    // Metadata: key, update_authority, mint, name, symbol, uri
+    let offset = 1 + 32 + 32;
+    for (let i = 0; i < 2; i++) {
+      offset += 4 + data.readUInt32LE(offset);
+    }
+    const length = data.readUInt32LE(offset);
+    return data
+      .subarray(offset + 4, offset + 4 + length)
+      .toString()
+      .replace(/\0/g, "");
+  }
+
+  it("Rotates the URI for tickets minted afterwards", async () => {
+    const NEW_URI = "Token Lottery v2";
+    const { roundNumber, ticketNum: oldTicket } =
+      await program.account.tokenLottery.fetch(tokenLotteryAddress);
+    await buyTicket();
+
+    await program.methods
+      .updateConfig({ uri: NEW_URI })
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+    const newTicket = oldTicket.addn(1);
+    await buyTicket();
+
+    const oldUri = await ticketUri(roundNumber, oldTicket);
+    const newUri = await ticketUri(roundNumber, newTicket);
+    assert.notEqual(oldUri, newUri);
+    assert.equal(newUri, NEW_URI);
+
+    try {
+      await program.methods
+        .updateConfig({ uri: "x".repeat(201) })
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      assert.fail("over-long URI should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "UriTooLong");
+    }
+  });
+
+  it("Derives the next ticket mint from the current counter", async () => {
+    const nextTicketNum = await program.methods
+      .nextTicketNum()
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .view();
+    const { roundNumber } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const nextMint = ticketMintAddress(roundNumber, nextTicketNum);
+    assert.isNull(await connection.getAccountInfo(nextMint));
+
+    await buyTicket();
+    assert.isNotNull(await connection.getAccountInfo(nextMint));
+  });
+
+  it("Stops and restarts sales with pause and resume", async () => {
+    await program.methods
+      .pauseLottery()
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+    try {
+      await buyTicket();
+      assert.fail("ticket bought while paused");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryPaused");
+    }
+
+    await program.methods
+      .resumeLottery()
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+    const { ticketNum } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await buyTicket();
+    const resumed = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.isFalse(resumed.paused);
+    assert.equal(resumed.ticketNum.toNumber(), ticketNum.toNumber() + 1);
+  });
+
+  it("Only changes the end slot and price before tickets are sold", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 11,
+      1000
+    );
+    const updateConfig = (update: {
+      lotteryEnd?: anchor.BN;
+      price?: anchor.BN;
+    }) =>
+      program.methods
+        .updateConfig({ uri: null, lotteryEnd: null, price: null, ...update })
+        .accounts({ tokenLottery: lottery })
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await updateConfig({ lotteryEnd: new anchor.BN(0) });
+      assert.fail("lottery end moved into the past");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidConfigUpdate");
+    }
+
+    const newEnd = (await connection.getSlot()) + 2000;
+    await updateConfig({
+      lotteryEnd: new anchor.BN(newEnd),
+      price: new anchor.BN(2 * TICKET_PRICE),
+    });
+    const updated = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(updated.lotteryEnd.toNumber(), newEnd);
+    assert.equal(updated.price.toNumber(), 2 * TICKET_PRICE);
+
+    await buyTicket(2 * TICKET_PRICE, wallet.payer, lottery);
+    try {
+      await updateConfig({ price: new anchor.BN(TICKET_PRICE) });
+      assert.fail("price changed after a ticket was sold");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidConfigUpdate");
+    }
+  });
+
+  it("Schedules a lottery by UNIX timestamp", async () => {
+    // The end is set as a slot number, long past as a timestamp
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 20,
+      1000,
+      { timingMode: { unixTimestamp: {} } }
+    );
+    const config = await program.account.tokenLottery.fetch(lottery);
+    assert.deepEqual(config.timingMode, { unixTimestamp: {} });
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("sales should be closed by the timestamp");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryNotOpen");
+    }
+
+    const now = await connection.getBlockTime(await connection.getSlot());
+    await program.methods
+      .updateConfig({
+        uri: null,
+        lotteryEnd: new anchor.BN(now + 600),
+        price: null,
+      })
+      .accounts({ tokenLottery: lottery })
+      .rpc({ commitment: "confirmed" });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const { ticketNum } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(ticketNum.toNumber(), 1);
+  });
+
+  it("Brands each lottery with its own name, symbol and URI", async () => {
+    const BRANDED_LOTTERY_ID = OTHER_LOTTERY_ID + 22;
+    const branding = {
+      name: "Summer Draw #",
+      symbol: "SUMMER",
+      uri: "https://example.com/summer.json",
+    };
+    try {
+      await initializeOtherLottery(BRANDED_LOTTERY_ID, 1000, {
+        ...branding,
+        name: "x".repeat(23),
+      });
+      assert.fail("a name without room for the ticket number was accepted");
+    } catch (err) {
+      assert.include(err.toString(), "NameTooLong");
+    }
+    try {
+      await initializeOtherLottery(BRANDED_LOTTERY_ID, 1000, {
+        ...branding,
+        uri: "x".repeat(201),
+      });
+      assert.fail("a URI over the Metaplex limit was accepted");
+    } catch (err) {
+      assert.include(err.toString(), "UriTooLong");
+    }
+
+    const { lottery, collectionMint } = await initializeOtherLottery(
+      BRANDED_LOTTERY_ID,
+      1000,
+      branding
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const ticketMint = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        new anchor.BN(BRANDED_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    assert.deepEqual(await metadataStrings(collectionMint), [
+      branding.name,
+      branding.symbol,
+      branding.uri,
+    ]);
+    assert.deepEqual(await metadataStrings(ticketMint), [
+      "Summer Draw #0",
+      branding.symbol,
+      branding.uri,
+    ]);
+  });
+
+  it("Gives each ticket its own URI under a base URI", async () => {
+    const LOTTERY = OTHER_LOTTERY_ID + 24;
+    const baseUri = "https://example.com/tickets";
+    try {
+      await initializeOtherLottery(LOTTERY, 1000, {
+        baseUri: "x".repeat(189),
+      });
+      assert.fail("a base URI without room for the ticket number was accepted");
+    } catch (err) {
+      assert.include(err.toString(), "UriTooLong");
+    }
+
+    const { lottery } = await initializeOtherLottery(LOTTERY, 1000, {
+      baseUri,
+    });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    for (const ticket of [0, 1]) {
+      const ticketMint = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          new anchor.BN(LOTTERY).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(ticket).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+      const [, symbol, uri] = await metadataStrings(ticketMint);
+      assert.equal(symbol, "TICKET");
+      assert.equal(uri, `${baseUri}/${ticket}.json`);
+    }
+  });
+
+  it("Only withdraws fees in deferred fee mode", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(lotteryConfig.feeMode, FEE_MODE_IMMEDIATE);
+    assert.equal(lotteryConfig.accruedFees.toNumber(), 0);
+
+    try {
+      await program.methods
+        .withdrawFees()
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .remainingAccounts(
+          treasuries.map(({ keypair }) => ({
+            pubkey: keypair.publicKey,
+            isWritable: true,
+            isSigner: false,
+          }))
+        )
+        .rpc();
+      assert.fail("immediate fees should not be withdrawable");
+    } catch (err) {
+      assert.include(err.toString(), "FeesNotDeferred");
+    }
+  });
+
+  it("Buys a ticket through CPI with a PDA payer", async () => {
+    const cpiBuyer = anchor.workspace.CpiBuyer as Program<CpiBuyer>;
+    const buyerPda = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("buyer")],
+      cpiBuyer.programId
+    )[0];
+    const airdropSig = await connection.requestAirdrop(
+      buyerPda,
+      anchor.web3.LAMPORTS_PER_SOL
+    );
+    await connection.confirmTransaction(airdropSig);
+
+    const { roundNumber, ticketNum } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const ticketMint = ticketMintAddress(roundNumber, ticketNum);
+    const destination = getAssociatedTokenAddressSync(
+      ticketMint,
+      buyerPda,
+      true
+    );
+    const metadataAddress = (
+      mint: anchor.web3.PublicKey,
+      suffix: Buffer[] = []
+    ) =>
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("metadata"),
+          TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+          mint.toBuffer(),
+          ...suffix,
+        ],
+        TOKEN_METADATA_PROGRAM_ID
+      )[0];
+
+    const buyIx = await cpiBuyer.methods
+      .buyTicket(new anchor.BN(TICKET_PRICE))
+      .accountsPartial({
+        buyer: buyerPda,
+        tokenLottery: tokenLotteryAddress,
+        lotteryVault: lotteryVaultAddress,
+        ticketMint,
+        destination,
+        ticketRecord: ticketRecordAddress(roundNumber, ticketNum),
+        buyerRecord: buyerRecordAddress(tokenLotteryAddress, buyerPda),
+        metadata: metadataAddress(ticketMint),
+        masterEdition: metadataAddress(ticketMint, [Buffer.from("edition")]),
+        collectionMetadata: metadataAddress(collectionMintAddress),
+        collectionMasterEdition: metadataAddress(collectionMintAddress, [
+          Buffer.from("edition"),
+        ]),
+        collectionMint: collectionMintAddress,
+        collectionAuthority: collectionAuthorityAddress,
+        collectionAuthorityRecord: collectionAuthorityRecordAddress,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenMetadataProgram: TOKEN_METADATA_PROGRAM_ID,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        tokenLotteryProgram: program.programId,
+      })
+      .remainingAccounts(
+        treasuries.map(({ keypair }) => ({
+          pubkey: keypair.publicKey,
+          isWritable: true,
+          isSigner: false,
+        }))
+      )
+      .instruction();
+    const computeIx = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+      units: 400000,
+    });
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction().add(computeIx).add(buyIx),
+      [wallet.payer]
+    );
+
+    const ticketRecord = await program.account.ticketRecord.fetch(
+      ticketRecordAddress(roundNumber, ticketNum)
+    );
+    assert.ok(ticketRecord.buyer.equals(buyerPda));
+    const balance = await connection.getTokenAccountBalance(destination);
+    assert.equal(balance.value.amount, "1");
+  });
+
+  it("Tracks sponsor funds apart from ticket revenue", async () => {
+    const SPONSOR_AMOUNT = 50000;
+    const sponsor = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+
+    await program.methods
+      .sponsorPot(new anchor.BN(SPONSOR_AMOUNT))
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        payer: sponsor.publicKey,
+      })
+      .signers([sponsor])
+      .rpc();
+    await buyTicket();
+
+    const after = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(
+      after.sponsorTotal.sub(before.sponsorTotal).toNumber(),
+      SPONSOR_AMOUNT
+    );
+    assert.equal(
+      after.lotteryPotAmount.sub(before.lotteryPotAmount).toNumber(),
+      SPONSOR_AMOUNT + TICKET_PRICE - TICKET_FEE
+    );
+    const ticketRevenueBefore = before.lotteryPotAmount.sub(
+      before.sponsorTotal
+    );
+    const ticketRevenueAfter = after.lotteryPotAmount.sub(after.sponsorTotal);
+    assert.equal(
+      ticketRevenueAfter.sub(ticketRevenueBefore).toNumber(),
+      TICKET_PRICE - TICKET_FEE
+    );
+
+    const sponsorRecordAddress = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("sponsor"),
+        lotteryIdSeed,
+        after.roundNumber.toArrayLike(Buffer, "le", 8),
+        sponsor.publicKey.toBuffer(),
+      ],
+      program.programId
+    )[0];
+    const sponsorRecord = await program.account.sponsorRecord.fetch(
+      sponsorRecordAddress
+    );
+    assert.ok(sponsorRecord.sponsor.equals(sponsor.publicKey));
+    assert.equal(sponsorRecord.amount.toNumber(), SPONSOR_AMOUNT);
+  });
+
+  it("Rejects sponsorships below the minimum", async () => {
+    const sponsor = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    const sponsorPot = (amount: number) =>
+      program.methods
+        .sponsorPot(new anchor.BN(amount))
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          payer: sponsor.publicKey,
+        })
+        .signers([sponsor])
+        .rpc();
+
+    try {
+      await sponsorPot(MIN_SPONSOR_AMOUNT - 1);
+      assert.fail("sponsorship below the minimum should be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "SponsorAmountTooLow");
+    }
+
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await sponsorPot(MIN_SPONSOR_AMOUNT);
+    const after = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(
+      after.sponsorTotal.sub(before.sponsorTotal).toNumber(),
+      MIN_SPONSOR_AMOUNT
+    );
+  });
+
+  async function validateLottery(collectionMint: anchor.web3.PublicKey) {
+    const collectionMetadata = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        collectionMint.toBuffer(),
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+    const collectionTokenAccount =
+      anchor.web3.PublicKey.findProgramAddressSync(
+        [Buffer.from("collection_token_account"), lotteryIdSeed],
+        program.programId
+      )[0];
+
+    const sig = await program.methods
+      .validateLottery()
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        collectionMint,
+        collectionTokenAccount,
+        collectionMetadata,
+        lotteryVault: lotteryVaultAddress,
+      })
+      .rpc({ commitment: "confirmed" });
+    const [report] = await getEvents(sig);
+    assert.equal(report.name, "integrityReport");
+    return report.data.anomalies;
+  }
+
+  it("Reports a healthy lottery as clean", async () => {
+    const anomalies = await validateLottery(collectionMintAddress);
+    assert.deepEqual(anomalies, []);
+  });
+
+  it("Flags a tampered collection mint", async () => {
+    const anomalies = await validateLottery(
+      anchor.web3.Keypair.generate().publicKey
+    );
+    assert.deepInclude(anomalies, { collectionMintAddressMismatch: {} });
+    assert.deepInclude(anomalies, { collectionMintNotInitialized: {} });
+    assert.deepInclude(anomalies, { collectionMetadataMissing: {} });
+  });
+
+  const SWITCHBOARD_QUEUE = new anchor.web3.PublicKey(
+    "A43DyUGA7s8eXPxqEjJY6EBu1KKbNgfxF8h17VAHn13w"
+  );
+  const RANDOMNESS_VALIDITY_SLOTS = 512;
+
+  async function sendSwitchboardTx(
+    ixs: anchor.web3.TransactionInstruction[],
+    signers: anchor.web3.Keypair[] = []
+  ) {
+    const tx = await sb.asV0Tx({
+      connection: connection,
+      ixs,
+      payer: wallet.publicKey,
+      signers: [wallet.payer, ...signers],
+      computeUnitPrice: 75_000,
+      computeUnitLimitMultiple: 1.3,
+    });
+    const blockhashContext = await connection.getLatestBlockhashAndContext();
+    const signature = await connection.sendTransaction(tx);
+    await connection.confirmTransaction({
+      signature,
+      blockhash: blockhashContext.value.blockhash,
+      lastValidBlockHeight: blockhashContext.value.lastValidBlockHeight,
+    });
+    return signature;
+  }
+
+  it("Re-requests randomness that never resolved", async () => {
+    const staleKp = anchor.web3.Keypair.generate();
+    const [stale, createIx] = await sb.Randomness.create(
+      switchboardProgram,
+      staleKp,
+      SWITCHBOARD_QUEUE
+    );
+    await sendSwitchboardTx([createIx], [staleKp]);
+
+    const commitIx = await program.methods
+      .commitAWinner()
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        randomnessAccountData: stale.pubkey,
+      })
+      .instruction();
+    await sendSwitchboardTx([
+      await stale.commitIx(SWITCHBOARD_QUEUE),
+      commitIx,
+    ]);
+
+    const rerequest = () =>
+      program.methods
+        .rerequestRandomness()
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          randomnessAccountData: stale.pubkey,
+        })
+        .rpc();
+
+    try {
+      await rerequest();
+      assert.fail("randomness should still be revealable");
+    } catch (err) {
+      assert.include(err.toString(), "RandomnessStillValid");
+    }
+
+    // Never reveal, so the commit goes stale once the window passes.
+    const staleData = await stale.loadData();
+    await waitForSlot(
+      staleData.seedSlot.toNumber() + RANDOMNESS_VALIDITY_SLOTS + 1
+    );
+    await rerequest();
+
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.ok(
+      lotteryConfig.randomnessAccount.equals(anchor.web3.PublicKey.default)
+    );
+  });
+
+  function ticketRecordAddress(
+    round: number | anchor.BN,
+    index: number | anchor.BN
+  ) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("ticket_record"),
+        lotteryIdSeed,
+        new anchor.BN(round).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function buyerRecordAddress(
+    lottery: anchor.web3.PublicKey,
+    buyer: anchor.web3.PublicKey
+  ) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("buyer"), lottery.toBuffer(), buyer.toBuffer()],
+      program.programId
+    )[0];
+  }
+
+  // The winner isn't known until the reveal, so every ticket record of the
+  // round is passed to the draw.
+  async function ticketRecordAccounts() {
+    const { roundNumber, ticketNum } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    return Array.from({ length: ticketNum.toNumber() }, (_, index) => ({
+      pubkey: ticketRecordAddress(roundNumber, index),
+      isWritable: false,
+      isSigner: false,
+    }));
+  }
+
+  async function drawWinner(
+    lottery = tokenLotteryAddress,
+    drawer?: anchor.web3.Keypair
+  ) {
+    const randomnessKp = anchor.web3.Keypair.generate();
+    const [randomness, createIx] = await sb.Randomness.create(
+      switchboardProgram,
+      randomnessKp,
+      SWITCHBOARD_QUEUE
+    );
+    await sendSwitchboardTx([createIx], [randomnessKp]);
+
+    const commitIx = await program.methods
+      .commitAWinner()
+      .accounts({
+        payer: drawer ? drawer.publicKey : wallet.publicKey,
+        tokenLottery: lottery,
+        randomnessAccountData: randomness.pubkey,
+      })
+      .instruction();
+    await sendSwitchboardTx(
+      [await randomness.commitIx(SWITCHBOARD_QUEUE), commitIx],
+      drawer ? [drawer] : []
+    );
+
+    const chooseIx = await program.methods
+      .chooseAWinner()
+      .accounts({
+        payer: drawer ? drawer.publicKey : wallet.publicKey,
+        tokenLottery: lottery,
+        randomnessAccountData: randomness.pubkey,
+      })
+      .remainingAccounts(await ticketRecordAccounts())
+      .instruction();
+    return sendSwitchboardTx(
+      [await randomness.revealIx(), chooseIx],
+      drawer ? [drawer] : []
+    );
+  }
+
+  /** Draws a lottery configured with `RandomnessSource::SlotHash`. */
+  async function drawWithSlotHash(lottery: anchor.web3.PublicKey) {
+    const accounts = {
+      tokenLottery: lottery,
+      randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+    };
+    await program.methods.commitAWinner().accounts(accounts).rpc();
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    // The commit slot's hash is only recorded once the slot completes
+    await waitForSlot(randomnessSeedSlot.toNumber() + 1);
+    return program.methods
+      .chooseAWinner()
+      .accounts(accounts)
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("Rejects drawing a lottery with no tickets sold", async () => {
+    const { lotteryEnd, ticketNum } = await program.account.tokenLottery.fetch(
+      otherLotteryAddress
+    );
+    assert.equal(ticketNum.toNumber(), 0);
+    await waitForSlot(lotteryEnd.toNumber());
+    try {
+      await drawWinner(otherLotteryAddress);
+      assert.fail("a lottery without tickets should not be drawn");
+    } catch (err) {
+      assert.include(err.toString(), "NoTicketsSold");
+    }
+  });
+
+  it("Lets anyone commit and draw once the public draw timeout passed", async () => {
+    const PUBLIC_DRAW_TIMEOUT_SLOTS = 5;
+    const { lottery } = await initializeOtherLottery(OTHER_LOTTERY_ID + 2, 10, {
+      publicDrawTimeoutSlots: new anchor.BN(PUBLIC_DRAW_TIMEOUT_SLOTS),
+    });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber() + PUBLIC_DRAW_TIMEOUT_SLOTS);
+    // The authority never commits: the drawer commits the randomness too
+    const drawer = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    await drawWinner(lottery, drawer);
+
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    assert.isTrue(drawn.winnerChosen);
+
+    // The prize of this lottery is never claimed, so it can't be closed
+    try {
+      await program.methods
+        .closeLottery()
+        .accountsPartial({ tokenLottery: lottery, vestingRecord: null })
+        .rpc();
+      assert.fail("lottery with an unclaimed prize should not close");
+    } catch (err) {
+      assert.include(err.toString(), "PrizeNotClaimed");
+    }
+  });
+
+  it("Splits the pot between several winners", async () => {
+    const SPLIT_LOTTERY_ID = OTHER_LOTTERY_ID + 4;
+    try {
+      await initializeOtherLottery(SPLIT_LOTTERY_ID, 10, {
+        numWinners: 2,
+        prizeSplit: [10000, 0, 0, 0],
+      });
+      assert.fail("every winner should get a share of the pot");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidPrizeSplit");
+    }
+
+    const overrides = {
+      numWinners: 2,
+      prizeSplit: [7000, 3000, 0, 0],
+      claimDelaySlots: new anchor.BN(0),
+      vestingSlots: new anchor.BN(0),
+    };
+    const { lottery: undersold } = await initializeOtherLottery(
+      SPLIT_LOTTERY_ID + 1,
+      10,
+      overrides
+    );
+    const { lottery } = await initializeOtherLottery(
+      SPLIT_LOTTERY_ID,
+      10,
+      overrides
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, undersold);
+    for (let i = 0; i < 3; i++) {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    }
+
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+    try {
+      await drawWinner(undersold);
+      assert.fail("two winners should not be drawn from one ticket");
+    } catch (err) {
+      assert.include(err.toString(), "NotEnoughTickets");
+    }
+    await drawWinner(lottery);
+
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    const winners = drawn.winners
+      .slice(0, 2)
+      .map((winner) => winner.toNumber());
+    assert.equal(winners[0], drawn.winner.toNumber());
+    assert.notEqual(winners[0], winners[1]);
+    assert.isTrue(winners.every((winner) => winner < 3));
+
+    const claim = (winnerIndex: number) => {
+      const ticketMint = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          new anchor.BN(SPLIT_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+          new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+          drawn.winners[winnerIndex].toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+      return program.methods
+        .claimPrize(winnerIndex)
+        .accountsPartial({
+          tokenLottery: lottery,
+          ticketMint,
+          vestingRecord: null,
+          badgeMint: null,
+          badgeDestination: null,
+          badgeMetadata: null,
+          badgeMasterEdition: null,
+          badgeCollectionMint: null,
+          badgeCollectionMetadata: null,
+          badgeCollectionMasterEdition: null,
+          badgeCollectionAuthorityRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    };
+
+    const pot = drawn.lotteryPotAmount.toNumber();
+    await claim(0);
+    let claimed = await program.account.tokenLottery.fetch(lottery);
+    const firstShare = Math.floor((pot * 7000) / 10000);
+    assert.equal(claimed.lotteryPotAmount.toNumber(), pot - firstShare);
+
+    try {
+      await claim(0);
+      assert.fail("a winning ticket should only be claimed once");
+    } catch (err) {
+      assert.include(err.toString(), "TicketAlreadyClaimed");
+    }
+    try {
+      await claim(2);
+      assert.fail("only two winners were drawn");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidWinnerIndex");
+    }
+
+    await claim(1);
+    claimed = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(claimed.lotteryPotAmount.toNumber(), 0);
+    assert.deepEqual(claimed.winnersClaimed.slice(0, 2), [true, true]);
+  });
+
+  it("Previews the lamports reclaimed by close_lottery", async () => {
+    // Every prize of the split lottery has been claimed
+    const lottery = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("token_lottery"),
+        new anchor.BN(OTHER_LOTTERY_ID + 4).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const preview = await program.methods
+      .previewClose()
+      .accounts({ tokenLottery: lottery })
+      .view();
+
+    const balanceBefore = await connection.getBalance(wallet.publicKey);
+    const closeSig = await program.methods
+      .closeLottery()
+      .accountsPartial({ tokenLottery: lottery, vestingRecord: null })
+      .rpc({ commitment: "confirmed" });
+    const txInfo = await connection.getTransaction(closeSig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const balanceAfter = await connection.getBalance(
+      wallet.publicKey,
+      "confirmed"
+    );
+
+    assert.isAbove(preview.toNumber(), 0);
+    assert.equal(
+      balanceAfter - balanceBefore + txInfo.meta.fee,
+      preview.toNumber()
+    );
+    assert.isNull(await connection.getAccountInfo(lottery, "confirmed"));
+  });
+
+  it("Rolls an expired prize over to the next round", async () => {
+    const ROLLOVER_LOTTERY_ID = OTHER_LOTTERY_ID + 15;
+    const CLAIM_DEADLINE_SLOTS = 20;
+    const { lottery } = await initializeOtherLottery(ROLLOVER_LOTTERY_ID, 10, {
+      claimDelaySlots: new anchor.BN(0),
+      vestingSlots: new anchor.BN(0),
+      claimDeadlineSlots: new anchor.BN(CLAIM_DEADLINE_SLOTS),
+    });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+    await drawWinner(lottery);
+
+    const rollover = () =>
+      program.methods
+        .rolloverUnclaimed()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await rollover();
+      assert.fail("prize rolled over before the claim deadline");
+    } catch (err) {
+      assert.include(err.toString(), "ClaimNotExpired");
+    }
+
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(
+      drawn.winnerChosenSlot.toNumber() + CLAIM_DEADLINE_SLOTS + 1
+    );
+    try {
+      await program.methods
+        .claimPrize(0)
+        .accountsPartial({
+          tokenLottery: lottery,
+          ticketMint: anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              new anchor.BN(ROLLOVER_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+              new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+              drawn.winners[0].toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+          )[0],
+          vestingRecord: null,
+          badgeMint: null,
+          badgeDestination: null,
+          badgeMetadata: null,
+          badgeMasterEdition: null,
+          badgeCollectionMint: null,
+          badgeCollectionMetadata: null,
+          badgeCollectionMasterEdition: null,
+          badgeCollectionAuthorityRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("prize claimed after the claim deadline");
+    } catch (err) {
+      assert.include(err.toString(), "ClaimExpired");
+    }
+
+    await rollover();
+    const rolled = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(rolled.lotteryPotAmount.toNumber(), 0);
+    assert.equal(
+      rolled.rolloverAmount.toNumber(),
+      drawn.lotteryPotAmount.toNumber()
+    );
+
+    const slot = await connection.getSlot();
+    await program.methods
+      .resetLottery(new anchor.BN(0), new anchor.BN(slot + 1000))
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    const nextRound = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(
+      nextRound.lotteryPotAmount.toNumber(),
+      drawn.lotteryPotAmount.toNumber()
+    );
+    assert.equal(nextRound.rolloverAmount.toNumber(), 0);
+  });
+
+  it("Stops resetting a lottery after max_rounds", async () => {
+    const MAX_ROUNDS = 2;
+    const CLAIM_DEADLINE_SLOTS = 5;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 26,
+      10,
+      {
+        claimDelaySlots: new anchor.BN(0),
+        vestingSlots: new anchor.BN(0),
+        claimDeadlineSlots: new anchor.BN(CLAIM_DEADLINE_SLOTS),
+        maxRounds: new anchor.BN(MAX_ROUNDS),
+      }
+    );
+    const reset = async () => {
+      const slot = await connection.getSlot();
+      return program.methods
+        .resetLottery(new anchor.BN(0), new anchor.BN(slot + 10))
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+    };
+
+    // Roll each round's prize over so the pot is empty for the reset
+    for (let round = 0; round < MAX_ROUNDS; round++) {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+      await waitForSlot(lotteryEnd.toNumber());
+      await drawWinner(lottery);
+      const { winnerChosenSlot } = await program.account.tokenLottery.fetch(
+        lottery
+      );
+      await waitForSlot(winnerChosenSlot.toNumber() + CLAIM_DEADLINE_SLOTS + 1);
+      await program.methods
+        .rolloverUnclaimed()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+      if (round < MAX_ROUNDS - 1) {
+        await reset();
+      }
+    }
+
+    const { roundNumber } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(roundNumber.toNumber(), MAX_ROUNDS - 1);
+    try {
+      await reset();
+      assert.fail("reset past max_rounds");
+    } catch (err) {
+      assert.include(err.toString(), "RoundLimitReached");
+    }
+  });
+
+  it("Pays the creator's share of the prize on claim", async () => {
+    const CREATOR_LOTTERY_ID = OTHER_LOTTERY_ID + 27;
+    const CREATOR_SHARE_BPS = 1000;
+    const creator = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL / 100);
+    try {
+      await initializeOtherLottery(CREATOR_LOTTERY_ID, 10, {
+        creator: creator.publicKey,
+        creatorShareBps: 10000 - FEE_BASIS_POINTS + 1,
+      });
+      assert.fail("creator and fee shares above 100% were accepted");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidCreatorShare");
+    }
+
+    const { lottery } = await initializeOtherLottery(CREATOR_LOTTERY_ID, 10, {
+      claimDelaySlots: new anchor.BN(0),
+      vestingSlots: new anchor.BN(0),
+      randomnessSource: { slotHash: {} },
+      creator: creator.publicKey,
+      creatorShareBps: CREATOR_SHARE_BPS,
+    });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+    const randomnessAccounts = {
+      tokenLottery: lottery,
+      randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+    };
+    await program.methods.commitAWinner().accounts(randomnessAccounts).rpc();
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    await waitForSlot(randomnessSeedSlot.toNumber() + 1);
+    await program.methods
+      .chooseAWinner()
+      .accounts(randomnessAccounts)
+      .rpc({ commitment: "confirmed" });
+
+    const claim = (remainingAccounts: anchor.web3.AccountMeta[]) =>
+      program.methods
+        .claimPrize(0)
+        .accountsPartial({
+          tokenLottery: lottery,
+          ticketMint: anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              new anchor.BN(CREATOR_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+              new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+              new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+            ],
+            program.programId
+          )[0],
+          vestingRecord: null,
+          badgeMint: null,
+          badgeDestination: null,
+          badgeMetadata: null,
+          badgeMasterEdition: null,
+          badgeCollectionMint: null,
+          badgeCollectionMetadata: null,
+          badgeCollectionMasterEdition: null,
+          badgeCollectionAuthorityRecord: null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts(remainingAccounts)
+        .rpc({ commitment: "confirmed" });
+    try {
+      await claim([]);
+      assert.fail("claimed without paying the creator");
+    } catch (err) {
+      assert.include(err.toString(), "IncorrectCreator");
+    }
+
+    const { lotteryPotAmount } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    const creatorBalanceBefore = await connection.getBalance(
+      creator.publicKey,
+      "confirmed"
+    );
+    const sig = await claim([
+      { pubkey: creator.publicKey, isWritable: true, isSigner: false },
+    ]);
+
+    const creatorCut = Math.floor(
+      (lotteryPotAmount.toNumber() * CREATOR_SHARE_BPS) / 10000
+    );
+    assert.equal(
+      (await connection.getBalance(creator.publicKey, "confirmed")) -
+        creatorBalanceBefore,
+      creatorCut
+    );
+    const claimed = (await getEvents(sig)).find(
+      (event) => event.name === "prizeClaimed"
+    );
+    assert.equal(
+      claimed.data.amount.toNumber(),
+      lotteryPotAmount.toNumber() - creatorCut
+    );
+    const after = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(after.lotteryPotAmount.toNumber(), 0);
+  });
+
+  it("Rolls expired token prizes over to the next round", async () => {
+    const CLAIM_DEADLINE_SLOTS = 5;
+    const { mint, price } = paymentTokens[0];
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 30,
+      10,
+      {
+        claimDelaySlots: new anchor.BN(0),
+        vestingSlots: new anchor.BN(0),
+        claimDeadlineSlots: new anchor.BN(CLAIM_DEADLINE_SLOTS),
+        randomnessSource: { slotHash: {} },
+      }
+    );
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+    const payerAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      mint.publicKey,
+      wallet.publicKey
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      mint.publicKey,
+      payerAccount.address,
+      wallet.payer,
+      price
+    );
+    const vaultAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      mint.publicKey,
+      vault,
+      true
+    );
+    const buyIx = await program.methods
+      .buyTicket(new anchor.BN(price), 1)
+      .accountsPartial({
+        tokenLottery: lottery,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        paymentMint: mint.publicKey,
+        payerPaymentAccount: payerAccount.address,
+        vaultPaymentAccount: vaultAccount.address,
+      })
+      .instruction();
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction()
+        .add(buyIx)
+        .add(
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+            units: 300000,
+          })
+        ),
+      [wallet.payer]
+    );
+
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+    await drawWithSlotHash(lottery);
+    const { winnerChosenSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    await waitForSlot(winnerChosenSlot.toNumber() + CLAIM_DEADLINE_SLOTS + 1);
+
+    try {
+      await program.methods
+        .claimTokenPrize(1)
+        .accountsPartial({
+          tokenLottery: lottery,
+          paymentMint: mint.publicKey,
+          vaultPaymentAccount: vaultAccount.address,
+          winnerPaymentAccount: payerAccount.address,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("token prize claimed after the claim deadline");
+    } catch (err) {
+      assert.include(err.toString(), "ClaimExpired");
+    }
+
+    await program.methods
+      .rolloverUnclaimed()
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    const rolled = await program.account.tokenLottery.fetch(lottery);
+    assert.deepEqual(
+      rolled.paymentTokenRollovers.map((rollover) => rollover.toNumber()),
+      [price, 0]
+    );
+    assert.ok(rolled.paymentTokenPots.every((pot) => pot.isZero()));
+
+    const slot = await connection.getSlot();
+    await program.methods
+      .resetLottery(new anchor.BN(0), new anchor.BN(slot + 1000))
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    const nextRound = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(nextRound.roundNumber.toNumber(), 1);
+    assert.deepEqual(
+      nextRound.paymentTokenPots.map((pot) => pot.toNumber()),
+      [price, 0]
+    );
+    assert.ok(nextRound.paymentTokenRollovers.every((pot) => pot.isZero()));
+  });
+
+  it("Bars the authority from buying when authority_cannot_buy is set", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 32,
+      10,
+      { authorityCannotBuy: true }
+    );
+    try {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      assert.fail("authority bought a ticket in its own lottery");
+    } catch (err) {
+      assert.include(err.toString(), "AuthorityCannotParticipate");
+    }
+
+    const buyer = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    await buyTicket(TICKET_PRICE, buyer, lottery);
+    const { ticketNum } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(ticketNum.toNumber(), 1);
+  });
+
+  it("Scales the number of winners with winner_fraction_bps", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 33,
+      20,
+      {
+        winnerFractionBps: 5000,
+        randomnessSource: { slotHash: {} },
+      }
+    );
+    for (let i = 0; i < 4; i++) {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    }
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+    await drawWithSlotHash(lottery);
+
+    // Half of the 4 tickets win, splitting the pot evenly
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(drawn.numWinners, 2);
+    assert.deepEqual(drawn.prizeSplit, [5000, 5000, 0, 0]);
+    assert.notEqual(drawn.winners[0].toNumber(), drawn.winners[1].toNumber());
+  });
+
+  it("Switches a lottery from SOL to a token mint before sales", async () => {
+    const PRICE = 3000;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 37,
+      1000,
+      { paymentTokens: [] }
+    );
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+    const mint = anchor.web3.Keypair.generate();
+    await createMint(connection, wallet.payer, wallet.publicKey, null, 6, mint);
+    const vaultPaymentAccount = getAssociatedTokenAddressSync(
+      mint.publicKey,
+      vault,
+      true
+    );
+    const updatePaymentMint = (paymentIndex: number) =>
+      program.methods
+        .updatePaymentMint(paymentIndex, new anchor.BN(PRICE))
+        .accountsPartial({
+          tokenLottery: lottery,
+          paymentMint: mint.publicKey,
+          vaultPaymentAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await updatePaymentMint(2);
+      assert.fail("set a payment token past the end of the list");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidPaymentToken");
+    }
+    await updatePaymentMint(1);
+
+    const updated = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(updated.paymentTokens.length, 1);
+    assert.ok(updated.paymentTokens[0].mint.equals(mint.publicKey));
+    assert.equal(updated.paymentTokens[0].price.toNumber(), PRICE);
+    assert.deepEqual(updated.paymentTokenDecimals, [6]);
+
+    const payerAccount = await getOrCreateAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      mint.publicKey,
+      wallet.publicKey
+    );
+    await mintTo(
+      connection,
+      wallet.payer,
+      mint.publicKey,
+      payerAccount.address,
+      wallet.payer,
+      PRICE
+    );
+    const buyIx = await program.methods
+      .buyTicket(new anchor.BN(PRICE), 1)
+      .accountsPartial({
+        tokenLottery: lottery,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        paymentMint: mint.publicKey,
+        payerPaymentAccount: payerAccount.address,
+        vaultPaymentAccount,
+      })
+      .instruction();
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction()
+        .add(buyIx)
+        .add(
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+            units: 300000,
+          })
+        ),
+      [wallet.payer]
+    );
+    const vaultAccount = await getAccount(connection, vaultPaymentAccount);
+    assert.equal(Number(vaultAccount.amount), PRICE);
+    const sold = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(sold.paymentTokenPots[0].toNumber(), PRICE);
+
+    try {
+      await updatePaymentMint(1);
+      assert.fail("changed the payment mint after a ticket was sold");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidConfigUpdate");
+    }
+  });
+
+  it("Locks tickets from transfer until sales end", async () => {
+    const LOCKED_LOTTERY_ID = OTHER_LOTTERY_ID + 16;
+    const { lottery } = await initializeOtherLottery(LOCKED_LOTTERY_ID, 20, {
+      resaleLock: true,
+    });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const ticketMint = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        new anchor.BN(LOCKED_LOTTERY_ID).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+        new anchor.BN(0).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+    const ticketAccount = getAssociatedTokenAddressSync(
+      ticketMint,
+      wallet.publicKey
+    );
+    const recipient = await getOrCreateAssociatedTokenAccount(
+      connection,
+      wallet.payer,
+      ticketMint,
+      anchor.web3.Keypair.generate().publicKey
+    );
+    const transferTicket = () =>
+      transfer(
+        connection,
+        wallet.payer,
+        ticketAccount,
+        recipient.address,
+        wallet.payer,
+        1
+      );
+    const unlockTicket = () =>
+      program.methods
+        .unlockTicket(new anchor.BN(0), new anchor.BN(0))
+        .accountsPartial({
+          tokenLottery: lottery,
+          ticketMint,
+          ticketAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    try {
+      await transferTicket();
+      assert.fail("locked ticket transferred");
+    } catch (err) {
+      assert.include(err.toString(), "0x11");
+    }
+    try {
+      await unlockTicket();
+      assert.fail("ticket unlocked before sales ended");
+    } catch (err) {
+      assert.include(err.toString(), "ResaleLocked");
+    }
+
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber() + 1);
+    await unlockTicket();
+    await transferTicket();
+    const received = await getAccount(connection, recipient.address);
+    assert.equal(Number(received.amount), 1);
+  });
+
+  it("Burns the pot of an undersubscribed lottery", async () => {
+    const { lottery } = await initializeOtherLottery(OTHER_LOTTERY_ID + 6, 10, {
+      minTickets: new anchor.BN(2),
+      burnOnFailure: true,
+      refundWindowSlots: new anchor.BN(5),
+    });
+    const vault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), lottery.toBuffer()],
+      program.programId
+    )[0];
+    // Sponsor enough for the incinerator to hold a rent-exempt balance
+    await program.methods
+      .sponsorPot(new anchor.BN(anchor.web3.LAMPORTS_PER_SOL))
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const burnPot = () =>
+      program.methods
+        .burnPot()
+        .accountsPartial({
+          tokenLottery: lottery,
+          incinerator: new anchor.web3.PublicKey(
+            "1nc1nerator11111111111111111111111111111111"
+          ),
+        })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await burnPot();
+      assert.fail("pot burned before the lottery ended");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryNotCompleted");
+    }
+
+    const { lotteryEnd, lotteryPotAmount } =
+      await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber() + 1);
+    const vaultBalanceBefore = await connection.getBalance(vault);
+    await burnPot();
+
+    const burned = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(burned.lotteryPotAmount.toNumber(), 0);
+    assert.isTrue(burned.cancelled);
+    assert.equal(
+      vaultBalanceBefore - (await connection.getBalance(vault)),
+      lotteryPotAmount.toNumber()
+    );
+
+    // Like a cancellation, the burn opens a refund window, after which
+    // what is left can be swept and the lottery closed
+    assert.isAbove(
+      burned.refundDeadlineSlot.toNumber(),
+      lotteryEnd.toNumber()
+    );
+    await waitForSlot(burned.refundDeadlineSlot.toNumber() + 1);
+    await program.methods
+      .sweepUnclaimedRefunds(PAYMENT_INDEX_SOL)
+      .accountsPartial({
+        tokenLottery: lottery,
+        tokenProgram: null,
+        paymentMint: null,
+        vaultPaymentAccount: null,
+        payerPaymentAccount: null,
+      })
+      .rpc({ commitment: "confirmed" });
+    await program.methods
+      .closeLottery()
+      .accountsPartial({ tokenLottery: lottery, vestingRecord: null })
+      .rpc({ commitment: "confirmed" });
+    assert.isNull(await connection.getAccountInfo(lottery, "confirmed"));
+  });
+
+  it("Refuses to draw below min_tickets", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 25,
+      10,
+      {
+        minTickets: new anchor.BN(2),
+        randomnessSource: { slotHash: {} },
+      }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    const accounts = {
+      tokenLottery: lottery,
+      randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+    };
+    await program.methods.commitAWinner().accounts(accounts).rpc();
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    await waitForSlot(randomnessSeedSlot.toNumber() + 1);
+    try {
+      await program.methods.chooseAWinner().accounts(accounts).rpc();
+      assert.fail("drew a winner below min_tickets");
+    } catch (err) {
+      assert.include(err.toString(), "MinimumNotReached");
+    }
+
+    await program.methods
+      .cancelLottery()
+      .accounts({ tokenLottery: lottery })
+      .rpc();
+    const cancelled = await program.account.tokenLottery.fetch(lottery);
+    assert.isTrue(cancelled.cancelled);
+    assert.isFalse(cancelled.winnerChosen);
+  });
+
+  it("Slashes the authority's bond into the pot after a missed draw", async () => {
+    const BOND = anchor.web3.LAMPORTS_PER_SOL / 10;
+    const DRAW_DEADLINE_SLOTS = 5;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 14,
+      10,
+      {
+        authorityBond: new anchor.BN(BOND),
+        drawDeadlineSlots: new anchor.BN(DRAW_DEADLINE_SLOTS),
+      }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+
+    const keeper = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    const slashBond = () =>
+      program.methods
+        .slashBond()
+        .accounts({ tokenLottery: lottery, payer: keeper.publicKey })
+        .signers([keeper])
+        .rpc({ commitment: "confirmed" });
+    try {
+      await slashBond();
+      assert.fail("bond slashed before the draw deadline");
+    } catch (err) {
+      assert.include(err.toString(), "DrawNotOverdue");
+    }
+
+    const { lotteryEnd, lotteryPotAmount } =
+      await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber() + DRAW_DEADLINE_SLOTS + 1);
+    await slashBond();
+
+    const slashed = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(slashed.authorityBond.toNumber(), 0);
+    assert.equal(
+      slashed.lotteryPotAmount.toNumber(),
+      lotteryPotAmount.toNumber() + BOND
+    );
+    try {
+      await program.methods
+        .withdrawBond()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc();
+      assert.fail("bond withdrawn before the pot was paid out");
+    } catch (err) {
+      assert.include(err.toString(), "BondLocked");
+    }
+  });
+
+  it("Refuses to cancel an overdue draw to recover the bond", async () => {
+    const BOND = anchor.web3.LAMPORTS_PER_SOL / 10;
+    const DRAW_DEADLINE_SLOTS = 5;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 38,
+      10,
+      {
+        authorityBond: new anchor.BN(BOND),
+        drawDeadlineSlots: new anchor.BN(DRAW_DEADLINE_SLOTS),
+      }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber() + DRAW_DEADLINE_SLOTS + 1);
+
+    const cancel = () =>
+      program.methods
+        .cancelLottery()
+        .accounts({ tokenLottery: lottery })
+        .rpc({ commitment: "confirmed" });
+    const withdrawBond = () =>
+      program.methods
+        .withdrawBond()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+    try {
+      await cancel();
+      assert.fail("cancelled a lottery with an overdue draw");
+    } catch (err) {
+      assert.include(err.toString(), "DrawOverdue");
+    }
+    try {
+      await withdrawBond();
+      assert.fail("withdrew the bond of an overdue draw");
+    } catch (err) {
+      assert.include(err.toString(), "DrawOverdue");
+    }
+
+    // Once slashed, the bond is in the pot and the lottery can be cancelled
+    await program.methods
+      .slashBond()
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    await cancel();
+    await withdrawBond();
+    const cancelled = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(cancelled.authorityBond.toNumber(), 0);
+    assert.equal(
+      cancelled.lotteryPotAmount.toNumber(),
+      TICKET_PRICE - TICKET_FEE + BOND
+    );
+  });
+
+  it("Keeps the bond locked until the last round", async () => {
+    const BOND = anchor.web3.LAMPORTS_PER_SOL / 10;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 39,
+      10,
+      {
+        authorityBond: new anchor.BN(BOND),
+        drawDeadlineSlots: new anchor.BN(1000),
+        maxRounds: new anchor.BN(2),
+        claimDelaySlots: new anchor.BN(0),
+        vestingSlots: new anchor.BN(0),
+        claimDeadlineSlots: new anchor.BN(1),
+        randomnessSource: { slotHash: {} },
+      }
+    );
+    // Draws the round and rolls its unclaimed pot over, leaving it empty
+    const finishRound = async () => {
+      await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+      const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+      await waitForSlot(lotteryEnd.toNumber());
+      await drawWithSlotHash(lottery);
+      const { winnerChosenSlot } = await program.account.tokenLottery.fetch(
+        lottery
+      );
+      await waitForSlot(winnerChosenSlot.toNumber() + 2);
+      await program.methods
+        .rolloverUnclaimed()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+    };
+    const withdrawBond = () =>
+      program.methods
+        .withdrawBond()
+        .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+        .rpc({ commitment: "confirmed" });
+
+    await finishRound();
+    try {
+      await withdrawBond();
+      assert.fail("bond withdrawn with another round to go");
+    } catch (err) {
+      assert.include(err.toString(), "BondLocked");
+    }
+
+    const slot = await connection.getSlot();
+    await program.methods
+      .resetLottery(new anchor.BN(0), new anchor.BN(slot + 10))
+      .accounts({ tokenLottery: lottery, payer: wallet.publicKey })
+      .rpc({ commitment: "confirmed" });
+    await finishRound();
+    await withdrawBond();
+    const { authorityBond } = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(authorityBond.toNumber(), 0);
+  });
+
+  it("Rejects a malformed randomness account", async () => {
+    const { lottery } = await initializeOtherLottery(OTHER_LOTTERY_ID + 17);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    try {
+      // The lottery's own account is not randomness account data
+      await program.methods
+        .commitAWinner()
+        .accounts({ tokenLottery: lottery, randomnessAccountData: lottery })
+        .rpc();
+      assert.fail("garbage randomness account committed");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRandomnessAccount");
+    }
+  });
+
+  it("Rejects randomness re-committed after the lottery's commit", async () => {
+    const { lottery } = await initializeOtherLottery(OTHER_LOTTERY_ID + 18);
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    const randomnessKp = anchor.web3.Keypair.generate();
+    const [randomness, createIx] = await sb.Randomness.create(
+      switchboardProgram,
+      randomnessKp,
+      SWITCHBOARD_QUEUE
+    );
+    await sendSwitchboardTx([createIx], [randomnessKp]);
+    const commitIx = await program.methods
+      .commitAWinner()
+      .accounts({
+        tokenLottery: lottery,
+        randomnessAccountData: randomness.pubkey,
+      })
+      .instruction();
+    await sendSwitchboardTx([
+      await randomness.commitIx(SWITCHBOARD_QUEUE),
+      commitIx,
+    ]);
+    // The randomness owner commits again, moving the seed slot
+    await sendSwitchboardTx([await randomness.commitIx(SWITCHBOARD_QUEUE)]);
+
+    const chooseIx = await program.methods
+      .chooseAWinner()
+      .accounts({
+        tokenLottery: lottery,
+        randomnessAccountData: randomness.pubkey,
+      })
+      .remainingAccounts(await ticketRecordAccounts())
+      .instruction();
+    try {
+      await sendSwitchboardTx([await randomness.revealIx(), chooseIx]);
+      assert.fail("re-committed randomness revealed a winner");
+    } catch (err) {
+      assert.include(err.toString(), "StaleRandomness");
+    }
+  });
+
+  it("Waits min_confirmations slots after the seed slot to draw", async () => {
+    const MIN_CONFIRMATIONS = 20;
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 21,
+      10,
+      { minConfirmations: new anchor.BN(MIN_CONFIRMATIONS) }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    const randomnessKp = anchor.web3.Keypair.generate();
+    const [randomness, createIx] = await sb.Randomness.create(
+      switchboardProgram,
+      randomnessKp,
+      SWITCHBOARD_QUEUE
+    );
+    await sendSwitchboardTx([createIx], [randomnessKp]);
+    const commitIx = await program.methods
+      .commitAWinner()
+      .accounts({
+        tokenLottery: lottery,
+        randomnessAccountData: randomness.pubkey,
+      })
+      .instruction();
+    await sendSwitchboardTx([
+      await randomness.commitIx(SWITCHBOARD_QUEUE),
+      commitIx,
+    ]);
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+
+    const choose = async () =>
+      sendSwitchboardTx([
+        await randomness.revealIx(),
+        await program.methods
+          .chooseAWinner()
+          .accounts({
+            tokenLottery: lottery,
+            randomnessAccountData: randomness.pubkey,
+          })
+          .instruction(),
+      ]);
+    try {
+      await choose();
+      assert.fail("drew before the randomness was confirmed");
+    } catch (err) {
+      assert.include(err.toString(), "RandomnessNotConfirmed");
+    }
+
+    await waitForSlot(randomnessSeedSlot.toNumber() + MIN_CONFIRMATIONS);
+    await choose();
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    assert.isTrue(drawn.winnerChosen);
+  });
+
+  it("Lets the trusted keeper draw before min_confirmations", async () => {
+    const MIN_CONFIRMATIONS = 50;
+    const keeper = await fundedKeypair(anchor.web3.LAMPORTS_PER_SOL);
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 31,
+      10,
+      {
+        minConfirmations: new anchor.BN(MIN_CONFIRMATIONS),
+        trustedKeeper: keeper.publicKey,
+        randomnessSource: { slotHash: {} },
+      }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    const accounts = {
+      tokenLottery: lottery,
+      randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+    };
+    await program.methods.commitAWinner().accounts(accounts).rpc();
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    await waitForSlot(randomnessSeedSlot.toNumber() + 1);
+
+    try {
+      await program.methods.chooseAWinner().accounts(accounts).rpc();
+      assert.fail("authority drew before the randomness was confirmed");
+    } catch (err) {
+      assert.include(err.toString(), "RandomnessNotConfirmed");
+    }
+
+    await program.methods
+      .chooseAWinner()
+      .accounts({ ...accounts, payer: keeper.publicKey })
+      .signers([keeper])
+      .rpc({ commitment: "confirmed" });
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    assert.isTrue(drawn.winnerChosen);
+    assert.isBelow(
+      drawn.winnerChosenSlot.toNumber(),
+      randomnessSeedSlot.toNumber() + MIN_CONFIRMATIONS
+    );
+  });
+
+  it("Draws a winner from slot-hash randomness", async () => {
+    const { lottery } = await initializeOtherLottery(
+      OTHER_LOTTERY_ID + 23,
+      10,
+      { randomnessSource: { slotHash: {} } }
+    );
+    await buyTicket(TICKET_PRICE, wallet.payer, lottery);
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(lottery);
+    await waitForSlot(lotteryEnd.toNumber());
+
+    try {
+      await program.methods
+        .commitAWinner()
+        .accounts({
+          tokenLottery: lottery,
+          randomnessAccountData: anchor.web3.SYSVAR_CLOCK_PUBKEY,
+        })
+        .rpc();
+      assert.fail("committed an account other than the SlotHashes sysvar");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidRandomnessAccount");
+    }
+
+    await program.methods
+      .commitAWinner()
+      .accounts({
+        tokenLottery: lottery,
+        randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .rpc();
+
+    // The hash is soon public, so discarding it would allow a re-roll
+    try {
+      await program.methods
+        .rerequestRandomness()
+        .accounts({
+          tokenLottery: lottery,
+          randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+        })
+        .rpc();
+      assert.fail("discarded a slot-hash commit");
+    } catch (err) {
+      assert.include(err.toString(), "SlotHashNotRerequestable");
+    }
+
+    const { randomnessSeedSlot } = await program.account.tokenLottery.fetch(
+      lottery
+    );
+    // The commit slot's hash is only recorded once the slot completes
+    await waitForSlot(randomnessSeedSlot.toNumber() + 1);
+
+    await program.methods
+      .chooseAWinner()
+      .accounts({
+        tokenLottery: lottery,
+        randomnessAccountData: anchor.web3.SYSVAR_SLOT_HASHES_PUBKEY,
+      })
+      .rpc();
+    const drawn = await program.account.tokenLottery.fetch(lottery);
+    assert.isTrue(drawn.winnerChosen);
+    assert.equal(drawn.winner.toNumber(), 0);
+    assert.notDeepEqual(drawn.revealedRandomness, new Array(32).fill(0));
+  });
 
-    const blockhashContext = await connection.getLatestBlockhash();
+  it("Buys several tickets in one call", async () => {
+    const lotteryId = OTHER_LOTTERY_ID + 19;
+    const { lottery } = await initializeOtherLottery(lotteryId, 50);
+    const idSeed = new anchor.BN(lotteryId).toArrayLike(Buffer, "le", 8);
+    const roundSeed = new anchor.BN(0).toArrayLike(Buffer, "le", 8);
+    const buyer = wallet.payer.publicKey;
 
-    const tx = new anchor.web3.Transaction({
-      blockhash: blockhashContext.blockhash,
-      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
-      feePayer: wallet.payer.publicKey,
-    })
-      .add(initConfigIx)
-      .add(initLotteryIx);
+    const ticketMints = [0, 1].map(
+      (index) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [
+            idSeed,
+            roundSeed,
+            new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+          ],
+          program.programId
+        )[0]
+    );
+    const ticketAccounts = [0, 1].flatMap((index) => {
+      const ticketMint = ticketMints[index];
+      const ticketRecord = anchor.web3.PublicKey.findProgramAddressSync(
+        [
+          Buffer.from("ticket_record"),
+          idSeed,
+          roundSeed,
+          new anchor.BN(index).toArrayLike(Buffer, "le", 8),
+        ],
+        program.programId
+      )[0];
+      const [metadata, masterEdition] = [[], [Buffer.from("edition")]].map(
+        (suffix) =>
+          anchor.web3.PublicKey.findProgramAddressSync(
+            [
+              Buffer.from("metadata"),
+              TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+              ticketMint.toBuffer(),
+              ...suffix,
+            ],
+            TOKEN_METADATA_PROGRAM_ID
+          )[0]
+      );
+      return [
+        ticketMint,
+        getAssociatedTokenAddressSync(ticketMint, buyer),
+        ticketRecord,
+        metadata,
+        masterEdition,
+      ].map((pubkey) => ({ pubkey, isWritable: true, isSigner: false }));
+    });
+    const treasuryAccounts = treasuries.map(({ keypair }) => ({
+      pubkey: keypair.publicKey,
+      isWritable: true,
+      isSigner: false,
+    }));
 
-    const sig = await anchor.web3.sendAndConfirmTransaction(connection, tx, [
-      wallet.payer,
-    ]);
-    console.log(sig);
-  });
+    const buyMultiple = (count: number, batchAccounts = ticketAccounts) =>
+      program.methods
+        .buyMultipleTickets(new anchor.BN(TICKET_PRICE * count), count)
+        .accountsPartial({
+          tokenLottery: lottery,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .remainingAccounts([...treasuryAccounts, ...batchAccounts])
+        .preInstructions([
+          anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+            units: 1_400_000,
+          }),
+        ]);
 
-  it("Is buying tickets!", async () => {
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
-    await buyTicket();
+    for (const count of [0, 9]) {
+      try {
+        await buyMultiple(count).rpc();
+        assert.fail(`bought a batch of ${count} tickets`);
+      } catch (err) {
+        assert.include(err.toString(), "InvalidTicketCount");
+      }
+    }
+
+    // Tickets out of order, accounts swapped within a ticket, one missing
+    const first = ticketAccounts.slice(0, 5);
+    const second = ticketAccounts.slice(5);
+    const scrambled = [
+      [...second, ...first],
+      [first[0], first[1], first[3], first[2], first[4], ...second],
+      ticketAccounts.slice(0, 9),
+    ];
+    for (const batchAccounts of scrambled) {
+      try {
+        await buyMultiple(2, batchAccounts).rpc();
+        assert.fail("bought a batch with malformed ticket accounts");
+      } catch (err) {
+        assert.include(err.toString(), "IncorrectTicketAccounts");
+      }
+    }
+
+    const signature = await buyMultiple(2).rpc({ commitment: "confirmed" });
+
+    const lotteryConfig = await program.account.tokenLottery.fetch(lottery);
+    assert.equal(lotteryConfig.ticketNum.toNumber(), 2);
+    // One summary event for the whole batch, none per ticket
+    const events = await getEvents(signature);
+    assert.deepEqual(
+      events.map((event) => event.name),
+      ["ticketsPurchased"]
+    );
+    const summary = events[0].data;
+    assert.ok(summary.buyer.equals(buyer));
+    assert.equal(summary.firstTicket.toNumber(), 0);
+    assert.equal(summary.count.toNumber(), 2);
+    assert.equal(summary.totalPrice.toNumber(), TICKET_PRICE * 2);
+    assert.equal(summary.instantWinnings.toNumber(), 0);
+    for (const ticketMint of ticketMints) {
+      const ticketAccount = await getAccount(
+        connection,
+        getAssociatedTokenAddressSync(ticketMint, buyer)
+      );
+      assert.equal(Number(ticketAccount.amount), 1);
+    }
   });
 
   it("Is committing and revealing a winner", async () => {
-    const queue = new anchor.web3.PublicKey(
-      "A43DyUGA7s8eXPxqEjJY6EBu1KKbNgfxF8h17VAHn13w"
-    );
+    const queue = SWITCHBOARD_QUEUE;
 
     const queueAccount = new sb.Queue(switchboardProgram, queue);
     console.log("Queue account", queue.toString());
@@ -188,6 +3530,7 @@ describe("token-lottery", () => {
     const commitIx = await program.methods
       .commitAWinner()
       .accounts({
+        tokenLottery: tokenLotteryAddress,
         randomnessAccountData: randomness.pubkey,
       })
       .instruction();
@@ -213,8 +3556,10 @@ describe("token-lottery", () => {
     const revealIx = await program.methods
       .chooseAWinner()
       .accounts({
+        tokenLottery: tokenLotteryAddress,
         randomnessAccountData: randomness.pubkey,
       })
+      .remainingAccounts(await ticketRecordAccounts())
       .instruction();
 
     const revealTx = await sb.asV0Tx({
@@ -235,11 +3580,257 @@ describe("token-lottery", () => {
     console.log("  Transaction Signature revealTx", revealSignature);
   });
 
-  it("Is claiming a prize", async () => {
-    const tokenLotteryAddress = anchor.web3.PublicKey.findProgramAddressSync(
-      [Buffer.from("token_lottery")],
+  it("Stores the winning ticket mint and wallet", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.isTrue(lotteryConfig.winnerChosen);
+    assert.ok(
+      lotteryConfig.winnerMint.equals(
+        ticketMintAddress(lotteryConfig.roundNumber, lotteryConfig.winner)
+      )
+    );
+
+    const winnerRecord = await program.account.ticketRecord.fetch(
+      ticketRecordAddress(lotteryConfig.roundNumber, lotteryConfig.winner)
+    );
+    assert.ok(lotteryConfig.winnerWallet.equals(winnerRecord.buyer));
+    assert.ok(
+      lotteryConfig.winnerAddress.equals(anchor.web3.PublicKey.default)
+    );
+    assert.ok(winnerRecord.ticketMint.equals(lotteryConfig.winnerMint));
+  });
+
+  async function verifyDraw(revealedRandomness: number[], ticketNum: anchor.BN) {
+    const sig = await program.methods
+      .verifyDraw(revealedRandomness, ticketNum)
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+    const [event] = await getEvents(sig);
+    assert.equal(event.name, "drawVerified");
+    return event.data;
+  }
+
+  it("Verifies the draw from the stored randomness", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const randomness = Array.from(lotteryConfig.revealedRandomness);
+
+    const valid = await verifyDraw(randomness, lotteryConfig.ticketNum);
+    assert.isTrue(valid.matches);
+    assert.ok(valid.derivedWinner.eq(lotteryConfig.winner));
+
+    const tampered = [...randomness];
+    tampered[0] ^= 1;
+    const mismatch = await verifyDraw(tampered, lotteryConfig.ticketNum);
+    assert.isFalse(mismatch.matches);
+  });
+
+  it("Refuses to burn the winning ticket", async () => {
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    try {
+      await program.methods
+        .claimConsolation(lotteryConfig.winner)
+        .accounts({
+          tokenLottery: tokenLotteryAddress,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("winning ticket should not be burned");
+    } catch (err) {
+      assert.include(err.toString(), "CannotBurnWinningTicket");
+    }
+  });
+
+  it("Burns a losing ticket for a consolation", async () => {
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const losingTicket = before.winner.toNumber() === 0 ? 1 : 0;
+
+    await program.methods
+      .claimConsolation(new anchor.BN(losingTicket))
+      .accounts({
+        tokenLottery: tokenLotteryAddress,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .rpc();
+
+    const after = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(
+      before.lotteryPotAmount.sub(after.lotteryPotAmount).toNumber(),
+      CONSOLATION_AMOUNT
+    );
+  });
+
+  function vestingRecordAddress(round: number | anchor.BN) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("vesting"),
+        lotteryIdSeed,
+        new anchor.BN(round).toArrayLike(Buffer, "le", 8),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function badgeMintAddress(round: number | anchor.BN, winnerIndex = 0) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("winner_badge"),
+        lotteryIdSeed,
+        new anchor.BN(round).toArrayLike(Buffer, "le", 8),
+        Buffer.from([winnerIndex]),
+      ],
+      program.programId
+    )[0];
+  }
+
+  function metadataAddress(
+    mint: anchor.web3.PublicKey,
+    suffix: Buffer[] = []
+  ) {
+    return anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        Buffer.from("metadata"),
+        TOKEN_METADATA_PROGRAM_ID.toBuffer(),
+        mint.toBuffer(),
+        ...suffix,
+      ],
+      TOKEN_METADATA_PROGRAM_ID
+    )[0];
+  }
+
+  /** Reads the name, symbol and URI of a mint's Metaplex metadata. */
+  async function metadataStrings(mint: anchor.web3.PublicKey) {
+    const { data } = await connection.getAccountInfo(metadataAddress(mint));
+    // Metadata: key, update_authority, mint, name, symbol, uri
+    let offset = 1 + 32 + 32;
+    return [0, 1, 2].map(() => {
+      const length = data.readUInt32LE(offset);
+      const value = data
+        .subarray(offset + 4, offset + 4 + length)
+        .toString()
+        .replace(/\0/g, "");
+      offset += 4 + length;
+      return value;
+    });
+  }
+
+  async function claimPrize(
+    tokenProgram = TOKEN_PROGRAM_ID,
+    lotteryVault = lotteryVaultAddress
+  ) {
+    const { roundNumber, winner } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const badgeMint = badgeMintAddress(roundNumber);
+    const claimIx = await program.methods
+      .claimPrize(0)
+      .accountsPartial({
+        tokenLottery: tokenLotteryAddress,
+        ticketMint: ticketMintAddress(roundNumber, winner),
+        vestingRecord: vestingRecordAddress(roundNumber),
+        badgeMint,
+        badgeDestination: getAssociatedTokenAddressSync(
+          badgeMint,
+          wallet.publicKey
+        ),
+        badgeMetadata: metadataAddress(badgeMint),
+        badgeMasterEdition: metadataAddress(badgeMint, [Buffer.from("edition")]),
+        badgeCollectionMint: collectionMintAddress,
+        badgeCollectionMetadata: metadataAddress(collectionMintAddress),
+        badgeCollectionMasterEdition: metadataAddress(collectionMintAddress, [
+          Buffer.from("edition"),
+        ]),
+        badgeCollectionAuthorityRecord: collectionAuthorityRecordAddress,
+        lotteryVault,
+        tokenProgram,
+      })
+      .instruction();
+
+    const computeIx = anchor.web3.ComputeBudgetProgram.setComputeUnitLimit({
+      units: 400000,
+    });
+
+    const blockhashContext = await connection.getLatestBlockhash();
+
+    const claimTx = new anchor.web3.Transaction({
+      blockhash: blockhashContext.blockhash,
+      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
+      feePayer: wallet.payer.publicKey,
+    })
+      .add(claimIx)
+      .add(computeIx);
+
+    return anchor.web3.sendAndConfirmTransaction(connection, claimTx, [
+      wallet.payer,
+    ]);
+  }
+
+  it("Rejects claims during the claim cooldown", async () => {
+    try {
+      await claimPrize();
+      assert.fail("claim should fail during the cooldown");
+    } catch (err) {
+      assert.include(err.toString(), "ClaimCooldownActive");
+    }
+  });
+
+  it("Rejects a claim with a mismatched token program", async () => {
+    try {
+      await claimPrize(TOKEN_2022_PROGRAM_ID);
+      assert.fail("claim should fail with a token program not owning the mint");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintMintTokenProgram");
+    }
+  });
+
+  it("Rejects another lottery's vault", async () => {
+    const otherVault = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), otherLotteryAddress.toBuffer()],
       program.programId
     )[0];
+    try {
+      await program.methods
+        .buyTicket(new anchor.BN(TICKET_PRICE), PAYMENT_INDEX_SOL)
+        .accountsPartial({
+          tokenLottery: tokenLotteryAddress,
+          lotteryVault: otherVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("bought a ticket into another lottery's vault");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintSeeds");
+    }
+    try {
+      await claimPrize(TOKEN_PROGRAM_ID, otherVault);
+      assert.fail("claimed a prize from another lottery's vault");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintSeeds");
+    }
+    try {
+      await program.methods
+        .refundTicket(new anchor.BN(0))
+        .accountsPartial({
+          tokenLottery: tokenLotteryAddress,
+          lotteryVault: otherVault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc();
+      assert.fail("refunded a ticket from another lottery's vault");
+    } catch (err) {
+      assert.include(err.toString(), "ConstraintSeeds");
+    }
+  });
+
+  it("Is claiming a prize", async () => {
     const lotteryConfig = await program.account.tokenLottery.fetch(
       tokenLotteryAddress
     );
@@ -255,10 +3846,10 @@ describe("token-lottery", () => {
       console.log("Token account address", account.pubkey.toBase58());
     });
 
-    const winningMint = anchor.web3.PublicKey.findProgramAddressSync(
-      [new anchor.BN(lotteryConfig.winner).toArrayLike(Buffer, "le", 8)],
-      program.programId
-    )[0];
+    const winningMint = ticketMintAddress(
+      lotteryConfig.roundNumber,
+      lotteryConfig.winner
+    );
     console.log("Winning mint", winningMint.toBase58());
 
     const winningTokenAddress = getAssociatedTokenAddressSync(
@@ -267,26 +3858,335 @@ describe("token-lottery", () => {
     );
     console.log("Winning token address", winningTokenAddress.toBase58());
 
-    const claimIx = await program.methods
-      .claimPrize()
+    await waitForSlot(
+      lotteryConfig.winnerChosenSlot.toNumber() + CLAIM_DELAY_SLOTS
+    );
+
+    const claimSig = await claimPrize();
+    console.log(claimSig);
+
+    const claimed = (await getEvents(claimSig)).find(
+      (event) => event.name === "prizeClaimed"
+    );
+    assert.ok(claimed.data.winner.equals(wallet.publicKey));
+    assert.ok(claimed.data.amount.eq(lotteryConfig.lotteryPotAmount));
+
+    const { winnerAddress } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.ok(winnerAddress.equals(wallet.publicKey));
+  });
+
+  it("Mints a verified winner badge on claim", async () => {
+    const badgeMint = badgeMintAddress(0);
+    const badgeAccount = await getAccount(
+      connection,
+      getAssociatedTokenAddressSync(badgeMint, wallet.publicKey)
+    );
+    assert.equal(Number(badgeAccount.amount), 1);
+
+    const { data } = await connection.getAccountInfo(
+      metadataAddress(badgeMint)
+    );
+    const collection = decodeMetadataCollection(data);
+    assert.isNotNull(collection);
+    assert.ok(collection.key.equals(collectionMintAddress));
+    assert.isTrue(collection.verified);
+
+    // Named after the lottery, round and ticket, with the lottery's symbol
+    const { winner, symbol } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    const [name, badgeSymbol] = await metadataStrings(badgeMint);
+    assert.equal(name, `Winner #${LOTTERY_ID}-${winner}`);
+    assert.equal(badgeSymbol, symbol);
+  });
+
+  async function claimVested(round: number) {
+    const sig = await program.methods
+      .claimVested()
       .accounts({
-        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenLottery: tokenLotteryAddress,
+        vestingRecord: vestingRecordAddress(round),
       })
-      .instruction();
+      .rpc({ commitment: "confirmed" });
+    const tx = await connection.getTransaction(sig, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    return tx.slot;
+  }
 
-    const blockhashContext = await connection.getLatestBlockhash();
+  it("Claims a vested prize in installments", async () => {
+    const vesting = await program.account.vestingRecord.fetch(
+      vestingRecordAddress(0)
+    );
+    assert.ok(vesting.beneficiary.equals(wallet.publicKey));
+    assert.equal(vesting.claimed.toNumber(), 0);
+    const total = vesting.total.toNumber();
+    const startSlot = vesting.startSlot.toNumber();
 
-    const claimTx = new anchor.web3.Transaction({
-      blockhash: blockhashContext.blockhash,
-      lastValidBlockHeight: blockhashContext.lastValidBlockHeight,
-      feePayer: wallet.payer.publicKey,
-    }).add(claimIx);
+    await waitForSlot(startSlot + VESTING_SLOTS / 2);
+    const firstSlot = await claimVested(0);
+    const firstInstallment = Math.floor(
+      (total * Math.min(firstSlot - startSlot, VESTING_SLOTS)) / VESTING_SLOTS
+    );
+    let claimed = await program.account.vestingRecord.fetch(
+      vestingRecordAddress(0)
+    );
+    assert.equal(claimed.claimed.toNumber(), firstInstallment);
 
-    const claimSig = await anchor.web3.sendAndConfirmTransaction(
-      connection,
-      claimTx,
-      [wallet.payer]
+    await waitForSlot(startSlot + VESTING_SLOTS);
+    const vaultBalanceBefore = await connection.getBalance(
+      lotteryVaultAddress
     );
-    console.log(claimSig);
+    await claimVested(0);
+    const vaultBalanceAfter = await connection.getBalance(lotteryVaultAddress);
+    assert.equal(
+      vaultBalanceBefore - vaultBalanceAfter,
+      total - firstInstallment
+    );
+    claimed = await program.account.vestingRecord.fetch(
+      vestingRecordAddress(0)
+    );
+    assert.equal(claimed.claimed.toNumber(), total);
+  });
+
+  it("Pays the token pots to the winner", async () => {
+    try {
+      await program.methods
+        .resetLottery(new anchor.BN(0), new anchor.BN(0))
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      assert.fail("round should not reset with unclaimed token pots");
+    } catch (err) {
+      assert.include(err.toString(), "PrizeNotClaimed");
+    }
+
+    for (const [index, { mint, price }] of paymentTokens.entries()) {
+      const winnerAccount = getAssociatedTokenAddressSync(
+        mint.publicKey,
+        wallet.publicKey
+      );
+      await program.methods
+        .claimTokenPrize(index + 1)
+        .accountsPartial({
+          tokenLottery: tokenLotteryAddress,
+          paymentMint: mint.publicKey,
+          vaultPaymentAccount: vaultPaymentAccounts[index],
+          winnerPaymentAccount: winnerAccount,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+      const winnerBalance = await getAccount(connection, winnerAccount);
+      assert.equal(Number(winnerBalance.amount), price);
+    }
+
+    const lotteryConfig = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.ok(lotteryConfig.paymentTokenPots.every((pot) => pot.isZero()));
+  });
+
+  async function resetLottery() {
+    const slot = await connection.getSlot();
+    return program.methods
+      .resetLottery(new anchor.BN(0), new anchor.BN(slot + 10))
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+  }
+
+  it("Runs consecutive rounds", async () => {
+    for (const expectedRound of [1, 2]) {
+      const resetSig = await resetLottery();
+      const [resetEvent] = await getEvents(resetSig);
+      assert.equal(resetEvent.name, "lotteryReset");
+      assert.equal(resetEvent.data.roundNumber.toNumber(), expectedRound);
+
+      const lotteryConfig = await program.account.tokenLottery.fetch(
+        tokenLotteryAddress
+      );
+      assert.equal(lotteryConfig.roundNumber.toNumber(), expectedRound);
+      assert.equal(lotteryConfig.ticketNum.toNumber(), 0);
+
+      await buyTicket();
+      try {
+        await drawWinner();
+        assert.fail("randomness seeded before lottery_end should be rejected");
+      } catch (err) {
+        assert.include(err.toString(), "RandomnessPredatesDrawWindow");
+      }
+
+      // Buying right after the reset lands in the anti-snipe window.
+      const { lotteryEnd } = await program.account.tokenLottery.fetch(
+        tokenLotteryAddress
+      );
+      await waitForSlot(lotteryEnd.toNumber());
+      const drawSig = await drawWinner();
+
+      const drawn = await program.account.tokenLottery.fetch(
+        tokenLotteryAddress
+      );
+      const winnerEvent = (await getEvents(drawSig)).find(
+        (event) => event.name === "winnerChosen"
+      );
+      assert.ok(winnerEvent.data.winner.eq(drawn.winner));
+      assert.ok(winnerEvent.data.winnerWallet.equals(wallet.publicKey));
+      assert.ok(winnerEvent.data.totalTickets.eq(drawn.ticketNum));
+      assert.equal(winnerEvent.data.totalTickets.toNumber(), 1);
+      await waitForSlot(drawn.winnerChosenSlot.toNumber() + CLAIM_DELAY_SLOTS);
+      await claimPrize();
+    }
+  });
+
+  it("Names the tickets of every round uniquely", async () => {
+    const [roundOneName, roundTwoName] = await Promise.all(
+      [1, 2].map(async (round) => {
+        const { data } = await connection.getAccountInfo(
+          metadataAddress(ticketMintAddress(round, 0))
+        );
+        // Metadata: key, update_authority, mint, name
+        const nameLength = data.readUInt32LE(1 + 32 + 32);
+        return data
+          .subarray(1 + 32 + 32 + 4, 1 + 32 + 32 + 4 + nameLength)
+          .toString()
+          .replace(/\0/g, "");
+      })
+    );
+    assert.equal(roundOneName, "Token Lottery Ticket #1-0");
+    assert.equal(roundTwoName, "Token Lottery Ticket #2-0");
+  });
+
+  // Decodes the events named `name` as documented in events.rs, without the
+  // IDL, returning the bytes following each discriminator.
+  async function loggedEventData(signature: string, name: string) {
+    const txInfo = await connection.getTransaction(signature, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    const discriminator = Buffer.from(
+      anchor.utils.sha256.hash(`event:${name}`),
+      "hex"
+    ).subarray(0, 8);
+    return txInfo.meta.logMessages
+      .filter((log) => log.startsWith("Program data: "))
+      .map((log) => Buffer.from(log.slice("Program data: ".length), "base64"))
+      .filter((data) => data.subarray(0, 8).equals(discriminator))
+      .map((data) => data.subarray(8));
+  }
+
+  it("Logs WinnerChosen and PrizeClaimed in the documented schema", async () => {
+    const resetSig = await resetLottery();
+    const { roundNumber } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await buyTicket();
+    const { lotteryEnd } = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await waitForSlot(lotteryEnd.toNumber());
+    const drawSig = await drawWinner();
+    const drawn = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await waitForSlot(drawn.winnerChosenSlot.toNumber() + CLAIM_DELAY_SLOTS);
+    const claimSig = await claimPrize();
+
+    assert.isEmpty(await loggedEventData(resetSig, "WinnerChosen"));
+    const [winnerChosen] = await loggedEventData(drawSig, "WinnerChosen");
+    const u64At = (data: Buffer, offset: number) =>
+      new anchor.BN(data.subarray(offset, offset + 8), "le");
+    assert.ok(
+      new anchor.web3.PublicKey(winnerChosen.subarray(0, 32)).equals(
+        tokenLotteryAddress
+      )
+    );
+    assert.ok(u64At(winnerChosen, 32).eq(roundNumber));
+    assert.ok(u64At(winnerChosen, 40).eq(drawn.winner));
+    assert.ok(
+      new anchor.web3.PublicKey(winnerChosen.subarray(48, 80)).equals(
+        wallet.publicKey
+      )
+    );
+    const winnerCount = winnerChosen.readUInt32LE(80);
+    assert.equal(winnerCount, 1);
+    assert.ok(u64At(winnerChosen, 84).eq(drawn.winner));
+    assert.ok(u64At(winnerChosen, 84 + 8 * winnerCount).eq(drawn.ticketNum));
+
+    const [prizeClaimed] = await loggedEventData(claimSig, "PrizeClaimed");
+    assert.ok(
+      new anchor.web3.PublicKey(prizeClaimed.subarray(0, 32)).equals(
+        tokenLotteryAddress
+      )
+    );
+    assert.ok(u64At(prizeClaimed, 32).eq(roundNumber));
+    assert.ok(
+      new anchor.web3.PublicKey(prizeClaimed.subarray(40, 72)).equals(
+        wallet.publicKey
+      )
+    );
+    assert.ok(u64At(prizeClaimed, 72).eq(drawn.lotteryPotAmount));
+  });
+
+  it("Extends lottery_end for tickets bought near the end", async () => {
+    const slot = await connection.getSlot();
+    await program.methods
+      .resetLottery(new anchor.BN(0), new anchor.BN(slot + 30))
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+
+    await buyTicket();
+    const early = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.ok(early.lotteryEnd.eq(before.lotteryEnd));
+
+    await waitForSlot(before.lotteryEnd.toNumber() - ANTI_SNIPE_SLOTS + 2);
+    await buyTicket();
+    const after = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.equal(
+      after.lotteryEnd.sub(before.lotteryEnd).toNumber(),
+      ANTI_SNIPE_SLOTS
+    );
+    assert.equal(after.antiSnipeExtension.toNumber(), ANTI_SNIPE_SLOTS);
+  });
+
+  it("Ends ticket sales early", async () => {
+    const before = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    await program.methods
+      .endSalesEarly()
+      .accounts({ tokenLottery: tokenLotteryAddress })
+      .rpc({ commitment: "confirmed" });
+
+    const ended = await program.account.tokenLottery.fetch(
+      tokenLotteryAddress
+    );
+    assert.ok(ended.lotteryEnd.lt(before.lotteryEnd));
+
+    await waitForSlot(ended.lotteryEnd.toNumber() + 1);
+    try {
+      await buyTicket();
+      assert.fail("ticket bought after sales ended");
+    } catch (err) {
+      assert.include(err.toString(), "LotteryNotOpen");
+    }
+
+    try {
+      await program.methods
+        .endSalesEarly()
+        .accounts({ tokenLottery: tokenLotteryAddress })
+        .rpc();
+      assert.fail("ended sales should not be ended again");
+    } catch (err) {
+      assert.include(err.toString(), "SalesAlreadyEnded");
+    }
   });
 });